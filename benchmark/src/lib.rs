@@ -1,23 +1,31 @@
 extern crate hwloc;
 extern crate libc;
 extern crate regex;
+extern crate serde_derive;
+extern crate toml;
 
-use faster_rs::FasterKv;
+mod perf;
+
+pub use crate::perf::{PerfCounters, PerfCounts};
+use faster_rs::{status, FasterKey, FasterKv, ReadHandle, RefreshSchedule};
 use hwloc::{CpuSet, ObjectType, Topology, CPUBIND_THREAD};
 use regex::Regex;
-use std::fs::File;
+use serde_derive::Deserialize;
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::prelude::FileExt;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Barrier, Mutex};
 use std::time::{Duration, Instant};
 
 const K_CHECKPOINT_SECONDS: u64 = 30;
-const K_COMPLETE_PENDING_INTERVAL: usize = 1600;
-const K_REFRESH_INTERVAL: usize = 64;
+// Pending operations are completed every 1600 operations
+const K_COMPLETE_PENDING_REFRESHES: u64 = 25;
+const K_REFRESH_INTERVAL: u64 = 64;
 const K_RUN_TIME: u64 = 360;
-const K_CHUNK_SIZE: usize = 3200;
+const K_MIN_CHUNK_SIZE: usize = 64;
+const K_CHUNKS_PER_THREAD: usize = 4;
 const K_FILE_CHUNK_SIZE: usize = 131072;
 const K_INIT_COUNT: usize = 250000000;
 const K_TXN_COUNT: usize = 1000000000;
@@ -26,10 +34,660 @@ const K_NANOS_PER_SECOND: usize = 1000000000;
 
 const K_THREAD_STACK_SIZE: usize = 4 * 1024 * 1024;
 
+/// In-memory log size for disk-resident runs, a fraction of the space taken by the
+/// initial keys so that most reads have to go to disk
+pub const K_DISK_RESIDENT_LOG_SIZE: u64 = 1 << 30;
+
+pub const K_DEFAULT_CHUNK_SIZE: usize = 3200;
+pub const K_DEFAULT_TABLE_SIZE: u64 = 134217728;
+pub const K_DEFAULT_LOG_SIZE: u64 = 17179869184;
+pub const K_STORAGE_DIR: &str = "benchmark_store";
+
+#[derive(Clone, Copy)]
 pub enum Operation {
     Read,
     Upsert,
     Rmw,
+    Delete,
+    /// Operation defined outside the benchmark, see [CustomOperation](trait.CustomOperation.html)
+    Custom(&'static dyn CustomOperation),
+}
+
+/// Operation issued by a workload in addition to the built-in ones, e.g. a batch or an
+/// application-specific RMW. Operations are kept in a `static` and handed out by reference,
+/// so allocating them costs no more than allocating a built-in operation.
+/// They are counted together as custom operations.
+pub trait CustomOperation: Sync {
+    /// Issues the operation on `key`, formatted according to `key_format` (see
+    /// [string_key](fn.string_key.html)), and returns FASTER's status
+    fn execute(&self, store: &FasterKv, key: u64, key_format: KeyFormat, serial: u64) -> u8;
+}
+
+pub type OpAllocator = fn(usize, &mut Rng) -> Operation;
+
+/// Decides which operation is issued for every transaction of a phase and on which key.
+/// Implemented by every `Fn(usize, &mut Rng) -> Operation`, such as the built-in workloads.
+pub trait Workload: Send + Sync {
+    /// Operation to issue for the transaction at `key_idx` in the run file
+    fn next_op(&self, key_idx: usize, rng: &mut Rng) -> Operation;
+
+    /// Key to issue the transaction at `key_idx` on. Defaults to the key in the run file,
+    /// override it to draw keys from a different distribution.
+    fn next_key(&self, key_idx: usize, keys: &[u64], _rng: &mut Rng) -> u64 {
+        keys[key_idx]
+    }
+}
+
+impl<F> Workload for F
+where
+    F: Fn(usize, &mut Rng) -> Operation + Send + Sync,
+{
+    fn next_op(&self, key_idx: usize, rng: &mut Rng) -> Operation {
+        self(key_idx, rng)
+    }
+}
+
+/// Representation of the keys handed to the store
+#[derive(Clone, Copy, PartialEq)]
+pub enum KeyFormat {
+    U64,
+    /// `u64` keys and `i32` values copied into the store without serializing them
+    U64Pod,
+    /// YCSB-style 24 byte keys such as `user00000000000000000042`
+    String,
+}
+
+impl KeyFormat {
+    pub fn parse(format: &str) -> Result<KeyFormat, String> {
+        match format {
+            "u64" => Ok(KeyFormat::U64),
+            "u64-pod" => Ok(KeyFormat::U64Pod),
+            "string" => Ok(KeyFormat::String),
+            _ => Err(format!("Unknown key format {}", format)),
+        }
+    }
+}
+
+pub const KEY_FORMATS: &[&str] = &["u64", "u64-pod", "string"];
+
+/// String key of `key` as the `string` key format stores it
+pub fn string_key(key: u64) -> String {
+    StringKey::new().format(key).clone()
+}
+
+// Formats string keys into a buffer reused across operations, so the benchmark measures
+// serializing and hashing the keys rather than allocating them
+struct StringKey {
+    buffer: String,
+}
+
+impl StringKey {
+    fn new() -> StringKey {
+        StringKey {
+            buffer: String::with_capacity(24),
+        }
+    }
+
+    fn format(&mut self, key: u64) -> &String {
+        self.buffer.clear();
+        write!(self.buffer, "user{:020}", key).unwrap();
+        &self.buffer
+    }
+}
+
+fn execute<K: FasterKey>(store: &FasterKv, key: &K, op: &Operation, serial: u64) -> u8 {
+    match op {
+        Operation::Read => {
+            let (status, _): (u8, ReadHandle<i32>) = store.read(key, serial);
+            status
+        }
+        Operation::Upsert => store.upsert(key, &42, serial),
+        Operation::Rmw => store.rmw(key, &5, serial),
+        Operation::Delete => store.delete(key, serial),
+        Operation::Custom(_) => unreachable!("Custom operations execute themselves"),
+    }
+}
+
+// Like execute, but skips serialization. The records are the same execute writes.
+fn execute_pod(store: &FasterKv, key: u64, op: &Operation, serial: u64) -> u8 {
+    match op {
+        Operation::Read => {
+            let (status, _): (u8, ReadHandle<i32>) = store.read_pod(&key, serial);
+            status
+        }
+        Operation::Upsert => store.upsert_pod(&key, &42i32, serial),
+        Operation::Rmw => store.rmw_pod(&key, &5i32, serial),
+        Operation::Delete => store.delete_pod(&key, serial),
+        Operation::Custom(_) => unreachable!("Custom operations execute themselves"),
+    }
+}
+
+/// Operations issued by benchmark threads
+#[derive(Clone, Copy, Default)]
+pub struct OpCounts {
+    pub reads: usize,
+    pub upserts: usize,
+    pub rmws: usize,
+    pub deletes: usize,
+    /// Custom operations of any kind
+    pub custom: usize,
+    /// Reads that went pending because the record had to be fetched from disk
+    pub pending_reads: usize,
+}
+
+impl OpCounts {
+    pub fn total(&self) -> usize {
+        self.reads + self.upserts + self.rmws + self.deletes + self.custom
+    }
+
+    fn add(&mut self, other: &OpCounts) {
+        self.reads += other.reads;
+        self.upserts += other.upserts;
+        self.rmws += other.rmws;
+        self.deletes += other.deletes;
+        self.custom += other.custom;
+        self.pending_reads += other.pending_reads;
+    }
+
+    fn print_other_ops(&self) {
+        if self.deletes + self.custom > 0 {
+            println!(
+                "{} deletes, {} custom operations",
+                self.deletes, self.custom
+            );
+        }
+    }
+
+    fn print_disk_reads(&self, duration: Duration, num_threads: u8) {
+        if self.reads == 0 {
+            return;
+        }
+        println!(
+            "{} of {} reads went to disk ({:.2}%), {} disk reads/second/thread",
+            self.pending_reads,
+            self.reads,
+            100.0 * self.pending_reads as f64 / self.reads as f64,
+            self.pending_reads as f64 / duration.as_secs_f64() / num_threads as f64
+        );
+    }
+}
+
+pub const WORKLOADS: &[&str] = &[
+    "read_100",
+    "read_upsert_50_50",
+    "read_upsert_95_5",
+    "rmw_100",
+    "upsert_100",
+];
+
+/// Period of a benchmark run during which operations are allocated by a single workload
+#[derive(Clone)]
+pub struct Phase {
+    pub workload: String,
+    pub op_allocator: Arc<dyn Workload>,
+    pub duration: Duration,
+}
+
+impl Phase {
+    pub fn new(workload: &str, duration: Duration) -> Result<Phase, String> {
+        let op_allocator: OpAllocator = match workload {
+            "read_100" => read_100,
+            "read_upsert_50_50" => read_upsert5050,
+            "read_upsert_95_5" => read_upsert9505,
+            "rmw_100" => rmw_100,
+            "upsert_100" => upsert_100,
+            _ => {
+                return Err(format!(
+                    "Unexpected workload {}. Options are: {}",
+                    workload,
+                    WORKLOADS.join(", ")
+                ))
+            }
+        };
+        Ok(Phase::custom(workload, op_allocator, duration))
+    }
+
+    /// Phase running a workload defined outside the benchmark, reported as `name`
+    pub fn custom<W: Workload + 'static>(name: &str, workload: W, duration: Duration) -> Phase {
+        Phase {
+            workload: String::from(name),
+            op_allocator: Arc::new(workload),
+            duration,
+        }
+    }
+
+    /// Single phase running `workload` for the default run time
+    pub fn single(workload: &str) -> Result<Phase, String> {
+        Phase::new(workload, Duration::from_secs(K_RUN_TIME))
+    }
+}
+
+// Parses a schedule of the form `<workload>:<seconds>,<workload>:<seconds>,...`
+pub fn parse_phases(schedule: &str) -> Result<Vec<Phase>, String> {
+    let mut phases = Vec::new();
+    for phase in schedule.split(',').map(|phase| phase.trim()) {
+        let mut parts = phase.splitn(2, ':');
+        let workload = parts.next().unwrap_or("");
+        let seconds: u64 = match parts.next().map(|seconds| seconds.parse()) {
+            Some(Ok(seconds)) if seconds > 0 => seconds,
+            _ => {
+                return Err(format!(
+                    "Invalid phase {}, expected <workload>:<seconds>",
+                    phase
+                ))
+            }
+        };
+        phases.push(Phase::new(workload, Duration::from_secs(seconds))?);
+    }
+    Ok(phases)
+}
+
+/// Parameters of a benchmark run, given on the command line or in an experiment file
+pub struct RunConfig {
+    pub load_keys_file: String,
+    pub run_keys_file: String,
+    pub thread_counts: Vec<u8>,
+    pub repopulate: bool,
+    pub chunk_size: usize,
+    pub table_sizes: Vec<u64>,
+    pub log_size: u64,
+    pub seed: u64,
+    pub key_format: KeyFormat,
+    pub open_loop_rate: Option<u64>,
+    pub phases: Vec<Phase>,
+    pub storage_dir: String,
+    /// File to write the throughput of every configuration to as CSV
+    pub results_file: Option<String>,
+    /// File to write the memory usage sampled every second to as CSV
+    pub memory_file: Option<String>,
+}
+
+// Experiment file as written by users. Fields mirror the options of the `run` subcommand.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ExperimentFile {
+    load: String,
+    run: String,
+    threads: Vec<u8>,
+    repopulate: Option<bool>,
+    chunk_size: Option<usize>,
+    table_sizes: Option<Vec<u64>>,
+    log_size: Option<u64>,
+    disk_resident: Option<bool>,
+    seed: Option<u64>,
+    key_format: Option<String>,
+    rate: Option<u64>,
+    workload: Option<String>,
+    phases: Option<Vec<PhaseFile>>,
+    storage_dir: Option<String>,
+    results: Option<String>,
+    memory_samples: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhaseFile {
+    workload: String,
+    seconds: u64,
+}
+
+impl RunConfig {
+    /// Reads a TOML experiment description, so that a run can be reproduced by sharing a
+    /// single file. Options that are left out take the same defaults as on the command line.
+    pub fn from_file(path: &str) -> Result<RunConfig, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Unable to read {}: {}", path, err))?;
+        let file: ExperimentFile = toml::from_str(&contents)
+            .map_err(|err| format!("Invalid experiment file {}: {}", path, err))?;
+        let phases = match (file.workload, file.phases) {
+            (Some(workload), None) => vec![Phase::single(&workload)?],
+            (None, Some(phases)) if !phases.is_empty() => {
+                let mut schedule = Vec::with_capacity(phases.len());
+                for phase in phases {
+                    if phase.seconds == 0 {
+                        return Err(format!(
+                            "Phase {} must last at least a second",
+                            phase.workload
+                        ));
+                    }
+                    schedule.push(Phase::new(
+                        &phase.workload,
+                        Duration::from_secs(phase.seconds),
+                    )?);
+                }
+                schedule
+            }
+            _ => {
+                return Err(String::from(
+                    "Experiment must have either a workload or phases",
+                ))
+            }
+        };
+        if file.threads.is_empty() {
+            return Err(String::from(
+                "Experiment must have at least one thread count",
+            ));
+        }
+        let log_size = match file.disk_resident {
+            Some(true) => K_DISK_RESIDENT_LOG_SIZE,
+            _ => file.log_size.unwrap_or(K_DEFAULT_LOG_SIZE),
+        };
+        Ok(RunConfig {
+            load_keys_file: file.load,
+            run_keys_file: file.run,
+            thread_counts: file.threads,
+            repopulate: file.repopulate.unwrap_or(false),
+            chunk_size: file.chunk_size.unwrap_or(K_DEFAULT_CHUNK_SIZE),
+            table_sizes: file
+                .table_sizes
+                .unwrap_or_else(|| vec![K_DEFAULT_TABLE_SIZE]),
+            log_size,
+            seed: file.seed.unwrap_or(0),
+            key_format: KeyFormat::parse(file.key_format.as_ref().map_or("u64", |f| f.as_str()))?,
+            open_loop_rate: file.rate,
+            phases,
+            storage_dir: file
+                .storage_dir
+                .unwrap_or_else(|| String::from(K_STORAGE_DIR)),
+            results_file: file.results,
+            memory_file: file.memory_samples,
+        })
+    }
+}
+
+/// SplitMix64 pseudo-random number generator.
+///
+/// Implemented here rather than pulled in from a crate so that a given seed produces the
+/// same sequence regardless of dependency versions, keeping results reproducible.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Generator for the given thread, derived from the benchmark's seed
+    pub fn for_thread(seed: u64, thread_id: u8) -> Rng {
+        let mut derived = Rng::new(seed ^ (thread_id as u64 + 1).wrapping_mul(0x9E3779B97F4A7C15));
+        Rng::new(derived.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed number in `0..bound`
+    pub fn gen_range(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+const K_LATENCY_SUB_BUCKETS: usize = 16;
+const K_LATENCY_SUB_BUCKET_BITS: usize = 4;
+// Sleep rather than spin while waiting for the next operation of an open-loop run
+const K_OPEN_LOOP_SLEEP_THRESHOLD: Duration = Duration::from_micros(100);
+
+/// Histogram of latencies in nanoseconds.
+///
+/// Values are bucketed by their power of two and split into 16 linear sub-buckets each,
+/// so recorded latencies are reported with a precision of about 6%.
+#[derive(Clone)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> LatencyHistogram {
+        LatencyHistogram {
+            buckets: vec![0; (64 - K_LATENCY_SUB_BUCKET_BITS + 1) * K_LATENCY_SUB_BUCKETS],
+            count: 0,
+            max: 0,
+        }
+    }
+
+    fn bucket(nanos: u64) -> usize {
+        if nanos < K_LATENCY_SUB_BUCKETS as u64 {
+            return nanos as usize;
+        }
+        let exponent = 63 - nanos.leading_zeros() as usize;
+        let sub_bucket = (nanos >> (exponent - K_LATENCY_SUB_BUCKET_BITS)) as usize
+            & (K_LATENCY_SUB_BUCKETS - 1);
+        (exponent - K_LATENCY_SUB_BUCKET_BITS + 1) * K_LATENCY_SUB_BUCKETS + sub_bucket
+    }
+
+    fn lower_bound(bucket: usize) -> u64 {
+        if bucket < K_LATENCY_SUB_BUCKETS {
+            return bucket as u64;
+        }
+        let exponent = bucket / K_LATENCY_SUB_BUCKETS + K_LATENCY_SUB_BUCKET_BITS - 1;
+        let sub_bucket = (bucket % K_LATENCY_SUB_BUCKETS) as u64;
+        (K_LATENCY_SUB_BUCKETS as u64 + sub_bucket) << (exponent - K_LATENCY_SUB_BUCKET_BITS)
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let nanos = latency.as_nanos() as u64;
+        self.buckets[LatencyHistogram::bucket(nanos)] += 1;
+        self.count += 1;
+        self.max = std::cmp::max(self.max, nanos);
+    }
+
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (bucket, count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *bucket += count;
+        }
+        self.count += other.count;
+        self.max = std::cmp::max(self.max, other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Upper bound of the latency below which `percentile` percent of the values fall
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        let target = ((percentile / 100.0) * self.count as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= std::cmp::max(target, 1) {
+                let upper = LatencyHistogram::lower_bound(bucket + 1) - 1;
+                return Duration::from_nanos(std::cmp::min(upper, self.max));
+            }
+        }
+        Duration::from_nanos(self.max)
+    }
+
+    pub fn print(&self, name: &str) {
+        if self.count == 0 {
+            return;
+        }
+        let micros = |percentile| self.percentile(percentile).as_nanos() as f64 / 1000.0;
+        println!(
+            "{} (us) over {} operations: p50 {:.1}, p90 {:.1}, p99 {:.1}, p99.9 {:.1}, max {:.1}",
+            name,
+            self.count,
+            micros(50.0),
+            micros(90.0),
+            micros(99.0),
+            micros(99.9),
+            self.max as f64 / 1000.0
+        );
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram::new()
+    }
+}
+
+/// Memory usage of the process at a point of a run
+#[derive(Clone, Copy, Default)]
+pub struct MemorySample {
+    /// Time since the start of the run
+    pub elapsed: Duration,
+    pub rss_bytes: u64,
+    /// Page faults since the start of the run
+    pub minor_faults: u64,
+    pub major_faults: u64,
+    /// Bytes allocated through malloc, which is where FASTER keeps its hash table and the
+    /// in-memory part of the log. Only known when built against glibc.
+    pub heap_bytes: Option<u64>,
+}
+
+// Page faults of the process since it started, as (minor, major)
+fn page_faults() -> (u64, u64) {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return (0, 0);
+    }
+    (usage.ru_minflt as u64, usage.ru_majflt as u64)
+}
+
+// The second field of /proc/self/statm is the number of resident pages
+fn rss_bytes() -> u64 {
+    let pages = fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|statm| statm.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .unwrap_or(0);
+    pages * unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn heap_bytes() -> Option<u64> {
+    let info = unsafe { libc::mallinfo2() };
+    // Large allocations, such as the log's pages, are mmapped rather than taken from the arena
+    Some((info.uordblks + info.hblkhd) as u64)
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn heap_bytes() -> Option<u64> {
+    None
+}
+
+/// Samples the memory usage of the process over the course of a run
+pub struct MemorySampler {
+    start: Instant,
+    start_faults: (u64, u64),
+    samples: Vec<MemorySample>,
+}
+
+impl MemorySampler {
+    pub fn start() -> MemorySampler {
+        let mut sampler = MemorySampler {
+            start: Instant::now(),
+            start_faults: page_faults(),
+            samples: Vec::new(),
+        };
+        sampler.sample();
+        sampler
+    }
+
+    pub fn sample(&mut self) {
+        let (minor_faults, major_faults) = page_faults();
+        self.samples.push(MemorySample {
+            elapsed: self.start.elapsed(),
+            rss_bytes: rss_bytes(),
+            minor_faults: minor_faults - self.start_faults.0,
+            major_faults: major_faults - self.start_faults.1,
+            heap_bytes: heap_bytes(),
+        });
+    }
+
+    pub fn finish(mut self) -> MemoryUsage {
+        self.sample();
+        MemoryUsage {
+            samples: self.samples,
+        }
+    }
+}
+
+/// Memory usage sampled during a run
+#[derive(Clone, Default)]
+pub struct MemoryUsage {
+    pub samples: Vec<MemorySample>,
+}
+
+impl MemoryUsage {
+    pub fn peak_rss_bytes(&self) -> u64 {
+        self.samples.iter().map(|s| s.rss_bytes).max().unwrap_or(0)
+    }
+
+    pub fn peak_heap_bytes(&self) -> Option<u64> {
+        self.samples.iter().filter_map(|s| s.heap_bytes).max()
+    }
+
+    /// Page faults over the whole run, as (minor, major)
+    pub fn page_faults(&self) -> (u64, u64) {
+        self.samples
+            .last()
+            .map_or((0, 0), |s| (s.minor_faults, s.major_faults))
+    }
+
+    fn print(&self) {
+        let (minor_faults, major_faults) = self.page_faults();
+        let mut line = format!(
+            "Peak RSS {} MiB, {} minor and {} major page faults",
+            self.peak_rss_bytes() >> 20,
+            minor_faults,
+            major_faults
+        );
+        if let Some(heap_bytes) = self.peak_heap_bytes() {
+            write!(line, ", peak heap {} MiB", heap_bytes >> 20).unwrap();
+        }
+        println!("{}", line);
+    }
+}
+
+/// Size of the store, recorded with each phase so throughput can be related to it.
+///
+/// Garbage ratios, log addresses and read cache hit rates are not exposed by faster-c, so
+/// only the sizes of the log and of the storage directory are available.
+#[derive(Clone, Copy, Default)]
+pub struct StoreStats {
+    /// Bytes of the hybrid log, as reported by `FasterKv::size`
+    pub log_bytes: u64,
+    /// Bytes of log segments and checkpoints on disk, for stores with storage
+    pub disk_bytes: Option<u64>,
+}
+
+impl StoreStats {
+    pub fn capture(store: &FasterKv) -> StoreStats {
+        StoreStats {
+            log_bytes: store.size(),
+            disk_bytes: store.disk_usage().ok().map(|usage| usage.total()),
+        }
+    }
+
+    fn print(&self, name: &str) {
+        let mut line = format!("{}: log {} MiB", name, self.log_bytes >> 20);
+        if let Some(disk_bytes) = self.disk_bytes {
+            write!(line, ", {} MiB on disk", disk_bytes >> 20).unwrap();
+        }
+        println!("{}", line);
+    }
+}
+
+// Counters that could not be read, or were never opened, count zero events
+fn read_perf(perf: &mut Option<PerfCounters>) -> PerfCounts {
+    perf.as_mut()
+        .and_then(|perf| perf.read().ok())
+        .unwrap_or_default()
+}
+
+// Size of the next chunk of work to claim. While plenty of work remains this is
+// `max_chunk_size`, shrinking towards the end so threads finish at roughly the same time.
+fn adaptive_chunk_size(remaining: usize, num_threads: u8, max_chunk_size: usize) -> usize {
+    let fair_share = remaining / (num_threads as usize * K_CHUNKS_PER_THREAD);
+    std::cmp::max(K_MIN_CHUNK_SIZE, std::cmp::min(max_chunk_size, fair_share))
 }
 
 fn cpuset_for_core(topology: &Topology, idx: usize) -> CpuSet {
@@ -55,6 +713,17 @@ pub fn process_ycsb(input_file: &str, output_file: &str) {
     }
 }
 
+// Run keys are drawn uniformly from the loaded keys using the given seed
+pub fn generate_uniform_keys(out_file: &str, seed: u64) {
+    let mut output = File::create(out_file).expect("Unable to create output file");
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..K_TXN_COUNT {
+        let key = rng.gen_range(K_INIT_COUNT as u64);
+        output.write(&key.to_be_bytes()).unwrap();
+    }
+}
+
 pub fn generate_sequential_keys(out_file: &str, workload: &str) {
     let mut output = File::create(out_file).expect("Unable to create output file");
     let num_keys = match workload {
@@ -68,7 +737,11 @@ pub fn generate_sequential_keys(out_file: &str, workload: &str) {
     }
 }
 
-pub fn read_upsert5050(key: usize) -> Operation {
+pub fn read_100(_key: usize, _rng: &mut Rng) -> Operation {
+    Operation::Read
+}
+
+pub fn read_upsert5050(key: usize, _rng: &mut Rng) -> Operation {
     match key % 2 {
         0 => Operation::Read,
         1 => Operation::Upsert,
@@ -76,11 +749,18 @@ pub fn read_upsert5050(key: usize) -> Operation {
     }
 }
 
-pub fn rmw_100(_key: usize) -> Operation {
+pub fn read_upsert9505(_key: usize, rng: &mut Rng) -> Operation {
+    match rng.gen_range(100) {
+        0..=94 => Operation::Read,
+        _ => Operation::Upsert,
+    }
+}
+
+pub fn rmw_100(_key: usize, _rng: &mut Rng) -> Operation {
     Operation::Rmw
 }
 
-pub fn upsert_100(_key: usize) -> Operation {
+pub fn upsert_100(_key: usize, _rng: &mut Rng) -> Operation {
     Operation::Upsert
 }
 
@@ -142,7 +822,13 @@ pub fn load_files(load_file: &str, run_file: &str) -> (Vec<u64>, Vec<u64>) {
     (init_keys, run_keys)
 }
 
-pub fn populate_store(store: &Arc<FasterKv>, keys: &Arc<Vec<u64>>, num_threads: u8) {
+pub fn populate_store(
+    store: &Arc<FasterKv>,
+    keys: &Arc<Vec<u64>>,
+    num_threads: u8,
+    chunk_size: usize,
+    key_format: KeyFormat,
+) {
     let topo = Arc::new(Mutex::new(Topology::new()));
     let idx = Arc::new(AtomicUsize::new(0));
     let mut threads = vec![];
@@ -164,19 +850,46 @@ pub fn populate_store(store: &Arc<FasterKv>, keys: &Arc<Vec<u64>>, num_threads:
                     .unwrap();
             }
 
+            let mut string_key = StringKey::new();
+            let mut refresh = RefreshSchedule::new(K_REFRESH_INTERVAL);
             let _session = store.start_session();
-            let mut chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
-            while chunk_idx < K_INIT_COUNT {
-                for i in chunk_idx..(chunk_idx + K_CHUNK_SIZE) {
-                    if i % K_REFRESH_INTERVAL == 0 {
-                        store.refresh();
-                        if i % K_COMPLETE_PENDING_INTERVAL == 0 {
-                            store.complete_pending(false);
+            loop {
+                let claimed = idx.load(Ordering::SeqCst);
+                if claimed >= K_INIT_COUNT {
+                    break;
+                }
+                let size = adaptive_chunk_size(K_INIT_COUNT - claimed, num_threads, chunk_size);
+                let chunk_idx = idx.fetch_add(size, Ordering::SeqCst);
+                if chunk_idx >= K_INIT_COUNT {
+                    break;
+                }
+                let chunk_end = std::cmp::min(chunk_idx + size, K_INIT_COUNT);
+                let chunk_keys = &keys[chunk_idx..chunk_end];
+                match key_format {
+                    // Batches refresh the session themselves
+                    KeyFormat::U64 => {
+                        let records: Vec<(u64, i32)> =
+                            chunk_keys.iter().map(|&key| (key, 42)).collect();
+                        store.upsert_batch(&records, chunk_idx as u64);
+                    }
+                    KeyFormat::String => {
+                        let records: Vec<(String, i32)> = chunk_keys
+                            .iter()
+                            .map(|&key| (string_key.format(key).clone(), 42))
+                            .collect();
+                        store.upsert_batch(&records, chunk_idx as u64);
+                    }
+                    KeyFormat::U64Pod => {
+                        for (i, &key) in (chunk_idx..chunk_end).zip(chunk_keys) {
+                            if refresh.tick() {
+                                store.refresh();
+                            }
+                            execute_pod(&store, key, &Operation::Upsert, i as u64);
                         }
                     }
-                    store.upsert(&*keys.get(i as usize).unwrap(), &42, i as u64);
                 }
-                chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
+                store.refresh();
+                store.complete_pending(false);
             }
             store.complete_pending(true);
             store.stop_session();
@@ -187,17 +900,26 @@ pub fn populate_store(store: &Arc<FasterKv>, keys: &Arc<Vec<u64>>, num_threads:
     }
 }
 
-pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
+pub fn run_benchmark(
     store: &Arc<FasterKv>,
     keys: &Arc<Vec<u64>>,
     num_threads: u8,
-    op_allocator: F,
-) {
+    chunk_size: usize,
+    seed: u64,
+    phases: Vec<Phase>,
+    open_loop_rate: Option<u64>,
+    key_format: KeyFormat,
+) -> (OpCounts, MemoryUsage) {
+    assert!(!phases.is_empty(), "At least one phase must be specified");
+    let phases = Arc::new(phases);
+    let current_phase = Arc::new(AtomicUsize::new(0));
     let topo = Arc::new(Mutex::new(Topology::new()));
     let idx = Arc::new(AtomicUsize::new(0));
     let done = Arc::new(AtomicBool::new(false));
     let barrier = Arc::new(Barrier::new((num_threads + 1) as usize));
     let mut threads = vec![];
+    // Opened before spawning the threads, so that their events are counted too
+    let mut perf = PerfCounters::open();
 
     for thread_id in 0..num_threads {
         let store = Arc::clone(&store);
@@ -206,6 +928,8 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
         let done = Arc::clone(&done);
         let barrier = Arc::clone(&barrier);
         let topo = Arc::clone(&topo);
+        let phases = Arc::clone(&phases);
+        let current_phase = Arc::clone(&current_phase);
 
         threads.push(
             std::thread::Builder::new()
@@ -221,43 +945,96 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
                             .unwrap();
                     }
 
-                    let mut reads = 0;
-                    let mut upserts = 0;
-                    let mut rmws = 0;
+                    let mut rng = Rng::for_thread(seed, thread_id);
+                    let mut string_key = StringKey::new();
+                    let mut refresh = RefreshSchedule::new(K_REFRESH_INTERVAL);
+                    let mut counts = vec![OpCounts::default(); phases.len()];
+                    // Response times count from when an operation was scheduled to start, so a
+                    // stall delaying the following operations is not hidden (coordinated
+                    // omission). Service times count from when it actually started.
+                    let mut response_times = LatencyHistogram::new();
+                    let mut service_times = LatencyHistogram::new();
+                    // In an open-loop run every thread issues its share of the arrival rate
+                    let interval = open_loop_rate.map(|rate| {
+                        Duration::from_nanos(
+                            (K_NANOS_PER_SECOND as u64 * num_threads as u64) / rate.max(1),
+                        )
+                    });
 
                     let _session = store.start_session();
 
                     barrier.wait();
                     let start = Instant::now();
-                    while !done.load(Ordering::SeqCst) {
-                        let mut chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
-                        while chunk_idx >= K_TXN_COUNT {
-                            if chunk_idx == K_TXN_COUNT {
-                                idx.store(0, Ordering::SeqCst);
+                    // Stagger the threads' schedules so arrivals are spread evenly
+                    let mut next_op = start
+                        + interval.unwrap_or_default() / num_threads as u32 * thread_id as u32;
+                    'run: while !done.load(Ordering::SeqCst) {
+                        let phase = current_phase.load(Ordering::SeqCst);
+                        let op_allocator = &*phases[phase].op_allocator;
+                        let counts = &mut counts[phase];
+                        // Cycle through the transactions until the run time is over
+                        let chunk_idx = idx.fetch_add(chunk_size, Ordering::SeqCst) % K_TXN_COUNT;
+                        let chunk_end = std::cmp::min(chunk_idx + chunk_size, K_TXN_COUNT);
+                        for i in chunk_idx..chunk_end {
+                            let mut intended_start = None;
+                            if let Some(interval) = interval {
+                                if done.load(Ordering::Relaxed) {
+                                    break 'run;
+                                }
+                                loop {
+                                    let now = Instant::now();
+                                    if now >= next_op {
+                                        break;
+                                    }
+                                    let wait = next_op - now;
+                                    if wait > K_OPEN_LOOP_SLEEP_THRESHOLD {
+                                        std::thread::sleep(wait - K_OPEN_LOOP_SLEEP_THRESHOLD);
+                                    } else {
+                                        std::sync::atomic::spin_loop_hint();
+                                    }
+                                }
+                                intended_start = Some(next_op);
+                                next_op += interval;
                             }
-                            chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
-                        }
-                        for i in chunk_idx..(chunk_idx + K_CHUNK_SIZE) {
-                            if i % K_REFRESH_INTERVAL == 0 {
+                            let op_start = Instant::now();
+                            if refresh.tick() {
                                 store.refresh();
-                                if i % K_COMPLETE_PENDING_INTERVAL == 0 {
+                                if refresh.refreshes() % K_COMPLETE_PENDING_REFRESHES == 0 {
                                     store.complete_pending(false);
                                 }
                             }
-                            match op_allocator(i) {
-                                Operation::Read => {
-                                    let (_, _): (u8, Receiver<i32>) =
-                                        store.read(&*keys.get(i).unwrap(), 1);
-                                    reads += 1;
+                            let op = op_allocator.next_op(i, &mut rng);
+                            let key = op_allocator.next_key(i, &keys, &mut rng);
+                            let status = match (op, key_format) {
+                                (Operation::Custom(op), _) => {
+                                    op.execute(&store, key, key_format, 1)
                                 }
-                                Operation::Upsert => {
-                                    store.upsert(&*keys.get(i).unwrap(), &42, 1);
-                                    upserts += 1;
+                                (_, KeyFormat::U64) => execute(&store, &key, &op, 1),
+                                (_, KeyFormat::U64Pod) => execute_pod(&store, key, &op, 1),
+                                (_, KeyFormat::String) => {
+                                    execute(&store, string_key.format(key), &op, 1)
                                 }
-                                Operation::Rmw => {
-                                    store.rmw(&*keys.get(i).unwrap(), &5, 1);
-                                    rmws += 1;
+                            };
+                            match op {
+                                Operation::Read => {
+                                    counts.reads += 1;
+                                    if status == status::PENDING {
+                                        counts.pending_reads += 1;
+                                        // Include the disk read in the response time
+                                        if interval.is_some() {
+                                            store.complete_pending(true);
+                                        }
+                                    }
                                 }
+                                Operation::Upsert => counts.upserts += 1,
+                                Operation::Rmw => counts.rmws += 1,
+                                Operation::Delete => counts.deletes += 1,
+                                Operation::Custom(_) => counts.custom += 1,
+                            }
+                            if let Some(intended_start) = intended_start {
+                                let op_end = Instant::now();
+                                response_times.record(op_end - intended_start);
+                                service_times.record(op_end - op_start);
                             }
                         }
                     }
@@ -265,17 +1042,21 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
                     store.complete_pending(true);
                     store.stop_session();
                     let duration = Instant::now().duration_since(start);
+                    let mut total = OpCounts::default();
+                    for phase_counts in &counts {
+                        total.add(phase_counts);
+                    }
 
                     println!(
                         "Thread {} completed {} reads, {} upserts and {} rmws in {}ms",
                         thread_id,
-                        reads,
-                        upserts,
-                        rmws,
+                        total.reads,
+                        total.upserts,
+                        total.rmws,
                         duration.as_millis()
                     );
 
-                    (reads, upserts, rmws, duration.as_nanos())
+                    (counts, response_times, service_times, duration.as_nanos())
                 })
                 .unwrap(),
         )
@@ -283,11 +1064,40 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
 
     barrier.wait();
     let start = Instant::now();
+    let mut memory = MemorySampler::start();
+    let mut phase_perf = Vec::with_capacity(phases.len());
+    let mut perf_start = read_perf(&mut perf);
+    // Store sizes at the end of each phase
+    let mut phase_stats = Vec::with_capacity(phases.len());
     let mut last_checkpoint = Instant::now();
     let mut num_checkpoints = 0;
+    let run_time: Duration = phases.iter().map(|phase| phase.duration).sum();
+    let mut phase = 0;
+    let mut phase_end = start + phases[0].duration;
+    println!(
+        "Phase 0: {} for {}s",
+        phases[0].workload,
+        phases[0].duration.as_secs()
+    );
 
-    while Instant::now().duration_since(start).as_secs() < K_RUN_TIME {
+    while Instant::now().duration_since(start) < run_time {
         std::thread::sleep(Duration::from_secs(1));
+        memory.sample();
+        if Instant::now() >= phase_end && phase + 1 < phases.len() {
+            let perf_end = read_perf(&mut perf);
+            phase_perf.push(perf_end - perf_start);
+            perf_start = perf_end;
+            phase_stats.push(StoreStats::capture(store));
+            phase += 1;
+            phase_end += phases[phase].duration;
+            current_phase.store(phase, Ordering::SeqCst);
+            println!(
+                "Phase {}: {} for {}s",
+                phase,
+                phases[phase].workload,
+                phases[phase].duration.as_secs()
+            );
+        }
         if Instant::now().duration_since(last_checkpoint).as_secs() > K_CHECKPOINT_SECONDS {
             println!("Checkpointing...");
             store.checkpoint();
@@ -297,23 +1107,165 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
     }
 
     done.store(true, Ordering::SeqCst);
+    phase_perf.push(read_perf(&mut perf) - perf_start);
+    phase_stats.push(StoreStats::capture(store));
 
-    let mut total_counts = (0, 0, 0, 0);
+    let mut total_counts = OpCounts::default();
+    let mut total_duration = 0;
+    let mut phase_counts = vec![OpCounts::default(); phases.len()];
+    let mut total_response_times = LatencyHistogram::new();
+    let mut total_service_times = LatencyHistogram::new();
+    let memory = memory.finish();
     for t in threads {
-        let (reads, upserts, rmws, duration) = t.join().expect("Something went wrong in a thread");
-        total_counts.0 += reads;
-        total_counts.1 += upserts;
-        total_counts.2 += rmws;
-        total_counts.3 += duration;
+        let (counts, response_times, service_times, duration) =
+            t.join().expect("Something went wrong in a thread");
+        total_response_times.merge(&response_times);
+        total_service_times.merge(&service_times);
+        for (phase, counts) in counts.iter().enumerate() {
+            phase_counts[phase].add(counts);
+            total_counts.add(counts);
+        }
+        total_duration += duration;
+    }
+
+    if phases.len() > 1 {
+        for (i, counts) in phase_counts.iter().enumerate() {
+            println!(
+                "Phase {} ({}): {} reads, {} writes, {} rmws. {} ops/second/thread",
+                i,
+                phases[i].workload,
+                counts.reads,
+                counts.upserts,
+                counts.rmws,
+                counts.total() / (phases[i].duration.as_secs() as usize * num_threads as usize)
+            );
+            counts.print_other_ops();
+            counts.print_disk_reads(phases[i].duration, num_threads);
+        }
+    }
+    for (i, stats) in phase_stats.iter().enumerate() {
+        stats.print(&format!("Store after phase {} ({})", i, phases[i].workload));
+    }
+    if perf.is_some() {
+        // Operations that completed during a phase, counted against the events of the phase
+        for (i, counts) in phase_counts.iter().enumerate() {
+            phase_perf[i].print(
+                &format!("Phase {} ({})", i, phases[i].workload),
+                counts.total(),
+            );
+        }
     }
 
     println!(
         "Finished benchmark: {} checkpoints, {} reads, {} writes, {} rmws. {} ops/second/thread",
         num_checkpoints,
-        total_counts.0,
-        total_counts.1,
-        total_counts.2,
-        (total_counts.0 + total_counts.1 + total_counts.2)
-            / (total_counts.3 as usize / K_NANOS_PER_SECOND)
-    )
+        total_counts.reads,
+        total_counts.upserts,
+        total_counts.rmws,
+        total_counts.total() / (total_duration as usize / K_NANOS_PER_SECOND)
+    );
+    total_counts.print_other_ops();
+    total_counts.print_disk_reads(run_time, num_threads);
+    if let Some(rate) = open_loop_rate {
+        println!(
+            "Open loop at {} ops/second, achieved {:.0} ops/second",
+            rate,
+            total_counts.total() as f64 / run_time.as_secs_f64()
+        );
+        total_response_times.print("Response time");
+        total_service_times.print("Service time");
+    }
+    memory.print();
+    (total_counts, memory)
+}
+
+/// Result of a single configuration of a sweep over table sizes and thread counts
+pub struct SweepResult {
+    pub table_size: u64,
+    pub num_threads: u8,
+    pub counts: OpCounts,
+    pub memory: MemoryUsage,
+    /// Size of the store at the end of the run
+    pub store: StoreStats,
+}
+
+/// Prints the throughput of each configuration of a sweep as CSV, for plotting.
+///
+/// Speedup is relative to the per-thread throughput of the first thread count with the
+/// same table size.
+pub fn print_sweep(results: &[SweepResult], run_time: Duration) {
+    let stdout = std::io::stdout();
+    write_sweep(&mut stdout.lock(), results, run_time).expect("Unable to print results");
+}
+
+/// Writes the throughput of each configuration as CSV, like `print_sweep`
+pub fn write_sweep<W: Write>(
+    output: &mut W,
+    results: &[SweepResult],
+    run_time: Duration,
+) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "table_size,threads,ops_per_second,ops_per_second_per_thread,speedup,\
+         peak_rss_bytes,minor_faults,major_faults,peak_heap_bytes,log_bytes,disk_bytes"
+    )?;
+    let ops_per_second = |counts: &OpCounts| counts.total() as f64 / run_time.as_secs_f64();
+    let mut base = 0.0;
+    let mut base_table_size = None;
+    for result in results {
+        let throughput = ops_per_second(&result.counts);
+        if base_table_size != Some(result.table_size) {
+            base = throughput / result.num_threads as f64;
+            base_table_size = Some(result.table_size);
+        }
+        let (minor_faults, major_faults) = result.memory.page_faults();
+        writeln!(
+            output,
+            "{},{},{:.0},{:.0},{:.2},{},{},{},{},{},{}",
+            result.table_size,
+            result.num_threads,
+            throughput,
+            throughput / result.num_threads as f64,
+            throughput / base,
+            result.memory.peak_rss_bytes(),
+            minor_faults,
+            major_faults,
+            optional_csv(result.memory.peak_heap_bytes()),
+            result.store.log_bytes,
+            optional_csv(result.store.disk_bytes)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the memory usage sampled every second of each configuration as CSV
+pub fn write_memory_samples<W: Write>(
+    output: &mut W,
+    results: &[SweepResult],
+) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "table_size,threads,seconds,rss_bytes,minor_faults,major_faults,heap_bytes"
+    )?;
+    for result in results {
+        for sample in &result.memory.samples {
+            writeln!(
+                output,
+                "{},{},{:.3},{},{},{},{}",
+                result.table_size,
+                result.num_threads,
+                sample.elapsed.as_secs_f64(),
+                sample.rss_bytes,
+                sample.minor_faults,
+                sample.major_faults,
+                optional_csv(sample.heap_bytes)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// Unknown values are left empty
+fn optional_csv(value: Option<u64>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
 }
@@ -1,11 +1,15 @@
 extern crate clap;
 
 use benchmark::*;
-use clap::{App, Arg, SubCommand};
-use faster_rs::FasterKvBuilder;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use faster_rs::{FasterKv, FasterKvBuilder};
+use std::fs::File;
 use std::sync::Arc;
 
 fn main() {
+    let default_chunk_size = K_DEFAULT_CHUNK_SIZE.to_string();
+    let default_table_size = K_DEFAULT_TABLE_SIZE.to_string();
+    let default_log_size = K_DEFAULT_LOG_SIZE.to_string();
     let matches = App::new("faster-rs Benchmark")
         .subcommand(
             SubCommand::with_name("process-ycsb")
@@ -24,33 +28,121 @@ fn main() {
         .subcommand(
             SubCommand::with_name("run")
                 .about("Run benchmark")
+                .arg(
+                    Arg::with_name("config")
+                        .long("config")
+                        .takes_value(true)
+                        .conflicts_with_all(&["num-threads", "threads", "load", "run", "phases", "workload"])
+                        .help("Run the experiment described in a TOML file instead of the other options"),
+                )
                 .arg(
                     Arg::with_name("num-threads")
                         .short("n")
-                        .required(true)
+                        .required_unless_one(&["threads", "config"])
                         .takes_value(true)
                         .display_order(1)
                         .help("Number of threads to use"),
                 )
+                .arg(
+                    Arg::with_name("threads")
+                        .long("threads")
+                        .takes_value(true)
+                        .use_delimiter(true)
+                        .conflicts_with("num-threads")
+                        .help("Sweep over thread counts, e.g. 1,2,4,8,16,32"),
+                )
+                .arg(
+                    Arg::with_name("repopulate")
+                        .long("repopulate")
+                        .requires("threads")
+                        .help("Recreate and populate the store for every thread count of a sweep"),
+                )
+                .arg(
+                    Arg::with_name("chunk-size")
+                        .long("chunk-size")
+                        .takes_value(true)
+                        .default_value(&default_chunk_size)
+                        .help("Maximum number of operations a thread claims at once"),
+                )
+                .arg(
+                    Arg::with_name("table-size")
+                        .long("table-size")
+                        .takes_value(true)
+                        .default_value(&default_table_size)
+                        .help("Number of hash table entries"),
+                )
+                .arg(
+                    Arg::with_name("table-sizes")
+                        .long("table-sizes")
+                        .takes_value(true)
+                        .use_delimiter(true)
+                        .help("Sweep over table sizes, overrides --table-size"),
+                )
+                .arg(
+                    Arg::with_name("log-size")
+                        .long("log-size")
+                        .takes_value(true)
+                        .default_value(&default_log_size)
+                        .help("Size of the in-memory log in bytes"),
+                )
+                .arg(
+                    Arg::with_name("disk-resident")
+                        .long("disk-resident")
+                        .help("Shrink the in-memory log so most keys live on disk, overrides --log-size"),
+                )
                 .arg(
                     Arg::with_name("load")
-                        .required(true)
+                        .required_unless("config")
                         .help("Path to YCSB load keys"),
                 )
                 .arg(
                     Arg::with_name("run")
-                        .required(true)
+                        .required_unless("config")
                         .help("Path to YCSB run keys"),
                 )
-                .arg(Arg::with_name("workload").required(true).possible_values(&[
-                    "read_upsert_50_50",
-                    "rmw_100",
-                    "upsert_100",
-                ])),
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Seed for random operation choices, derived per thread"),
+                )
+                .arg(
+                    Arg::with_name("key-format")
+                        .long("key-format")
+                        .takes_value(true)
+                        .possible_values(KEY_FORMATS)
+                        .default_value("u64")
+                        .help("Use the keys as u64, as u64 without serialization (u64-pod) or as 24 byte strings (userNNNN)"),
+                )
+                .arg(
+                    Arg::with_name("rate")
+                        .long("rate")
+                        .takes_value(true)
+                        .help("Run open loop at the given arrival rate (ops/second) and report latencies"),
+                )
+                .arg(
+                    Arg::with_name("phases")
+                        .long("phases")
+                        .takes_value(true)
+                        .conflicts_with("workload")
+                        .help("Schedule of workloads to run in turn, e.g. upsert_100:600,read_upsert_95_5:600 (seconds)"),
+                )
+                .arg(
+                    Arg::with_name("memory-samples")
+                        .long("memory-samples")
+                        .takes_value(true)
+                        .help("Write the memory usage sampled every second to a CSV file"),
+                )
+                .arg(
+                    Arg::with_name("workload")
+                        .required_unless_one(&["phases", "config"])
+                        .possible_values(WORKLOADS),
+                ),
         )
         .subcommand(
             SubCommand::with_name("generate-keys")
-                .about("Generate sequential keys")
+                .about("Generate sequential or uniformly distributed keys")
                 .arg(
                     Arg::with_name("load/run")
                         .required(true)
@@ -58,6 +150,21 @@ fn main() {
                         .possible_values(&["load", "run"])
                         .help("Generate keys for load or run"),
                 )
+                .arg(
+                    Arg::with_name("distribution")
+                        .long("distribution")
+                        .takes_value(true)
+                        .possible_values(&["sequential", "uniform"])
+                        .default_value("sequential")
+                        .help("Distribution of run keys, load keys are always sequential"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Seed for uniformly distributed keys"),
+                )
                 .arg(
                     Arg::with_name("output")
                         .required(true)
@@ -74,42 +181,111 @@ fn main() {
         println!("Processing YCSB workload");
         process_ycsb(input, output);
     } else if let Some(matches) = matches.subcommand_matches("run") {
-        let num_threads = matches
-            .value_of("num-threads")
-            .expect("Number of threads not specified");
-        let num_threads: u8 = num_threads
-            .parse()
-            .expect("num-threads argument must be integer");
-        let load_keys_file = matches
-            .value_of("load")
-            .expect("File containing load transactions not specified");
-        let run_keys_file = matches
-            .value_of("run")
-            .expect("File containing run transactions not specified");
-        let workload = matches
-            .value_of("workload")
-            .expect("Workload not specified");
-        let op_allocator = match workload {
-            "read_upsert_50_50" => read_upsert5050,
-            "rmw_100" => rmw_100,
-            "upsert_100" => upsert_100,
-            _ => panic!("Unexpected workload specified. Options are: read_upsert_50_50, rmw_100"),
+        let config = match matches.value_of("config") {
+            Some(path) => RunConfig::from_file(path),
+            None => run_config_from_args(matches),
         };
-
-        let table_size: u64 = 134217728;
-        let log_size: u64 = 17179869184;
-        let dir_path = String::from("benchmark_store");
-        let store = Arc::new(FasterKvBuilder::new(table_size, log_size).with_disk(&dir_path).build().unwrap());
-        let (load_keys, txn_keys) = load_files(load_keys_file, run_keys_file);
+        let RunConfig {
+            load_keys_file,
+            run_keys_file,
+            thread_counts,
+            repopulate,
+            chunk_size,
+            table_sizes,
+            log_size,
+            seed,
+            key_format,
+            open_loop_rate,
+            phases,
+            storage_dir: dir_path,
+            results_file,
+            memory_file,
+        } = config.unwrap_or_else(|err| panic!("{}", err));
+        let sweep_table_sizes = table_sizes.len() > 1;
+        let (load_keys, txn_keys) = load_files(&load_keys_file, &run_keys_file);
         let load_keys = Arc::new(load_keys);
         let txn_keys = Arc::new(txn_keys);
-        println!("Populating datastore");
-        populate_store(&store, &load_keys, num_threads);
-        println!("Beginning benchmark");
-        run_benchmark(&store, &txn_keys, num_threads, op_allocator);
-        match store.clean_storage() {
-            Ok(_) => { /*no-op*/ }
-            Err(_) => eprintln!("Unable to clear storage"),
+        // Without repopulating, the store is populated once using the most threads of the sweep
+        let populate_threads = *thread_counts.iter().max().unwrap();
+        let mut store: Option<Arc<FasterKv>> = None;
+        let mut store_table_size = 0;
+        let mut results = Vec::with_capacity(table_sizes.len() * thread_counts.len());
+        let configs = table_sizes
+            .iter()
+            .flat_map(|&table_size| thread_counts.iter().map(move |&n| (table_size, n)));
+        for (table_size, num_threads) in configs {
+            if store.is_none() || repopulate || table_size != store_table_size {
+                if let Some(store) = store.take() {
+                    if store.clean_storage().is_err() {
+                        eprintln!("Unable to clear storage");
+                    }
+                }
+                let new_store = Arc::new(
+                    FasterKvBuilder::new(table_size, log_size)
+                        .with_disk(&dir_path)
+                        .build()
+                        .unwrap(),
+                );
+                let populate_threads = if repopulate {
+                    num_threads
+                } else {
+                    populate_threads
+                };
+                println!("Populating datastore with table size {}", table_size);
+                populate_store(
+                    &new_store,
+                    &load_keys,
+                    populate_threads,
+                    chunk_size,
+                    key_format,
+                );
+                if sweep_table_sizes {
+                    // Prints the hash bucket chain lengths for the loaded keys
+                    new_store.dump_distribution();
+                }
+                store = Some(new_store);
+                store_table_size = table_size;
+            }
+            let store = store.as_ref().unwrap();
+            println!(
+                "Beginning benchmark with {} threads and seed {}",
+                num_threads, seed
+            );
+            let (counts, memory) = run_benchmark(
+                store,
+                &txn_keys,
+                num_threads,
+                chunk_size,
+                seed,
+                phases.clone(),
+                open_loop_rate,
+                key_format,
+            );
+            results.push(SweepResult {
+                table_size,
+                num_threads,
+                counts,
+                memory,
+                store: StoreStats::capture(store),
+            });
+        }
+        let run_time = phases.iter().map(|phase| phase.duration).sum();
+        if results.len() > 1 {
+            print_sweep(&results, run_time);
+        }
+        if let Some(results_file) = results_file {
+            let mut output = File::create(&results_file).expect("Unable to create results file");
+            write_sweep(&mut output, &results, run_time).expect("Unable to write results");
+        }
+        if let Some(memory_file) = memory_file {
+            let mut output = File::create(&memory_file).expect("Unable to create memory file");
+            write_memory_samples(&mut output, &results).expect("Unable to write memory samples");
+        }
+        if let Some(store) = store {
+            match store.clean_storage() {
+                Ok(_) => { /*no-op*/ }
+                Err(_) => eprintln!("Unable to clear storage"),
+            }
         }
     } else if let Some(matches) = matches.subcommand_matches("generate-keys") {
         let output_file = matches
@@ -118,7 +294,104 @@ fn main() {
         let workload = matches
             .value_of("load/run")
             .expect("Must specify load or run");
-        println!("Generating sequential keys");
-        generate_sequential_keys(output_file, workload);
+        let distribution = matches
+            .value_of("distribution")
+            .expect("Distribution not specified");
+        let seed: u64 = matches
+            .value_of("seed")
+            .expect("Seed not specified")
+            .parse()
+            .expect("seed argument must be integer");
+        if workload == "run" && distribution == "uniform" {
+            println!("Generating uniform keys with seed {}", seed);
+            generate_uniform_keys(output_file, seed);
+        } else {
+            println!("Generating sequential keys");
+            generate_sequential_keys(output_file, workload);
+        }
     }
 }
+
+fn run_config_from_args(matches: &ArgMatches) -> Result<RunConfig, String> {
+    let thread_counts: Vec<u8> = match matches.values_of("threads") {
+        Some(threads) => threads
+            .map(|n| n.parse().expect("threads argument must be integers"))
+            .collect(),
+        None => vec![matches
+            .value_of("num-threads")
+            .expect("Number of threads not specified")
+            .parse()
+            .expect("num-threads argument must be integer")],
+    };
+    let repopulate = matches.is_present("repopulate");
+    let open_loop_rate: Option<u64> = matches
+        .value_of("rate")
+        .map(|rate| rate.parse().expect("rate argument must be integer"));
+    let key_format = KeyFormat::parse(
+        matches
+            .value_of("key-format")
+            .expect("Key format not specified"),
+    )?;
+    let chunk_size: usize = matches
+        .value_of("chunk-size")
+        .expect("Chunk size not specified")
+        .parse()
+        .expect("chunk-size argument must be integer");
+    let seed: u64 = matches
+        .value_of("seed")
+        .expect("Seed not specified")
+        .parse()
+        .expect("seed argument must be integer");
+    let load_keys_file = matches
+        .value_of("load")
+        .expect("File containing load transactions not specified");
+    let run_keys_file = matches
+        .value_of("run")
+        .expect("File containing run transactions not specified");
+    let phases = match matches.value_of("phases") {
+        Some(schedule) => parse_phases(schedule),
+        None => {
+            let workload = matches
+                .value_of("workload")
+                .expect("Workload not specified");
+            Phase::single(workload).map(|phase| vec![phase])
+        }
+    };
+    let phases = phases?;
+
+    let table_sizes: Vec<u64> = match matches.values_of("table-sizes") {
+        Some(sizes) => sizes
+            .map(|size| size.parse().expect("table-sizes argument must be integers"))
+            .collect(),
+        None => vec![matches
+            .value_of("table-size")
+            .expect("Table size not specified")
+            .parse()
+            .expect("table-size argument must be integer")],
+    };
+    let log_size: u64 = if matches.is_present("disk-resident") {
+        K_DISK_RESIDENT_LOG_SIZE
+    } else {
+        matches
+            .value_of("log-size")
+            .expect("Log size not specified")
+            .parse()
+            .expect("log-size argument must be integer")
+    };
+    Ok(RunConfig {
+        load_keys_file: String::from(load_keys_file),
+        run_keys_file: String::from(run_keys_file),
+        thread_counts,
+        repopulate,
+        chunk_size,
+        table_sizes,
+        log_size,
+        seed,
+        key_format,
+        open_loop_rate,
+        phases,
+        storage_dir: String::from(K_STORAGE_DIR),
+        results_file: None,
+        memory_file: matches.value_of("memory-samples").map(String::from),
+    })
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Sub;
+
+/// Hardware events counted over a part of a run
+#[derive(Clone, Copy, Default)]
+pub struct PerfCounts {
+    pub cycles: u64,
+    pub llc_misses: u64,
+    pub branch_misses: u64,
+}
+
+impl Sub for PerfCounts {
+    type Output = PerfCounts;
+
+    fn sub(self, other: PerfCounts) -> PerfCounts {
+        PerfCounts {
+            cycles: self.cycles.saturating_sub(other.cycles),
+            llc_misses: self.llc_misses.saturating_sub(other.llc_misses),
+            branch_misses: self.branch_misses.saturating_sub(other.branch_misses),
+        }
+    }
+}
+
+impl PerfCounts {
+    pub fn print(&self, name: &str, ops: usize) {
+        let ops = ops.max(1) as f64;
+        println!(
+            "{}: {:.0} cycles, {:.2} LLC misses and {:.2} branch misses per operation",
+            name,
+            self.cycles as f64 / ops,
+            self.llc_misses as f64 / ops,
+            self.branch_misses as f64 / ops
+        );
+    }
+}
+
+/// Hardware counters of the current thread and the threads it spawns after they were opened.
+///
+/// Only available on Linux with the `perf` feature, and only where perf_event_paranoid
+/// allows counting user space events.
+pub struct PerfCounters {
+    cycles: File,
+    llc_misses: File,
+    branch_misses: File,
+}
+
+impl PerfCounters {
+    pub fn read(&mut self) -> io::Result<PerfCounts> {
+        Ok(PerfCounts {
+            cycles: read_counter(&mut self.cycles)?,
+            llc_misses: read_counter(&mut self.llc_misses)?,
+            branch_misses: read_counter(&mut self.branch_misses)?,
+        })
+    }
+}
+
+fn read_counter(counter: &mut File) -> io::Result<u64> {
+    let mut value = [0; 8];
+    counter.read_exact(&mut value)?;
+    Ok(u64::from_ne_bytes(value))
+}
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+mod sys {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::FromRawFd;
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    pub const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    // Generic cache misses, which the kernel maps to last level cache misses
+    pub const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    pub const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+
+    const FLAG_INHERIT: u64 = 1 << 1;
+    const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+    // struct perf_event_attr as of PERF_ATTR_SIZE_VER5
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved: u16,
+    }
+
+    // Counts `config` in user space for the calling thread and the threads it spawns
+    // afterwards. Reading the counter sums over all of them.
+    pub fn open_counter(config: u64) -> io::Result<File> {
+        let attr = PerfEventAttr {
+            type_: PERF_TYPE_HARDWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config,
+            flags: FLAG_INHERIT | FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+            ..PerfEventAttr::default()
+        };
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0,
+                -1,
+                -1,
+                0,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { File::from_raw_fd(fd as i32) })
+    }
+}
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+impl PerfCounters {
+    /// Starts counting. Must be called before spawning the threads to count.
+    pub fn open() -> Option<PerfCounters> {
+        let counters = sys::open_counter(sys::PERF_COUNT_HW_CPU_CYCLES).and_then(|cycles| {
+            Ok(PerfCounters {
+                cycles,
+                llc_misses: sys::open_counter(sys::PERF_COUNT_HW_CACHE_MISSES)?,
+                branch_misses: sys::open_counter(sys::PERF_COUNT_HW_BRANCH_MISSES)?,
+            })
+        });
+        match counters {
+            Ok(counters) => Some(counters),
+            Err(err) => {
+                eprintln!("Unable to open perf counters: {}", err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(all(feature = "perf", target_os = "linux")))]
+impl PerfCounters {
+    pub fn open() -> Option<PerfCounters> {
+        None
+    }
+}
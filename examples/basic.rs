@@ -1,11 +1,10 @@
 extern crate faster_rs;
 
-use faster_rs::{status, FasterKv};
-use std::sync::mpsc::Receiver;
+use faster_rs::{status, FasterKv, ReadHandle};
 
 fn main() {
-    // Create a Key-Value Store
-    let store = FasterKv::default();
+    // Create a Key-Value Store, storing its files in a temporary directory removed on drop
+    let store = FasterKv::new_tempfile_backed(1 << 15, 1024 * 1024 * 1024).unwrap();
     let key0: u64 = 1;
     let value0: u64 = 1000;
     let modification: u64 = 5;
@@ -26,17 +25,11 @@ fn main() {
 
     // Read
     for i in 0..1000 {
-        // Note: need to provide type annotation for the Receiver
-        let (read, recv): (u8, Receiver<u64>) = store.read(&(key0 + i), i);
+        // Note: need to provide type annotation for the ReadHandle
+        let (read, recv): (u8, ReadHandle<u64>) = store.read(&(key0 + i), i);
         assert!(read == status::OK || read == status::PENDING);
         let val = recv.recv().unwrap();
         assert_eq!(val, value0 + i + modification);
         println!("Key: {}, Value: {}", key0 + i, val);
     }
-
-    // Clear used storage
-    match store.clean_storage() {
-        Ok(()) => {}
-        Err(_err) => panic!("Unable to clear FASTER directory"),
-    }
 }
@@ -1,9 +1,8 @@
 extern crate faster_rs;
 extern crate serde_derive;
 
-use faster_rs::{status, FasterKv};
+use faster_rs::{status, FasterKv, ReadHandle};
 use serde_derive::{Deserialize, Serialize};
-use std::sync::mpsc::Receiver;
 
 // Note: Debug annotation is just for printing later
 #[derive(Serialize, Deserialize, Debug)]
@@ -13,8 +12,8 @@ struct MyKey {
 }
 
 fn main() {
-    // Create a Key-Value Store
-    let store = FasterKv::default();
+    // Create a Key-Value Store, storing its files in a temporary directory removed on drop
+    let store = FasterKv::new_tempfile_backed(1 << 15, 1024 * 1024 * 1024).unwrap();
     let key = MyKey {
         foo: String::from("Hello"),
         bar: String::from("World"),
@@ -27,15 +26,9 @@ fn main() {
 
     assert!(store.size() > 0);
 
-    // Note: need to provide type annotation for the Receiver
-    let (read, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    // Note: need to provide type annotation for the ReadHandle
+    let (read, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(read == status::OK || read == status::PENDING);
     let val = recv.recv().unwrap();
     println!("Key: {:?}, Value: {}", key, val);
-
-    // Clear used storage
-    match store.clean_storage() {
-        Ok(()) => {}
-        Err(_err) => panic!("Unable to clear FASTER directory"),
-    }
 }
@@ -0,0 +1,97 @@
+extern crate faster_rs;
+
+use faster_rs::*;
+use std::collections::HashMap;
+
+const TABLE_SIZE: u64 = 1 << 15;
+const LOG_SIZE: u64 = 1024 * 1024 * 1024;
+const NUM_RECORDS: u64 = 10000;
+const CHECKPOINT_AT: u64 = 4000;
+const CRASH_AT: u64 = 6000;
+const WORDS: &[&str] = &["apple", "banana", "cherry", "damson", "elderberry"];
+
+// Stand-in for a Kafka partition: a log of words that can be read from any offset. Polling
+// stops at `available`, which is how the example pauses and "crashes" the consumer.
+struct Partition {
+    position: u64,
+    available: u64,
+}
+
+fn word(offset: u64) -> String {
+    let i = offset * offset % WORDS.len() as u64;
+    WORDS[i as usize].to_owned()
+}
+
+impl Source for Partition {
+    type Record = String;
+
+    fn seek(&mut self, offset: u64) {
+        self.position = offset;
+    }
+
+    fn poll(&mut self) -> Option<(u64, String)> {
+        if self.position >= self.available {
+            return None;
+        }
+        self.position += 1;
+        Some((self.position - 1, word(self.position - 1)))
+    }
+}
+
+// Counts the occurrences of every word, issuing the same writes whenever a word is replayed
+fn count_word(writes: &mut Writes, word: String) {
+    writes.rmw(&word, &1u64);
+    writes.rmw(&"total".to_owned(), &1u64);
+}
+
+fn main() {
+    let storage = Storage::temporary().unwrap();
+    let open = || {
+        FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+            .with_storage(storage.clone())
+            .build()
+            .unwrap()
+    };
+    let mut partition = Partition {
+        position: 0,
+        available: CHECKPOINT_AT,
+    };
+
+    let token = {
+        let store = open();
+        let mut ingestion = ExactlyOnce::start(&store, "word-count", 0);
+        ingestion.process(&mut partition, count_word);
+        let token = ingestion.checkpoint().unwrap();
+        println!("Checkpointed {} records", ingestion.next_offset());
+
+        // Records after the checkpoint are lost in the crash
+        partition.available = CRASH_AT;
+        ingestion.process(&mut partition, count_word);
+        println!("Crashed after {} records", ingestion.next_offset());
+        token
+    };
+
+    let store = open();
+    let mut ingestion = ExactlyOnce::recover(&store, "word-count", &token).unwrap();
+    println!("Recovered, resuming at offset {}", ingestion.next_offset());
+    partition.available = NUM_RECORDS;
+    ingestion.process(&mut partition, count_word);
+    drop(ingestion);
+
+    let mut expected: HashMap<String, u64> = HashMap::new();
+    for offset in 0..NUM_RECORDS {
+        *expected.entry(word(offset)).or_insert(0) += 1;
+    }
+    expected.insert("total".to_owned(), NUM_RECORDS);
+
+    let session = store.session();
+    for (word, count) in expected {
+        let (res, recv) = session.read::<String, u64>(&word, 1);
+        if res == status::PENDING {
+            session.complete_pending(true);
+        }
+        let counted = recv.recv().unwrap();
+        println!("{}: {}", word, counted);
+        assert_eq!(count, counted, "{} was not counted exactly once", word);
+    }
+}
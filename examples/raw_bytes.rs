@@ -0,0 +1,37 @@
+extern crate faster_rs;
+
+use faster_rs::{status, FasterKv};
+
+// Runs without the `typed` feature: cargo run --example raw_bytes --no-default-features
+fn main() {
+    // Create a Key-Value Store, storing its files in a temporary directory removed on drop
+    let store = FasterKv::new_tempfile_backed(1 << 15, 1024 * 1024 * 1024).unwrap();
+
+    // Upsert, e.g. keys and values taken straight off the network
+    for i in 0..1000u64 {
+        let key = format!("user:{}", i);
+        let upsert = store.upsert_raw(key.as_bytes(), &i.to_be_bytes(), i);
+        assert!(upsert == status::OK || upsert == status::PENDING);
+    }
+
+    // Read-Modify-Write appends the modification to the stored bytes
+    let rmw = store.rmw_raw(b"user:0", b"!", 1000);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    assert!(store.size() > 0);
+
+    // Read
+    for i in 0..1000u64 {
+        let key = format!("user:{}", i);
+        let (read, recv) = store.read_raw(key.as_bytes(), 1000 + i);
+        assert!(read == status::OK || read == status::PENDING);
+        let val = recv.recv().unwrap();
+        assert_eq!(&val[..8], &i.to_be_bytes());
+        println!("Key: {}, Value: {:?}", key, val);
+    }
+
+    // Delete
+    assert_eq!(store.delete_raw(b"user:0", 2000), status::OK);
+    let (read, _) = store.read_raw(b"user:0", 2001);
+    assert_eq!(read, status::NOT_FOUND);
+}
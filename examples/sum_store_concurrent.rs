@@ -3,11 +3,10 @@ extern crate faster_rs;
 use faster_rs::*;
 use std::env;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 
 const TABLE_SIZE: u64 = 1 << 15;
-const LOG_SIZE: u64 = 17179869184;
+const LOG_SIZE: u64 = 1024 * 1024 * 1024;
 const NUM_OPS: u64 = 1 << 25;
 const NUM_UNIQUE_KEYS: u64 = 1 << 22;
 const REFRESH_INTERVAL: u64 = 1 << 8;
@@ -50,7 +49,10 @@ fn main() {
 }
 
 fn populate(num_threads: usize) -> () {
-    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE).with_disk(STORAGE_DIR).build() {
+    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+        .with_disk(STORAGE_DIR)
+        .build()
+    {
         let store = Arc::new(store);
         let mut threads = vec![];
         let num_active_threads = Arc::new(AtomicUsize::new(0));
@@ -95,7 +97,7 @@ fn populate(num_threads: usize) -> () {
         store.start_session();
         let mut read_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
         for idx in 0..NUM_UNIQUE_KEYS {
-            let (_, receiver): (u8, Receiver<u64>) = store.read(&idx, idx);
+            let (_, receiver): (u8, ReadHandle<u64>) = store.read(&idx, idx);
             read_results.insert(idx as usize, receiver);
         }
         store.complete_pending(true);
@@ -124,13 +126,16 @@ fn populate(num_threads: usize) -> () {
 
 fn recover(token: String, num_threads: usize) -> () {
     println!("Attempting to recover");
-    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE).with_disk(STORAGE_DIR).build() {
+    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+        .with_disk(STORAGE_DIR)
+        .build()
+    {
         match store.recover(token.clone(), token.clone()) {
             Ok(rec) => {
                 println!("Recover version: {}", rec.version);
                 println!("Recover status: {}", rec.status);
                 println!("Recovered sessions: {:?}", rec.session_ids);
-                
+
                 let mut serial_nums = vec![];
                 for id in rec.session_ids {
                     serial_nums.push(store.continue_session(id));
@@ -140,13 +145,12 @@ fn recover(token: String, num_threads: usize) -> () {
                 store.start_session();
                 let mut read_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
                 for idx in 0..NUM_UNIQUE_KEYS {
-                    let (_, receiver): (u8, Receiver<u64>) = store.read(&idx, idx);
+                    let (_, receiver): (u8, ReadHandle<u64>) = store.read(&idx, idx);
                     read_results.insert(idx as usize, receiver);
                 }
                 store.complete_pending(true);
                 store.stop_session();
 
-
                 println!("Generating expected values");
                 let mut expected_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
                 expected_results.resize(NUM_UNIQUE_KEYS as usize, 0);
@@ -176,7 +180,7 @@ fn recover(token: String, num_threads: usize) -> () {
                 let mut incorrect = 0;
                 for i in 0..NUM_OPS {
                     let idx = i as u64;
-                    let (status, recv): (u8, Receiver<u64>) =
+                    let (status, recv): (u8, ReadHandle<u64>) =
                         store.read(&(idx % NUM_UNIQUE_KEYS), idx);
                     if let Ok(val) = recv.recv() {
                         let expected = *expected_results
@@ -2,7 +2,6 @@ extern crate faster_rs;
 
 use faster_rs::*;
 use std::env;
-use std::sync::mpsc::Receiver;
 
 const TABLE_SIZE: u64 = 1 << 15;
 const LOG_SIZE: u64 = 1024 * 1024 * 1024;
@@ -107,7 +106,7 @@ fn recover(token: String) -> () {
                 let mut incorrect = 0;
                 for i in 0..NUM_OPS {
                     let idx = i as u64;
-                    let (status, recv): (u8, Receiver<u64>) =
+                    let (status, recv): (u8, ReadHandle<u64>) =
                         recover_store.read(&(idx % NUM_UNIQUE_KEYS), idx);
                     if let Ok(val) = recv.recv() {
                         let expected = *expected_results
@@ -0,0 +1,31 @@
+#![no_main]
+use faster_rs::status;
+use faster_rs_fuzz::{new_store, ops, Model, Op};
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary byte keys and values through the raw API, checked against a model
+fuzz_target!(|data: &[u8]| {
+    let store = new_store();
+    let mut model = Model::default();
+    for (serial, op) in ops(data).iter().enumerate() {
+        let serial = serial as u64 + 1;
+        let result = match op {
+            Op::Upsert(key, value) => store.upsert_raw(key, value, serial),
+            Op::Rmw(key, modification) => store.rmw_raw(key, modification, serial),
+            Op::Delete(key) => store.delete_raw(key, serial),
+            Op::Read(key) => {
+                let (result, recv) = store.read_raw(key, serial);
+                store.complete_pending(true);
+                assert_eq!(recv.recv().ok().as_ref(), model.get(key));
+                result
+            }
+        };
+        assert!(
+            result == status::OK || result == status::PENDING || result == status::NOT_FOUND,
+            "{:?} failed with status {}",
+            op,
+            result
+        );
+        model.apply(op);
+    }
+});
@@ -0,0 +1,42 @@
+#![no_main]
+use faster_rs::{status, FasterError};
+use faster_rs_fuzz::{new_store, ops, Model, Op};
+use libfuzzer_sys::fuzz_target;
+
+// Arbitrary `Vec<u8>` keys and values through the typed API, checked against a model.
+// Values are also read back as other types, which may fail but must never panic.
+fuzz_target!(|data: &[u8]| {
+    let store = new_store();
+    let mut model = Model::default();
+    for (serial, op) in ops(data).iter().enumerate() {
+        let serial = serial as u64 + 1;
+        let result = match op {
+            Op::Upsert(key, value) => store.upsert(key, value, serial),
+            Op::Rmw(key, modification) => store.rmw(key, modification, serial),
+            Op::Delete(key) => store.delete(key, serial),
+            Op::Read(key) => {
+                let (result, recv) = store.read::<Vec<u8>, Vec<u8>>(key, serial);
+                let (_, as_string) = store.read::<Vec<u8>, String>(key, serial);
+                let (_, as_u64) = store.read::<Vec<u8>, u64>(key, serial);
+                store.complete_pending(true);
+                assert_eq!(recv.recv().ok().as_ref(), model.get(key));
+                for result in &[as_string.recv().err(), as_u64.recv().err()] {
+                    match result {
+                        None
+                        | Some(FasterError::NotFound)
+                        | Some(FasterError::Deserialize { .. }) => {}
+                        Some(err) => panic!("Unexpected error {:?}", err),
+                    }
+                }
+                result
+            }
+        };
+        assert!(
+            result == status::OK || result == status::PENDING || result == status::NOT_FOUND,
+            "{:?} failed with status {}",
+            op,
+            result
+        );
+        model.apply(op);
+    }
+});
@@ -0,0 +1,134 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, LOG_PAGE_SIZE};
+use std::collections::HashMap;
+
+// Small enough to open a fresh store for every input, large enough that no record
+// generated from an input is evicted from the log
+const TABLE_SIZE: u64 = 1 << 12;
+const LOG_SIZE: u64 = 4 * LOG_PAGE_SIZE;
+// Large values are generated in blocks, so a few input bytes can produce up to 1 MiB
+const LARGE_BLOCK_SIZE: usize = 4096;
+
+pub fn new_store() -> FasterKv {
+    FasterKv::new_in_memory(TABLE_SIZE, LOG_SIZE).unwrap()
+}
+
+#[derive(Debug)]
+pub enum Op {
+    Upsert(Vec<u8>, Vec<u8>),
+    Rmw(Vec<u8>, Vec<u8>),
+    Delete(Vec<u8>),
+    Read(Vec<u8>),
+}
+
+// Cursor over the fuzzer's input
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(byte)
+    }
+
+    fn take(&mut self, length: usize) -> Vec<u8> {
+        let length = length.min(self.data.len());
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        bytes.to_vec()
+    }
+
+    // Byte strings are prefixed with a tag: below 0xf0 it is the length itself, up to 0xfe
+    // a 16 bit length follows and 0xff generates a large string from a fill byte and a
+    // number of blocks
+    fn bytes(&mut self) -> Option<Vec<u8>> {
+        match self.byte()? {
+            0xff => {
+                let fill = self.byte()?;
+                let blocks = self.byte()? as usize + 1;
+                Some(vec![fill; blocks * LARGE_BLOCK_SIZE])
+            }
+            tag if tag >= 0xf0 => {
+                let length = u16::from_le_bytes([self.byte()?, self.byte()?]);
+                Some(self.take(length as usize))
+            }
+            length => Some(self.take(length as usize)),
+        }
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        let op = match self.byte()? % 4 {
+            0 => Op::Upsert(self.bytes()?, self.bytes()?),
+            1 => Op::Rmw(self.bytes()?, self.bytes()?),
+            2 => Op::Delete(self.bytes()?),
+            _ => Op::Read(self.bytes()?),
+        };
+        Some(op)
+    }
+}
+
+/// Operations encoded in `data`, up to the first one that is cut off or would append more
+/// than half the log
+pub fn ops(data: &[u8]) -> Vec<Op> {
+    let mut input = Input { data };
+    let mut ops = Vec::new();
+    let mut value_lengths = HashMap::new();
+    let mut appended = 0;
+    while let Some(op) = input.op() {
+        // Updates that don't fit in place append a new record with the whole value
+        appended += match &op {
+            Op::Upsert(key, value) => {
+                value_lengths.insert(key.clone(), value.len());
+                key.len() + value.len()
+            }
+            Op::Rmw(key, modification) => {
+                let length = value_lengths.entry(key.clone()).or_insert(0);
+                *length += modification.len();
+                key.len() + *length
+            }
+            Op::Delete(key) => {
+                value_lengths.remove(key);
+                key.len()
+            }
+            Op::Read(_) => 0,
+        } as u64;
+        if appended > LOG_SIZE / 2 {
+            break;
+        }
+        ops.push(op);
+    }
+    ops
+}
+
+/// Expected contents of the store. Read-Modify-Writes append, for raw bytes as well as
+/// for `Vec<u8>` values.
+#[derive(Default)]
+pub struct Model {
+    records: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl Model {
+    pub fn apply(&mut self, op: &Op) {
+        match op {
+            Op::Upsert(key, value) => {
+                self.records.insert(key.clone(), value.clone());
+            }
+            Op::Rmw(key, modification) => self
+                .records
+                .entry(key.clone())
+                .or_insert_with(Vec::new)
+                .extend_from_slice(modification),
+            Op::Delete(key) => {
+                self.records.remove(key);
+            }
+            Op::Read(_) => {}
+        }
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.records.get(key)
+    }
+}
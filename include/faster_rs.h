@@ -0,0 +1,107 @@
+#ifndef FASTER_RS_H
+#define FASTER_RS_H
+
+/* Generated with cbindgen from src/capi.rs, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Returned when a required pointer is null or a string is not valid UTF-8
+ */
+#define FASTER_RS_INVALID_ARGUMENT 255
+
+#define FASTER_RS_OK 0
+
+#define FASTER_RS_PENDING 1
+
+#define FASTER_RS_NOT_FOUND 2
+
+#define FASTER_RS_OUT_OF_MEMORY 3
+
+#define FASTER_RS_IO_ERROR 4
+
+#define FASTER_RS_CORRUPTION 5
+
+#define FASTER_RS_ABORTED 6
+
+typedef struct FasterKv FasterKv;
+
+/**
+ * Takes a full checkpoint.
+ *
+ * Returns the checkpoint token, which must be released with `faster_rs_free_string`, or
+ * null if the store has no storage or the checkpoint failed.
+ */
+char *faster_rs_checkpoint(const FasterKv *store);
+
+void faster_rs_close(FasterKv *store);
+
+void faster_rs_complete_pending(const FasterKv *store, bool wait);
+
+/**
+ * Continues a recovered session, returning the last serial number it persisted
+ */
+uint64_t faster_rs_continue_session(const FasterKv *store, const char *token);
+
+uint8_t faster_rs_delete(const FasterKv *store,
+                         const uint8_t *key,
+                         uintptr_t key_length,
+                         uint64_t monotonic_serial_number);
+
+void faster_rs_free_string(char *string);
+
+void faster_rs_free_value(uint8_t *value, uintptr_t value_length);
+
+/**
+ * Opens a store, persisting to `storage_dir` unless it is null.
+ *
+ * Returns null if the store could not be opened. The store must be closed with
+ * `faster_rs_close`.
+ */
+FasterKv *faster_rs_open(uint64_t table_size, uint64_t log_size, const char *storage_dir);
+
+/**
+ * Reads the value of `key`, completing the read if it goes pending.
+ *
+ * On `OK` the value is written to `value` and `value_length` and must be released with
+ * `faster_rs_free_value`.
+ */
+uint8_t faster_rs_read(const FasterKv *store,
+                       const uint8_t *key,
+                       uintptr_t key_length,
+                       uint64_t monotonic_serial_number,
+                       uint8_t **value,
+                       uintptr_t *value_length);
+
+void faster_rs_refresh(const FasterKv *store);
+
+/**
+ * Appends `modification` to the value of `key`, creating it if it does not exist
+ */
+uint8_t faster_rs_rmw(const FasterKv *store,
+                      const uint8_t *key,
+                      uintptr_t key_length,
+                      const uint8_t *modification,
+                      uintptr_t modification_length,
+                      uint64_t monotonic_serial_number);
+
+/**
+ * Starts a session on the calling thread.
+ *
+ * Returns the session token, which must be released with `faster_rs_free_string`.
+ */
+char *faster_rs_start_session(const FasterKv *store);
+
+void faster_rs_stop_session(const FasterKv *store);
+
+uint8_t faster_rs_upsert(const FasterKv *store,
+                         const uint8_t *key,
+                         uintptr_t key_length,
+                         const uint8_t *value,
+                         uintptr_t value_length,
+                         uint64_t monotonic_serial_number);
+
+#endif /* FASTER_RS_H */
@@ -4,4 +4,10 @@
 
 extern crate libc;
 
+#[cfg(not(target_arch = "wasm32"))]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+#[cfg(target_arch = "wasm32")]
+mod mock;
+#[cfg(target_arch = "wasm32")]
+pub use crate::mock::*;
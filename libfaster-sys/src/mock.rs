@@ -0,0 +1,234 @@
+// In-memory stand-in for FASTER's C interface on targets FASTER cannot be built for, such as
+// wasm32. It keeps the same signatures and ownership rules as the bindings generated from
+// faster-c.h: keys, values and modifications handed over are owned (and freed) by the callee,
+// read results are passed to the callback, and strings returned by the store live as long as it.
+// Nothing is persisted, so checkpoints and recovery always fail.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::sync::Mutex;
+
+const OK: u8 = 0;
+const NOT_FOUND: u8 = 2;
+
+pub struct faster_t {
+    records: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+    log_size: Mutex<u64>,
+    sessions: Mutex<Vec<CString>>,
+}
+
+#[repr(C)]
+pub struct faster_checkpoint_result {
+    pub checked: bool,
+    pub token: *mut c_char,
+}
+
+#[repr(C)]
+pub struct faster_recover_result {
+    pub status: u8,
+    pub version: u32,
+    pub session_ids_count: c_int,
+    pub session_ids: *mut c_char,
+}
+
+pub type read_callback =
+    Option<unsafe extern "C" fn(target: *mut c_void, value: *const u8, length: u64, status: u32)>;
+
+pub type rmw_callback = Option<
+    unsafe extern "C" fn(
+        current: *const u8,
+        length_current: u64,
+        modification: *mut u8,
+        length_modification: u64,
+        dst: *mut u8,
+    ) -> u64,
+>;
+
+unsafe fn take(ptr: *mut u8, length: u64) -> Vec<u8> {
+    Vec::from_raw_parts(ptr, length as usize, length as usize)
+}
+
+// Inserts the record while the caller holds the `records` lock
+unsafe fn append(
+    faster_t: *mut faster_t,
+    records: &mut HashMap<Vec<u8>, Vec<u8>>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+) {
+    *(*faster_t).log_size.lock().unwrap() += (key.len() + value.len()) as u64;
+    records.insert(key, value);
+}
+
+pub unsafe fn faster_open(
+    _table_size: u64,
+    _log_size: u64,
+    _pre_allocate_log: bool,
+) -> *mut faster_t {
+    Box::into_raw(Box::new(faster_t {
+        records: Mutex::new(HashMap::new()),
+        log_size: Mutex::new(0),
+        sessions: Mutex::new(Vec::new()),
+    }))
+}
+
+pub unsafe fn faster_open_with_disk(
+    table_size: u64,
+    log_size: u64,
+    _storage: *const c_char,
+    _log_mutable_fraction: f64,
+    pre_allocate_log: bool,
+) -> *mut faster_t {
+    faster_open(table_size, log_size, pre_allocate_log)
+}
+
+pub unsafe fn faster_upsert(
+    faster_t: *mut faster_t,
+    key: *mut u8,
+    key_length: u64,
+    value: *mut u8,
+    value_length: u64,
+    _monotonic_serial_number: u64,
+) -> u8 {
+    let mut records = (*faster_t).records.lock().unwrap();
+    append(
+        faster_t,
+        &mut records,
+        take(key, key_length),
+        take(value, value_length),
+    );
+    OK
+}
+
+pub unsafe fn faster_read(
+    faster_t: *mut faster_t,
+    key: *mut u8,
+    key_length: u64,
+    _monotonic_serial_number: u64,
+    cb: read_callback,
+    target: *mut c_void,
+) -> u8 {
+    let key = take(key, key_length);
+    let cb = cb.expect("Read callback must be set");
+    // The lock is released before calling back, as the callback may issue operations itself
+    let value = (*faster_t).records.lock().unwrap().get(&key).cloned();
+    match value {
+        Some(value) => {
+            cb(target, value.as_ptr(), value.len() as u64, OK as u32);
+            OK
+        }
+        None => {
+            cb(target, ptr::null(), 0, NOT_FOUND as u32);
+            NOT_FOUND
+        }
+    }
+}
+
+pub unsafe fn faster_rmw(
+    faster_t: *mut faster_t,
+    key: *mut u8,
+    key_length: u64,
+    modification: *mut u8,
+    length: u64,
+    _monotonic_serial_number: u64,
+    cb: rmw_callback,
+) -> u8 {
+    let key = take(key, key_length);
+    let mut modification = take(modification, length);
+    let cb = cb.expect("Read-Modify-Write callback must be set");
+    // The lock is held until the new value is inserted, so concurrent RMWs are not lost
+    let mut records = (*faster_t).records.lock().unwrap();
+    let value = match records.get(&key) {
+        // Like FASTER, the modification becomes the initial value of a missing key
+        None => modification,
+        Some(current) => {
+            let current_length = current.len() as u64;
+            let modification_ptr = modification.as_mut_ptr();
+            let size = cb(
+                current.as_ptr(),
+                current_length,
+                modification_ptr,
+                length,
+                ptr::null_mut(),
+            );
+            let mut value = vec![0; size as usize];
+            cb(
+                current.as_ptr(),
+                current_length,
+                modification_ptr,
+                length,
+                value.as_mut_ptr(),
+            );
+            value
+        }
+    };
+    append(faster_t, &mut records, key, value);
+    OK
+}
+
+pub unsafe fn faster_delete(
+    faster_t: *mut faster_t,
+    key: *mut u8,
+    key_length: u64,
+    _monotonic_serial_number: u64,
+) -> u8 {
+    let key = take(key, key_length);
+    match (*faster_t).records.lock().unwrap().remove(&key) {
+        Some(_) => OK,
+        None => NOT_FOUND,
+    }
+}
+
+pub unsafe fn faster_size(faster_t: *mut faster_t) -> u64 {
+    *(*faster_t).log_size.lock().unwrap()
+}
+
+pub unsafe fn faster_checkpoint(_faster_t: *mut faster_t) -> *mut faster_checkpoint_result {
+    ptr::null_mut()
+}
+
+pub unsafe fn faster_checkpoint_index(_faster_t: *mut faster_t) -> *mut faster_checkpoint_result {
+    ptr::null_mut()
+}
+
+pub unsafe fn faster_checkpoint_hybrid_log(
+    _faster_t: *mut faster_t,
+) -> *mut faster_checkpoint_result {
+    ptr::null_mut()
+}
+
+pub unsafe fn faster_recover(
+    _faster_t: *mut faster_t,
+    _index_token: *const c_char,
+    _hybrid_log_token: *const c_char,
+) -> *mut faster_recover_result {
+    ptr::null_mut()
+}
+
+pub unsafe fn faster_complete_pending(_faster_t: *mut faster_t, _b: bool) {}
+
+pub unsafe fn faster_start_session(faster_t: *mut faster_t) -> *const c_char {
+    let mut sessions = (*faster_t).sessions.lock().unwrap();
+    let guid = format!("00000000-0000-0000-0000-{:012x}", sessions.len() + 1);
+    sessions.push(CString::new(guid).unwrap());
+    sessions.last().unwrap().as_ptr()
+}
+
+pub unsafe fn faster_continue_session(_faster_t: *mut faster_t, _token: *const c_char) -> u64 {
+    0
+}
+
+pub unsafe fn faster_stop_session(_faster_t: *mut faster_t) {}
+
+pub unsafe fn faster_refresh_session(_faster_t: *mut faster_t) {}
+
+pub unsafe fn faster_dump_distribution(_faster_t: *mut faster_t) {}
+
+pub unsafe fn faster_grow_index(_faster_t: *mut faster_t) -> bool {
+    true
+}
+
+pub unsafe fn faster_destroy(faster_t: *mut faster_t) {
+    drop(Box::from_raw(faster_t));
+}
@@ -0,0 +1,273 @@
+extern crate faster_rs;
+extern crate pyo3;
+
+use faster_rs::{status, FasterKv, FasterKvBuilder, ReadHandle};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyIOError, PyKeyError, PyMemoryError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyType};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+create_exception!(faster_rs_py, FasterError, PyException);
+
+thread_local! {
+    // Stores for which the current thread has entered a session
+    static ACTIVE_SESSIONS: RefCell<HashSet<usize>> = RefCell::new(HashSet::new());
+}
+
+fn status_error(status: u8) -> PyErr {
+    match status {
+        status::OUT_OF_MEMORY => PyMemoryError::new_err("FASTER is out of memory"),
+        status::IO_ERROR => PyIOError::new_err("FASTER encountered an I/O error"),
+        status::CORRUPTION => FasterError::new_err("FASTER detected corruption"),
+        status::ABORTED => FasterError::new_err("Operation was aborted"),
+        status => FasterError::new_err(format!("Unexpected status {}", status)),
+    }
+}
+
+struct Store {
+    kv: FasterKv,
+    serial: AtomicU64,
+}
+
+impl Store {
+    fn id(&self) -> usize {
+        self as *const Store as usize
+    }
+
+    fn in_session(&self) -> bool {
+        ACTIVE_SESSIONS.with(|sessions| sessions.borrow().contains(&self.id()))
+    }
+
+    // Runs `op` in the session of the current thread, or in a short-lived session if the
+    // thread has not entered one
+    fn with_session<T, F: FnOnce(&FasterKv, u64) -> T>(&self, op: F) -> T {
+        let serial = self.serial.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.in_session() {
+            return op(&self.kv, serial);
+        }
+        self.kv.start_session();
+        let result = op(&self.kv, serial);
+        self.kv.complete_pending(true);
+        self.kv.stop_session();
+        result
+    }
+
+    // Keys and values go through the raw API, so records are byte-compatible with those
+    // written through the C interface
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, u8> {
+        self.with_session(|kv, serial| {
+            let (status, receiver): (u8, ReadHandle<Vec<u8>>) = kv.read_raw(key, serial);
+            match status {
+                status::OK => Ok(receiver.recv().ok()),
+                status::PENDING => {
+                    kv.complete_pending(true);
+                    Ok(receiver.recv().ok())
+                }
+                status::NOT_FOUND => Ok(None),
+                status => Err(status),
+            }
+        })
+    }
+
+    fn upsert(&self, key: &[u8], value: &[u8]) -> Result<(), u8> {
+        self.with_session(|kv, serial| match kv.upsert_raw(key, value, serial) {
+            status::OK | status::PENDING => Ok(()),
+            status => Err(status),
+        })
+    }
+
+    fn rmw(&self, key: &[u8], modification: &[u8]) -> Result<(), u8> {
+        self.with_session(|kv, serial| match kv.rmw_raw(key, modification, serial) {
+            status::OK | status::PENDING => Ok(()),
+            status => Err(status),
+        })
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<(), u8> {
+        self.with_session(|kv, serial| match kv.delete_raw(key, serial) {
+            status::OK | status::PENDING => Ok(()),
+            status => Err(status),
+        })
+    }
+}
+
+/// Session of the current thread, to be used as a context manager.
+///
+/// Operations on the store within the `with` block share this session instead of each
+/// starting their own.
+#[pyclass]
+struct Session {
+    store: Arc<Store>,
+    token: Option<String>,
+}
+
+#[pymethods]
+impl Session {
+    #[getter]
+    fn token(&self) -> Option<String> {
+        self.token.clone()
+    }
+
+    fn __enter__(&mut self) -> PyResult<()> {
+        if self.store.in_session() {
+            return Err(FasterError::new_err(
+                "A session is already active on this thread",
+            ));
+        }
+        self.token = Some(self.store.kv.start_session());
+        ACTIVE_SESSIONS.with(|sessions| sessions.borrow_mut().insert(self.store.id()));
+        Ok(())
+    }
+
+    fn __exit__(
+        &mut self,
+        py: Python,
+        _ty: Option<&Bound<PyType>>,
+        _value: Option<&Bound<PyAny>>,
+        _traceback: Option<&Bound<PyAny>>,
+    ) -> PyResult<bool> {
+        let store = &self.store;
+        py.detach(|| {
+            store.kv.complete_pending(true);
+            store.kv.stop_session();
+        });
+        ACTIVE_SESSIONS.with(|sessions| sessions.borrow_mut().remove(&self.store.id()));
+        Ok(false)
+    }
+}
+
+/// FASTER key-value store mapping `bytes` keys to `bytes` values.
+///
+/// Supports `store[key]`, `store[key] = value`, `del store[key]` and `key in store`.
+/// Iteration is not supported as FASTER's C interface offers no way to scan the log.
+#[pyclass(name = "FasterKv")]
+struct PyFasterKv {
+    store: Arc<Store>,
+}
+
+#[pymethods]
+impl PyFasterKv {
+    #[new]
+    #[pyo3(signature = (table_size = 32768, log_size = 1073741824, storage = None))]
+    fn new(table_size: u64, log_size: u64, storage: Option<String>) -> PyResult<Self> {
+        let mut builder = FasterKvBuilder::new(table_size, log_size);
+        if let Some(dir) = &storage {
+            builder.with_disk(dir);
+        }
+        let kv = builder
+            .build()
+            .map_err(|err| FasterError::new_err(err.to_string()))?;
+        Ok(PyFasterKv {
+            store: Arc::new(Store {
+                kv,
+                serial: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    #[pyo3(signature = (key, default = None))]
+    fn get(
+        &self,
+        py: Python,
+        key: &Bound<PyBytes>,
+        default: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let store = &self.store;
+        let key = key.as_bytes();
+        match py.detach(|| store.read(key)) {
+            Ok(Some(value)) => Ok(PyBytes::new(py, &value).into_any().unbind()),
+            Ok(None) => Ok(default.unwrap_or_else(|| py.None())),
+            Err(status) => Err(status_error(status)),
+        }
+    }
+
+    fn set(&self, py: Python, key: &Bound<PyBytes>, value: &Bound<PyBytes>) -> PyResult<()> {
+        let store = &self.store;
+        let (key, value) = (key.as_bytes(), value.as_bytes());
+        py.detach(|| store.upsert(key, value)).map_err(status_error)
+    }
+
+    /// Appends `value` to the current value of `key` with Read-Modify-Write
+    fn append(&self, py: Python, key: &Bound<PyBytes>, value: &Bound<PyBytes>) -> PyResult<()> {
+        let store = &self.store;
+        let (key, value) = (key.as_bytes(), value.as_bytes());
+        py.detach(|| store.rmw(key, value)).map_err(status_error)
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            store: Arc::clone(&self.store),
+            token: None,
+        }
+    }
+
+    /// Takes a full checkpoint and returns its token
+    fn checkpoint(&self, py: Python) -> PyResult<String> {
+        let store = &self.store;
+        match py.detach(|| store.kv.checkpoint().map_err(|err| err.to_string())) {
+            Ok(checkpoint) if checkpoint.checked => Ok(checkpoint.token),
+            Ok(_) => Err(FasterError::new_err("Checkpoint failed")),
+            Err(err) => Err(FasterError::new_err(err)),
+        }
+    }
+
+    /// Size of the log in bytes
+    fn size(&self) -> u64 {
+        self.store.kv.size()
+    }
+
+    fn __getitem__(&self, py: Python, key: &Bound<PyBytes>) -> PyResult<Py<PyAny>> {
+        let store = &self.store;
+        let bytes = key.as_bytes();
+        match py.detach(|| store.read(bytes)) {
+            Ok(Some(value)) => Ok(PyBytes::new(py, &value).into_any().unbind()),
+            Ok(None) => Err(PyKeyError::new_err(key.clone().unbind())),
+            Err(status) => Err(status_error(status)),
+        }
+    }
+
+    fn __setitem__(
+        &self,
+        py: Python,
+        key: &Bound<PyBytes>,
+        value: &Bound<PyBytes>,
+    ) -> PyResult<()> {
+        self.set(py, key, value)
+    }
+
+    fn __delitem__(&self, py: Python, key: &Bound<PyBytes>) -> PyResult<()> {
+        let store = &self.store;
+        let bytes = key.as_bytes();
+        let result = py.detach(|| match store.read(bytes) {
+            Ok(Some(_)) => store.delete(bytes).map(|_| true),
+            Ok(None) => Ok(false),
+            Err(status) => Err(status),
+        });
+        match result {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(PyKeyError::new_err(key.clone().unbind())),
+            Err(status) => Err(status_error(status)),
+        }
+    }
+
+    fn __contains__(&self, py: Python, key: &Bound<PyBytes>) -> PyResult<bool> {
+        let store = &self.store;
+        let key = key.as_bytes();
+        match py.detach(|| store.read(key)) {
+            Ok(value) => Ok(value.is_some()),
+            Err(status) => Err(status_error(status)),
+        }
+    }
+}
+
+#[pymodule]
+fn faster_rs_py(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_class::<PyFasterKv>()?;
+    m.add_class::<Session>()?;
+    m.add("FasterError", m.py().get_type::<FasterError>())?;
+    Ok(())
+}
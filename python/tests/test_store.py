@@ -0,0 +1,34 @@
+import pytest
+
+from faster_rs_py import FasterKv
+
+
+def test_set_get_delete():
+    store = FasterKv()
+    store[b"key"] = b"value"
+    assert store[b"key"] == b"value"
+    assert store.get(b"key") == b"value"
+    assert b"key" in store
+    del store[b"key"]
+    assert b"key" not in store
+    assert store.get(b"key", b"default") == b"default"
+    with pytest.raises(KeyError):
+        store[b"key"]
+    with pytest.raises(KeyError):
+        del store[b"key"]
+
+
+def test_append():
+    store = FasterKv()
+    store.append(b"key", b"hello")
+    store.append(b"key", b" world")
+    assert store[b"key"] == b"hello world"
+
+
+def test_session():
+    store = FasterKv()
+    with store.session():
+        store.set(b"key", b"")
+        store.append(b"key", b"\x00\xff")
+        assert store[b"key"] == b"\x00\xff"
+    assert store[b"key"] == b"\x00\xff"
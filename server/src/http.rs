@@ -0,0 +1,159 @@
+use crate::pool::SessionPool;
+use crate::record::{read_record, CasClock, Record};
+use faster_rs::{status, FasterKv};
+use std::error::Error;
+use std::io::Cursor;
+use std::sync::Arc;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const KV_PREFIX: &str = "/kv/";
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/// Serves the store over HTTP until the process is terminated.
+///
+/// Keys are taken from the request path and values are stored bincode-encoded along with
+/// their CAS token:
+/// * `GET /kv/<key>` returns the value, or 404 if the key does not exist
+/// * `PUT /kv/<key>` upserts the request body
+/// * `POST /kv/<key>` appends the request body to the current value (RMW)
+/// * `DELETE /kv/<key>` deletes the key
+/// * `POST /checkpoint` takes a checkpoint and returns its token
+///
+/// Every response for a key carries its CAS token as `ETag`. `PUT` and `DELETE` honour
+/// `If-Match`, answering 412 if the key has been modified since the token was handed out.
+///
+/// Requests are executed by the workers of `pool`.
+pub fn serve_http(
+    pool: &SessionPool,
+    clock: Arc<CasClock>,
+    addr: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let server = Server::http(addr)?;
+    for request in server.incoming_requests() {
+        let worker = match request.url().get(KV_PREFIX.len()..) {
+            Some(key) if request.url().starts_with(KV_PREFIX) => pool.route(key.as_bytes()),
+            _ => 0,
+        };
+        let clock = Arc::clone(&clock);
+        pool.submit(
+            worker,
+            Box::new(move |store, serial| {
+                if let Err(err) = handle_request(store, &clock, request, serial) {
+                    eprintln!("Failed to respond to request: {}", err);
+                }
+            }),
+        );
+    }
+    Ok(())
+}
+
+fn handle_request(
+    store: &FasterKv,
+    clock: &CasClock,
+    mut request: Request,
+    serial: u64,
+) -> std::io::Result<()> {
+    let url = request.url().to_owned();
+    let response = if url == "/checkpoint" && *request.method() == Method::Post {
+        match store.checkpoint() {
+            Ok(checkpoint) => Response::from_string(checkpoint.token),
+            Err(err) => Response::from_string(err.to_string()).with_status_code(500),
+        }
+    } else if url.starts_with(KV_PREFIX) && url.len() > KV_PREFIX.len() {
+        let key = url[KV_PREFIX.len()..].to_owned();
+        let if_match = if_match(&request);
+        match request.method() {
+            Method::Get => get(store, &key, serial),
+            Method::Put => {
+                let mut body = Vec::new();
+                request.as_reader().read_to_end(&mut body)?;
+                match check_cas(store, &key, if_match, serial) {
+                    Some(failed) => failed,
+                    None => {
+                        let cas = clock.next();
+                        let record = Record { cas, value: body };
+                        write_response(store, store.upsert(&key, &record, serial), cas)
+                    }
+                }
+            }
+            Method::Post => {
+                let mut body = Vec::new();
+                request.as_reader().read_to_end(&mut body)?;
+                let cas = clock.next();
+                let record = Record { cas, value: body };
+                write_response(store, store.rmw(&key, &record, serial), cas)
+            }
+            Method::Delete => match check_cas(store, &key, if_match, serial) {
+                Some(failed) => failed,
+                None => write_response(store, store.delete(&key, serial), 0),
+            },
+            _ => Response::from_string("Method not allowed").with_status_code(405),
+        }
+    } else {
+        Response::from_string("Not found").with_status_code(404)
+    };
+    request.respond(response)
+}
+
+/// The token of an `If-Match` header, `Some(None)` for `If-Match: *`
+fn if_match(request: &Request) -> Option<Option<u64>> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("If-Match"))
+        .map(|header| match header.value.as_str().trim() {
+            "*" => None,
+            // Tokens are never 0, so a malformed one fails the precondition
+            token => Some(token.trim_matches('"').parse().unwrap_or(0)),
+        })
+}
+
+/// Returns the response to send if the `If-Match` precondition does not hold
+fn check_cas(
+    store: &FasterKv,
+    key: &String,
+    if_match: Option<Option<u64>>,
+    serial: u64,
+) -> Option<HttpResponse> {
+    let expected = if_match?;
+    match read_record(store, key, serial) {
+        Ok(Some(ref record)) if expected.is_none_or(|cas| cas == record.cas) => None,
+        Ok(_) => Some(Response::from_string("Precondition failed").with_status_code(412)),
+        Err(res) => Some(status_error(res)),
+    }
+}
+
+fn etag(cas: u64) -> Header {
+    Header::from_bytes(&b"ETag"[..], format!("\"{}\"", cas)).unwrap()
+}
+
+fn get(store: &FasterKv, key: &String, serial: u64) -> HttpResponse {
+    match read_record(store, key, serial) {
+        Ok(Some(record)) => Response::from_data(record.value).with_header(etag(record.cas)),
+        Ok(None) => Response::from_string("Not found").with_status_code(404),
+        Err(res) => status_error(res),
+    }
+}
+
+fn write_response(store: &FasterKv, res: u8, cas: u64) -> HttpResponse {
+    let response = Response::from_data(Vec::new()).with_status_code(204);
+    match res {
+        status::OK | status::PENDING => {
+            if res == status::PENDING {
+                store.complete_pending(true);
+            }
+            if cas == 0 {
+                response
+            } else {
+                response.with_header(etag(cas))
+            }
+        }
+        status::NOT_FOUND => Response::from_string("Not found").with_status_code(404),
+        _ => status_error(res),
+    }
+}
+
+fn status_error(res: u8) -> HttpResponse {
+    Response::from_string(format!("Operation failed with status {}", res)).with_status_code(500)
+}
@@ -0,0 +1,13 @@
+extern crate faster_rs;
+extern crate serde_derive;
+extern crate tiny_http;
+
+mod http;
+mod pool;
+mod record;
+mod resp;
+
+pub use crate::http::serve_http;
+pub use crate::pool::{Job, SessionPool};
+pub use crate::record::CasClock;
+pub use crate::resp::serve_resp;
@@ -0,0 +1,90 @@
+extern crate clap;
+
+use clap::{App, Arg};
+use faster_rs::FasterKvBuilder;
+use faster_server::{serve_http, serve_resp, CasClock, SessionPool};
+use std::sync::Arc;
+
+fn main() {
+    let matches = App::new("faster-rs Server")
+        .about("Serve a FASTER Key-Value Store over the network")
+        .arg(
+            Arg::with_name("addr")
+                .long("addr")
+                .takes_value(true)
+                .default_value("127.0.0.1:7878")
+                .help("Address to listen on"),
+        )
+        .arg(
+            Arg::with_name("protocol")
+                .long("protocol")
+                .takes_value(true)
+                .possible_values(&["http", "resp"])
+                .default_value("http")
+                .help("Protocol to serve, resp is compatible with Redis clients"),
+        )
+        .arg(
+            Arg::with_name("num-threads")
+                .short("n")
+                .takes_value(true)
+                .default_value("4")
+                .help("Number of worker threads (and FASTER sessions) to use"),
+        )
+        .arg(
+            Arg::with_name("table-size")
+                .long("table-size")
+                .takes_value(true)
+                .default_value("1048576")
+                .help("Number of hash table buckets"),
+        )
+        .arg(
+            Arg::with_name("log-size")
+                .long("log-size")
+                .takes_value(true)
+                .default_value("1073741824")
+                .help("In-memory size of the hybrid log in bytes"),
+        )
+        .arg(
+            Arg::with_name("storage")
+                .long("storage")
+                .takes_value(true)
+                .help("Directory to persist the store in, enables checkpoints"),
+        )
+        .get_matches();
+
+    let addr = matches.value_of("addr").expect("No address specified");
+    let protocol = matches.value_of("protocol").expect("No protocol specified");
+    let num_threads: usize = matches
+        .value_of("num-threads")
+        .expect("Number of threads not specified")
+        .parse()
+        .expect("num-threads argument must be integer");
+    let table_size: u64 = matches
+        .value_of("table-size")
+        .expect("Table size not specified")
+        .parse()
+        .expect("table-size argument must be integer");
+    let log_size: u64 = matches
+        .value_of("log-size")
+        .expect("Log size not specified")
+        .parse()
+        .expect("log-size argument must be integer");
+
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    if let Some(storage) = matches.value_of("storage") {
+        builder.with_disk(storage);
+    }
+    let store = Arc::new(builder.build().expect("Unable to create FASTER store"));
+
+    println!("Listening on {} ({})", addr, protocol);
+    let pool = Arc::new(SessionPool::new(store, num_threads));
+    let clock = Arc::new(CasClock::new());
+    let result = match protocol {
+        "http" => serve_http(&pool, clock, addr),
+        "resp" => serve_resp(pool, clock, addr).map_err(|err| err.into()),
+        _ => panic!("Unexpected protocol specified. Options are: http, resp"),
+    };
+    if let Err(err) = result {
+        eprintln!("Server failed: {}", err);
+    }
+}
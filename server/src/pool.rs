@@ -0,0 +1,87 @@
+use faster_rs::{FasterKv, RefreshSchedule};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const REFRESH_INTERVAL: u64 = 64;
+const IDLE_TIMEOUT: Duration = Duration::from_millis(10);
+const QUEUE_DEPTH: usize = 1024;
+
+/// Work executed by a pool worker, given the store and the session's next serial number
+pub type Job = Box<dyn FnOnce(&FasterKv, u64) + Send>;
+
+/// Multiplexes requests from any number of connections onto a fixed set of worker threads,
+/// each owning one FASTER session for its whole lifetime.
+///
+/// Jobs are routed by key, so all operations on a key are executed in order by the same
+/// worker. This makes read-modify-write sequences on a single key atomic without locking.
+/// Connections can keep submitting jobs without waiting for earlier ones to finish, which
+/// allows clients to pipeline requests.
+pub struct SessionPool {
+    workers: Vec<SyncSender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl SessionPool {
+    pub fn new(store: Arc<FasterKv>, num_workers: usize) -> SessionPool {
+        let mut workers = vec![];
+        let mut threads = vec![];
+        for _ in 0..std::cmp::max(num_workers, 1) {
+            let (sender, receiver) = sync_channel(QUEUE_DEPTH);
+            let store = Arc::clone(&store);
+            workers.push(sender);
+            threads.push(thread::spawn(move || run_worker(&store, receiver)));
+        }
+        SessionPool { workers, threads }
+    }
+
+    /// Index of the worker responsible for `key`
+    pub fn route(&self, key: &[u8]) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.workers.len() as u64) as usize
+    }
+
+    /// Queues `job` on the given worker, blocking while its queue is full
+    pub fn submit(&self, worker: usize, job: Job) {
+        self.workers[worker]
+            .send(job)
+            .expect("Session pool worker stopped");
+    }
+}
+
+impl Drop for SessionPool {
+    fn drop(&mut self) {
+        // Closing the queues makes the workers stop their sessions and exit
+        self.workers.clear();
+        for t in self.threads.drain(..) {
+            let _ = t.join();
+        }
+    }
+}
+
+fn run_worker(store: &FasterKv, jobs: Receiver<Job>) {
+    let _session = store.start_session();
+    let mut serial: u64 = 0;
+    let mut refresh = RefreshSchedule::new(REFRESH_INTERVAL);
+    loop {
+        match jobs.recv_timeout(IDLE_TIMEOUT) {
+            Ok(job) => {
+                serial += 1;
+                job(store, serial);
+                if refresh.tick() {
+                    store.refresh();
+                }
+            }
+            // Refresh while idle so this session does not hold back the epoch
+            Err(RecvTimeoutError::Timeout) => store.refresh(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    store.complete_pending(true);
+    store.stop_session();
+}
@@ -0,0 +1,68 @@
+use faster_rs::{status, FasterKey, FasterKv, FasterRmw, ReadHandle};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A value as stored by the server, tagged with the CAS token of the write that produced it
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub cas: u64,
+    pub value: Vec<u8>,
+}
+
+impl FasterRmw for Record {
+    // Appends the modification's value and takes on its CAS token
+    fn rmw(&self, modification: Self) -> Self {
+        let mut value = Vec::with_capacity(self.value.len() + modification.value.len());
+        value.extend_from_slice(&self.value);
+        value.extend_from_slice(&modification.value);
+        Record {
+            cas: modification.cas,
+            value,
+        }
+    }
+}
+
+/// Source of CAS tokens.
+///
+/// Tokens are unique per write rather than per key, so a stale token never matches a key
+/// that has since been deleted and recreated. The clock is seeded from the system time so
+/// tokens handed out before a restart are not reused afterwards.
+pub struct CasClock(AtomicU64);
+
+impl CasClock {
+    pub fn new() -> CasClock {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_nanos() as u64)
+            .unwrap_or(0);
+        CasClock(AtomicU64::new(seed))
+    }
+
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+impl Default for CasClock {
+    fn default() -> CasClock {
+        CasClock::new()
+    }
+}
+
+/// Reads the record stored under `key`, returning the FASTER status on failure
+pub fn read_record<K: FasterKey>(
+    store: &FasterKv,
+    key: &K,
+    serial: u64,
+) -> Result<Option<Record>, u8> {
+    let (res, recv): (u8, ReadHandle<Record>) = store.read(key, serial);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        _ => Err(res),
+    }
+}
@@ -0,0 +1,452 @@
+use crate::pool::SessionPool;
+use crate::record::{read_record, CasClock, Record};
+use faster_rs::{status, FasterKv};
+use std::collections::VecDeque;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+const MAX_BULK_LENGTH: usize = 512 * 1024 * 1024;
+const MAX_ARGUMENTS: usize = 4096;
+const MAX_PIPELINED: usize = 1024;
+
+enum Reply {
+    Status(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Reply::Status(msg) => write!(writer, "+{}\r\n", msg),
+            Reply::Error(msg) => write!(writer, "-{}\r\n", msg),
+            Reply::Integer(n) => write!(writer, ":{}\r\n", n),
+            Reply::Bulk(None) => write!(writer, "$-1\r\n"),
+            Reply::Bulk(Some(value)) => {
+                write!(writer, "${}\r\n", value.len())?;
+                writer.write_all(value)?;
+                writer.write_all(b"\r\n")
+            }
+            Reply::Array(replies) => {
+                write!(writer, "*{}\r\n", replies.len())?;
+                for reply in replies {
+                    reply.write_to(writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reply to a command that has been handed to the session pool but may not have executed yet
+enum PendingReply {
+    Ready(Reply),
+    Single(Receiver<Reply>),
+    /// One reply per key, returned as an array (MGET)
+    Array(Vec<Receiver<Reply>>),
+    /// Sum of the integer replies per key (DEL)
+    Sum(Vec<Receiver<Reply>>),
+    /// OK unless one of the keys failed (MSET)
+    AllOk(Vec<Receiver<Reply>>),
+}
+
+impl PendingReply {
+    fn wait(self) -> Reply {
+        match self {
+            PendingReply::Ready(reply) => reply,
+            PendingReply::Single(recv) => wait_for(recv),
+            PendingReply::Array(recvs) => Reply::Array(recvs.into_iter().map(wait_for).collect()),
+            PendingReply::Sum(recvs) => {
+                let mut sum = 0;
+                for recv in recvs {
+                    match wait_for(recv) {
+                        Reply::Integer(n) => sum += n,
+                        err => return err,
+                    }
+                }
+                Reply::Integer(sum)
+            }
+            PendingReply::AllOk(recvs) => {
+                let mut result = Reply::Status("OK");
+                for recv in recvs {
+                    if let Reply::Error(err) = wait_for(recv) {
+                        result = Reply::Error(err);
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+fn wait_for(recv: Receiver<Reply>) -> Reply {
+    recv.recv()
+        .unwrap_or_else(|_| Reply::Error(String::from("ERR internal error")))
+}
+
+/// Serves the store using the Redis protocol (RESP) until the process is terminated.
+///
+/// Supports GET, SET, DEL, INCR, MGET, MSET and PING. Values are stored together with
+/// their CAS token, encoded the same way as by the HTTP front-end, so the two share their
+/// data. SCAN is rejected as the C interface does not expose FASTER's log scan.
+///
+/// For optimistic concurrency in the style of memcached, `GETS key` returns the value
+/// together with its CAS token and `CAS key token value` only stores the value if the key
+/// has not been modified since. CAS replies `+OK` on success, `-EXISTS` if the token is
+/// stale and a nil bulk string if the key does not exist.
+///
+/// Commands are executed by the workers of `pool`. Connections do not wait for a command
+/// to complete before reading the next one, so pipelined commands from a client are
+/// executed concurrently across workers while replies are still sent in order.
+pub fn serve_resp(pool: Arc<SessionPool>, clock: Arc<CasClock>, addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Failed to accept connection: {}", err);
+                continue;
+            }
+        };
+        let pool = Arc::clone(&pool);
+        let clock = Arc::clone(&clock);
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(&pool, &clock, stream) {
+                eprintln!("Connection closed with error: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(pool: &SessionPool, clock: &CasClock, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    let mut pending = VecDeque::new();
+    loop {
+        if reader.buffer().is_empty() || pending.len() >= MAX_PIPELINED {
+            // Answer everything received so far before blocking on more input
+            while let Some(reply) = pending.pop_front() {
+                PendingReply::wait(reply).write_to(&mut writer)?;
+            }
+            writer.flush()?;
+        }
+
+        let args = match read_command(&mut reader)? {
+            Some(args) => args,
+            None => return Ok(()),
+        };
+        if args.is_empty() {
+            continue;
+        }
+        if args[0].eq_ignore_ascii_case(b"QUIT") {
+            while let Some(reply) = pending.pop_front() {
+                PendingReply::wait(reply).write_to(&mut writer)?;
+            }
+            Reply::Status("OK").write_to(&mut writer)?;
+            return writer.flush();
+        }
+        pending.push_back(dispatch(pool, clock, args));
+    }
+}
+
+fn submit<F>(pool: &SessionPool, key: Vec<u8>, op: F) -> Receiver<Reply>
+where
+    F: FnOnce(&FasterKv, &Vec<u8>, u64) -> Reply + Send + 'static,
+{
+    let (sender, receiver) = channel();
+    let worker = pool.route(&key);
+    pool.submit(
+        worker,
+        Box::new(move |store, serial| {
+            let _ = sender.send(op(store, &key, serial));
+        }),
+    );
+    receiver
+}
+
+fn dispatch(pool: &SessionPool, clock: &CasClock, mut args: Vec<Vec<u8>>) -> PendingReply {
+    let command = String::from_utf8_lossy(&args[0]).to_uppercase();
+    match (command.as_str(), args.len()) {
+        ("PING", 1) => PendingReply::Ready(Reply::Status("PONG")),
+        ("GET", 2) => PendingReply::Single(submit(pool, args.remove(1), get)),
+        ("GETS", 2) => PendingReply::Single(submit(pool, args.remove(1), gets)),
+        ("SET", 3) => {
+            let record = Record {
+                cas: clock.next(),
+                value: args.remove(2),
+            };
+            PendingReply::Single(submit(pool, args.remove(1), move |store, key, serial| {
+                write_reply(store, store.upsert(key, &record, serial))
+            }))
+        }
+        ("CAS", 4) => {
+            let record = Record {
+                cas: clock.next(),
+                value: args.remove(3),
+            };
+            let expected = String::from_utf8_lossy(&args.remove(2)).parse().ok();
+            PendingReply::Single(submit(pool, args.remove(1), move |store, key, serial| {
+                cas(store, key, expected, record, serial)
+            }))
+        }
+        ("DEL", n) if n > 1 => {
+            PendingReply::Sum(args.drain(1..).map(|key| submit(pool, key, del)).collect())
+        }
+        ("INCR", 2) => {
+            let cas = clock.next();
+            PendingReply::Single(submit(pool, args.remove(1), move |store, key, serial| {
+                incr(store, key, cas, serial)
+            }))
+        }
+        ("MGET", n) if n > 1 => {
+            PendingReply::Array(args.drain(1..).map(|key| submit(pool, key, get)).collect())
+        }
+        ("MSET", n) if n > 1 && n % 2 == 1 => {
+            let mut recvs = vec![];
+            let mut pairs = args.drain(1..);
+            while let (Some(key), Some(value)) = (pairs.next(), pairs.next()) {
+                let record = Record {
+                    cas: clock.next(),
+                    value,
+                };
+                recvs.push(submit(pool, key, move |store, key, serial| {
+                    write_reply(store, store.upsert(key, &record, serial))
+                }));
+            }
+            PendingReply::AllOk(recvs)
+        }
+        ("SCAN", _) => PendingReply::Ready(Reply::Error(String::from("ERR SCAN is not supported"))),
+        ("PING", _)
+        | ("GET", _)
+        | ("GETS", _)
+        | ("SET", _)
+        | ("CAS", _)
+        | ("DEL", _)
+        | ("INCR", _)
+        | ("MGET", _)
+        | ("MSET", _) => PendingReply::Ready(Reply::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            command.to_lowercase()
+        ))),
+        _ => PendingReply::Ready(Reply::Error(format!("ERR unknown command '{}'", command))),
+    }
+}
+
+fn del(store: &FasterKv, key: &Vec<u8>, serial: u64) -> Reply {
+    // FASTER reports OK when deleting a key that does not exist, so check for it first.
+    // Only this worker writes to the key, so it cannot be created in between.
+    match read_record(store, key, serial) {
+        Ok(Some(_)) => (),
+        Ok(None) => return Reply::Integer(0),
+        Err(res) => return status_error(res),
+    }
+    let res = store.delete(key, serial);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Reply::Integer(1),
+        status::NOT_FOUND => Reply::Integer(0),
+        _ => status_error(res),
+    }
+}
+
+fn get(store: &FasterKv, key: &Vec<u8>, serial: u64) -> Reply {
+    match read_record(store, key, serial) {
+        Ok(record) => Reply::Bulk(record.map(|record| record.value)),
+        Err(res) => status_error(res),
+    }
+}
+
+fn gets(store: &FasterKv, key: &Vec<u8>, serial: u64) -> Reply {
+    match read_record(store, key, serial) {
+        Ok(Some(record)) => Reply::Array(vec![
+            Reply::Bulk(Some(record.value)),
+            Reply::Integer(record.cas as i64),
+        ]),
+        Ok(None) => Reply::Bulk(None),
+        Err(res) => status_error(res),
+    }
+}
+
+fn cas(
+    store: &FasterKv,
+    key: &Vec<u8>,
+    expected: Option<i64>,
+    record: Record,
+    serial: u64,
+) -> Reply {
+    // Only this worker writes to the key, so nothing can change between the read and upsert
+    match read_record(store, key, serial) {
+        Ok(Some(ref current)) if Some(current.cas as i64) == expected => {
+            write_reply(store, store.upsert(key, &record, serial))
+        }
+        Ok(Some(_)) => Reply::Error(String::from("EXISTS the value has been modified")),
+        Ok(None) => Reply::Bulk(None),
+        Err(res) => status_error(res),
+    }
+}
+
+fn incr(store: &FasterKv, key: &Vec<u8>, cas: u64, serial: u64) -> Reply {
+    let current = match read_record(store, key, serial) {
+        Ok(Some(record)) => match std::str::from_utf8(&record.value)
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+        {
+            Some(current) => current,
+            None => return Reply::Error(String::from("ERR value is not an integer")),
+        },
+        Ok(None) => 0,
+        Err(res) => return status_error(res),
+    };
+    let next = match current.checked_add(1) {
+        Some(next) => next,
+        None => return Reply::Error(String::from("ERR increment would overflow")),
+    };
+    let record = Record {
+        cas,
+        value: next.to_string().into_bytes(),
+    };
+    match write_reply(store, store.upsert(key, &record, serial)) {
+        Reply::Status(_) => Reply::Integer(next),
+        err => err,
+    }
+}
+
+fn write_reply(store: &FasterKv, res: u8) -> Reply {
+    match res {
+        status::OK => Reply::Status("OK"),
+        status::PENDING => {
+            store.complete_pending(true);
+            Reply::Status("OK")
+        }
+        _ => status_error(res),
+    }
+}
+
+fn status_error(res: u8) -> Reply {
+    Reply::Error(format!("ERR operation failed with status {}", res))
+}
+
+fn read_command<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<Vec<u8>>>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    let line = trim_line(&line);
+    if line.first() != Some(&b'*') {
+        // Inline command, as sent by e.g. telnet
+        return Ok(Some(
+            line.split(|b| *b == b' ')
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| arg.to_vec())
+                .collect(),
+        ));
+    }
+
+    let count = parse_length(&line[1..], MAX_ARGUMENTS)?;
+    let mut args = Vec::new();
+    for _ in 0..count {
+        let mut header = Vec::new();
+        reader.read_until(b'\n', &mut header)?;
+        let header = trim_line(&header);
+        if header.first() != Some(&b'$') {
+            return Err(protocol_error("expected bulk string"));
+        }
+        let length = parse_length(&header[1..], MAX_BULK_LENGTH)?;
+        // Read incrementally so a client cannot make us allocate more than it actually sends
+        let mut arg = Vec::new();
+        reader.by_ref().take(length as u64).read_to_end(&mut arg)?;
+        if arg.len() < length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut terminator = [0; 2];
+        reader.read_exact(&mut terminator)?;
+        if &terminator != b"\r\n" {
+            return Err(protocol_error("expected CRLF after bulk string"));
+        }
+        args.push(arg);
+    }
+    Ok(Some(args))
+}
+
+fn trim_line(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+    while end > 0 && (line[end - 1] == b'\n' || line[end - 1] == b'\r') {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+fn parse_length(bytes: &[u8], max: usize) -> io::Result<usize> {
+    match std::str::from_utf8(bytes).ok().and_then(|s| s.parse().ok()) {
+        Some(length) if length <= max => Ok(length),
+        _ => Err(protocol_error("invalid length")),
+    }
+}
+
+fn protocol_error(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Protocol error: {}", msg),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_command, Reply};
+    use std::io::Cursor;
+
+    #[test]
+    fn parses_array_and_inline_commands() {
+        let mut input = Cursor::new(&b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\nSET bar  baz\r\n"[..]);
+        let args = read_command(&mut input).unwrap().unwrap();
+        assert_eq!(args, vec![b"GET".to_vec(), b"foo".to_vec()]);
+        let args = read_command(&mut input).unwrap().unwrap();
+        assert_eq!(
+            args,
+            vec![b"SET".to_vec(), b"bar".to_vec(), b"baz".to_vec()]
+        );
+        assert!(read_command(&mut input).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        let mut input = Cursor::new(&b"*1\r\n:3\r\n"[..]);
+        assert!(read_command(&mut input).is_err());
+        let mut input = Cursor::new(&b"*x\r\n"[..]);
+        assert!(read_command(&mut input).is_err());
+        let mut input = Cursor::new(&b"*1000000000\r\n"[..]);
+        assert!(read_command(&mut input).is_err());
+        let mut input = Cursor::new(&b"*1\r\n$536870912\r\nGET\r\n"[..]);
+        assert!(read_command(&mut input).is_err());
+        let mut input = Cursor::new(&b"*1\r\n$3\r\nGETXX"[..]);
+        assert!(read_command(&mut input).is_err());
+    }
+
+    #[test]
+    fn encodes_replies() {
+        let mut output = Vec::new();
+        Reply::Array(vec![
+            Reply::Bulk(Some(b"foo".to_vec())),
+            Reply::Bulk(None),
+            Reply::Integer(3),
+            Reply::Status("OK"),
+            Reply::Error(String::from("ERR oops")),
+        ])
+        .write_to(&mut output)
+        .unwrap();
+        assert_eq!(
+            output,
+            b"*5\r\n$3\r\nfoo\r\n$-1\r\n:3\r\n+OK\r\n-ERR oops\r\n".to_vec()
+        );
+    }
+}
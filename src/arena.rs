@@ -0,0 +1,197 @@
+#[cfg(feature = "typed")]
+use crate::codec::encode;
+use crate::status::{self, FasterStatus};
+#[cfg(feature = "typed")]
+use crate::FasterKey;
+use crate::{FasterError, Session};
+#[cfg(feature = "typed")]
+use serde::Deserialize;
+use std::cell::UnsafeCell;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Bump allocator for the values read by a session, see
+/// [read_raw_in_arena](struct.Session.html#method.read_raw_in_arena).
+///
+/// Values are copied into chunks that are never reallocated while values point into them,
+/// so handing out shared slices while further values are added is sound. Chunks are only
+/// cleared by `reset`, which takes `&mut self` and so can't run while slices are borrowed.
+pub(crate) struct Arena {
+    chunks: UnsafeCell<Vec<Vec<u8>>>,
+}
+
+impl Arena {
+    pub fn new() -> Arena {
+        Arena {
+            chunks: UnsafeCell::new(Vec::new()),
+        }
+    }
+
+    fn alloc(&self, bytes: &[u8]) -> &[u8] {
+        // Arenas belong to a session, which never leaves its thread, and no reference to
+        // the chunk list itself outlives this call
+        let chunks = unsafe { &mut *self.chunks.get() };
+        let fits = match chunks.last() {
+            Some(chunk) => chunk.capacity() - chunk.len() >= bytes.len(),
+            None => false,
+        };
+        if !fits {
+            chunks.push(Vec::with_capacity(std::cmp::max(CHUNK_SIZE, bytes.len())));
+        }
+        let chunk = chunks.last_mut().unwrap();
+        let start = chunk.len();
+        // Stays within the capacity, so the chunk is not moved
+        chunk.extend_from_slice(bytes);
+        unsafe { std::slice::from_raw_parts(chunk.as_ptr().add(start), bytes.len()) }
+    }
+
+    fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        // Keeping the largest chunk lets the next scope reuse it without allocating
+        let largest = (0..chunks.len()).max_by_key(|&i| chunks[i].capacity());
+        if let Some(largest) = largest {
+            let mut chunk = chunks.swap_remove(largest);
+            chunk.clear();
+            chunks.clear();
+            chunks.push(chunk);
+        }
+    }
+
+    fn allocated(&self) -> usize {
+        let chunks = unsafe { &*self.chunks.get() };
+        chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+}
+
+struct ArenaRead<'a> {
+    arena: &'a Arena,
+    result: Option<Result<&'a [u8], FasterError<'static>>>,
+}
+
+#[inline(always)]
+unsafe extern "C" fn arena_read_callback(
+    context: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) {
+    let read = &mut *(context as *mut ArenaRead);
+    read.result = Some(if status == u32::from(status::OK) {
+        Ok(read
+            .arena
+            .alloc(std::slice::from_raw_parts(value, length as usize)))
+    } else if status == u32::from(status::NOT_FOUND) {
+        Err(FasterError::NotFound)
+    } else {
+        Err(FasterError::Failed(FasterStatus::from(status as u8)))
+    });
+}
+
+impl<'a> Session<'a> {
+    /// Reads the raw bytes stored under `key` into the session's arena, returning `None`
+    /// if the key does not exist.
+    ///
+    /// Values read into the arena are allocated together in large chunks and stay
+    /// available until [reset_arena](#method.reset_arena), which frees them all at once.
+    /// This saves an allocation per value for request-serving code reading many values per
+    /// request. Reads that go pending are completed before returning.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// let mut session = store.session();
+    ///
+    /// session.upsert(&1u64, &[1u8; 4], 1);
+    /// session.upsert(&2u64, &[2u8; 4], 2);
+    /// {
+    ///     let a = session.read_raw_in_arena(&1u64.to_le_bytes(), 3).unwrap();
+    ///     let b = session.read_raw_in_arena(&2u64.to_le_bytes(), 3).unwrap();
+    ///     assert_eq!(Some(&[1u8; 4][..]), a);
+    ///     assert_eq!(Some(&[2u8; 4][..]), b);
+    /// }
+    /// session.reset_arena();
+    /// ```
+    pub fn read_raw_in_arena(
+        &self,
+        key: &[u8],
+        monotonic_serial_number: u64,
+    ) -> Result<Option<&[u8]>, FasterError<'static>> {
+        let mut key = key.to_vec();
+        let key_length = key.len();
+        let key_ptr = key.as_mut_ptr();
+        std::mem::forget(key);
+        let mut read = ArenaRead {
+            arena: &self.arena,
+            result: None,
+        };
+        let res = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                key_ptr,
+                key_length as u64,
+                monotonic_serial_number,
+                Some(arena_read_callback),
+                &mut read as *mut ArenaRead as *mut libc::c_void,
+            )
+        };
+        // The callback writes to `read`, so it has to run before this returns
+        if res == status::PENDING {
+            self.complete_pending(true);
+        }
+        match read.result {
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(FasterError::NotFound)) => Ok(None),
+            Some(Err(err)) => Err(err),
+            None if res == status::NOT_FOUND => Ok(None),
+            None => Err(FasterError::Failed(FasterStatus::from(res))),
+        }
+    }
+
+    /// Like [read_raw_in_arena](#method.read_raw_in_arena), but deserializes the value. Values
+    /// borrowing from their encoding, such as `&str` or `&[u8]` fields, point into the
+    /// arena instead of being copied out of it.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// let session = store.session();
+    ///
+    /// session.upsert(&1u64, &String::from("hello"), 1);
+    /// let value: Option<&str> = session.read_in_arena(&1u64, 2).unwrap();
+    /// assert_eq!(Some("hello"), value);
+    /// ```
+    #[cfg(feature = "typed")]
+    pub fn read_in_arena<'s, K, V>(
+        &'s self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Option<V>, FasterError<'static>>
+    where
+        K: FasterKey,
+        V: Deserialize<'s>,
+    {
+        let encoded_key = encode(key);
+        match self.read_raw_in_arena(&encoded_key, monotonic_serial_number)? {
+            Some(bytes) => match bincode::deserialize(bytes) {
+                Ok(value) => Ok(Some(value)),
+                Err(source) => Err(FasterError::Deserialize {
+                    bytes: bytes.to_vec(),
+                    source,
+                }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Frees all values read into the arena at once, keeping its largest chunk for reuse
+    pub fn reset_arena(&mut self) {
+        self.arena.reset();
+    }
+
+    /// Bytes of values currently held by the arena
+    pub fn arena_bytes(&self) -> usize {
+        self.arena.allocated()
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{CheckpointToken, FasterError, FasterKv};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// What [enable_auto_checkpoint](struct.FasterKv.html#method.enable_auto_checkpoint) checkpoints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckpointKind {
+    /// The index and the hybrid log together
+    Full,
+    /// Only the hash index
+    Index,
+    /// Only the hybrid log, which is cheaper than a full checkpoint
+    HybridLog,
+}
+
+#[derive(Default)]
+struct LatestTokens {
+    index: Option<String>,
+    hybrid_log: Option<String>,
+    checkpoints: u64,
+    failures: u64,
+}
+
+/// Background thread checkpointing a store on an interval, see
+/// [enable_auto_checkpoint](struct.FasterKv.html#method.enable_auto_checkpoint).
+///
+/// Dropping it stops the thread, waiting for a checkpoint that is being started.
+pub struct AutoCheckpoint {
+    latest: Arc<Mutex<LatestTokens>>,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoCheckpoint {
+    /// Tokens of the most recently started index and hybrid log checkpoints, once there has
+    /// been at least one of each. Combined with index-only or log-only checkpoints, these
+    /// are the latest checkpoints of either kind, e.g. a rare index checkpoint and a
+    /// frequent log checkpoint taken after it.
+    pub fn latest_token(&self) -> Option<CheckpointToken> {
+        let latest = self.latest.lock().unwrap();
+        Some(CheckpointToken {
+            index: latest.index.clone()?,
+            hybrid_log: latest.hybrid_log.clone()?,
+        })
+    }
+
+    /// Number of checkpoints started so far
+    pub fn checkpoints(&self) -> u64 {
+        self.latest.lock().unwrap().checkpoints
+    }
+
+    /// Number of checkpoints FASTER refused to start, e.g. because the previous one was
+    /// still in progress
+    pub fn failures(&self) -> u64 {
+        self.latest.lock().unwrap().failures
+    }
+
+    /// Stops the thread, equivalent to dropping the handle
+    pub fn stop(self) {}
+}
+
+impl Drop for AutoCheckpoint {
+    fn drop(&mut self) {
+        // Disconnecting wakes the thread up immediately
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn take_checkpoint(store: &FasterKv, kind: CheckpointKind, latest: &Mutex<LatestTokens>) {
+    let checkpoint = match kind {
+        CheckpointKind::Full => store.checkpoint(),
+        CheckpointKind::Index => store.checkpoint_index(),
+        CheckpointKind::HybridLog => store.checkpoint_hybrid_log(),
+    };
+    let mut latest = latest.lock().unwrap();
+    match checkpoint {
+        Ok(checkpoint) if checkpoint.checked => {
+            if kind != CheckpointKind::HybridLog {
+                latest.index = Some(checkpoint.token.clone());
+            }
+            if kind != CheckpointKind::Index {
+                latest.hybrid_log = Some(checkpoint.token);
+            }
+            latest.checkpoints += 1;
+        }
+        _ => latest.failures += 1,
+    }
+}
+
+impl FasterKv {
+    /// Spawns a thread checkpointing the store every `interval` until the returned handle
+    /// is dropped, or the store is.
+    ///
+    /// Checkpoints are only started by the thread; like checkpoints taken with
+    /// [checkpoint](#method.checkpoint) they complete as the store's sessions refresh.
+    /// Stores without storage can't be checkpointed and return
+    /// [InvalidType](enum.FasterError.html#variant.InvalidType).
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{CheckpointKind, FasterKv};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let store = Arc::new(FasterKv::new_tempfile_backed(1 << 15, 1024 * 1024 * 1024).unwrap());
+    /// let checkpoints = store
+    ///     .enable_auto_checkpoint(Duration::from_secs(60), CheckpointKind::HybridLog)
+    ///     .unwrap();
+    /// assert!(checkpoints.latest_token().is_none());
+    /// checkpoints.stop();
+    /// ```
+    pub fn enable_auto_checkpoint(
+        self: &Arc<Self>,
+        interval: Duration,
+        kind: CheckpointKind,
+    ) -> Result<AutoCheckpoint, FasterError<'static>> {
+        if self.storage.is_none() {
+            return Err(FasterError::InvalidType);
+        }
+        let store = Arc::downgrade(self);
+        let latest = Arc::new(Mutex::new(LatestTokens::default()));
+        let thread_latest = Arc::clone(&latest);
+        let (stop, stopped) = channel::<()>();
+        let thread = thread::Builder::new()
+            .name(String::from("faster-checkpoint"))
+            .spawn(move || loop {
+                match stopped.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return,
+                }
+                let store = match store.upgrade() {
+                    Some(store) => store,
+                    None => return,
+                };
+                take_checkpoint(&store, kind, &thread_latest);
+            })
+            .map_err(FasterError::IOError)?;
+        Ok(AutoCheckpoint {
+            latest,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
@@ -1,67 +1,312 @@
-use crate::{FasterError, FasterKv};
+use crate::handle::{Maintenance, DEFAULT_COMPLETE_PENDING_INTERVAL, DEFAULT_REFRESH_INTERVAL};
+#[cfg(feature = "typed")]
+use crate::op_options::PendingIo;
+use crate::session::{spawn_stall_watchdog, SessionTracker, StallCallback};
+use crate::{ActiveSession, FasterError, FasterKv, Storage};
+#[cfg(feature = "typed")]
+use std::collections::HashMap;
 use std::ffi::CString;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "typed")]
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 
-pub struct FasterKvBuilder<'a> {
+/// Size of a page of FASTER's hybrid log. It is fixed when FASTER is compiled, so the log
+/// size has to be a multiple of it.
+pub const LOG_PAGE_SIZE: u64 = 1 << 25;
+
+// FASTER needs two mutable pages, one being written to and one to open once it fills up
+const MIN_MUTABLE_PAGES: u64 = 2;
+
+// Mutable fraction FASTER uses for stores kept in memory only
+const IN_MEMORY_MUTABLE_FRACTION: f64 = 0.9;
+
+type OrphanCallback = Arc<dyn Fn(&[PathBuf]) + Send + Sync>;
+
+pub struct FasterKvBuilder {
     table_size: u64,
     log_size: u64,
-    storage: Option<&'a str>,
+    storage: Option<Storage>,
     log_mutable_fraction: f64,
     pre_allocate_log: bool,
+    record_session_backtraces: bool,
+    stall_watchdog: Option<(Duration, StallCallback)>,
+    orphan_callback: Option<OrphanCallback>,
+    remove_orphaned_checkpoints: bool,
+    round_log_size: bool,
+    maintenance: Maintenance,
+    #[cfg(feature = "typed")]
+    background_pending_limit: usize,
+    #[cfg(feature = "typed")]
+    overload_limits: Option<(usize, Duration)>,
 }
 
-impl<'a> FasterKvBuilder<'a> {
-    pub fn new(table_size: u64, log_size: u64) -> FasterKvBuilder<'a> {
+impl FasterKvBuilder {
+    /// Configures a store with a hash index of `table_size` buckets and an in-memory log
+    /// buffer of `log_size` bytes. By default the store is kept in memory only, 90% of the
+    /// log buffer is mutable and the log is allocated lazily.
+    pub fn new(table_size: u64, log_size: u64) -> FasterKvBuilder {
         FasterKvBuilder {
             table_size,
             log_size,
             storage: None,
             log_mutable_fraction: 0.9,
             pre_allocate_log: false,
+            record_session_backtraces: false,
+            stall_watchdog: None,
+            orphan_callback: None,
+            remove_orphaned_checkpoints: false,
+            round_log_size: false,
+            maintenance: Maintenance {
+                refresh_interval: DEFAULT_REFRESH_INTERVAL,
+                complete_pending_interval: DEFAULT_COMPLETE_PENDING_INTERVAL,
+            },
+            #[cfg(feature = "typed")]
+            background_pending_limit: 64,
+            #[cfg(feature = "typed")]
+            overload_limits: None,
         }
     }
 
-    pub fn with_disk(&mut self, path: &'a str) -> &mut FasterKvBuilder<'a> {
-        self.storage = Some(path);
+    /// Persists the log and checkpoints to the directory at `path`
+    pub fn with_disk(&mut self, path: &str) -> &mut FasterKvBuilder {
+        self.storage = Some(Storage::at(path));
+        self
+    }
+
+    /// Persists to `storage`. Unlike `with_disk`, this can give the store a unique or
+    /// temporary directory.
+    pub fn with_storage(&mut self, storage: Storage) -> &mut FasterKvBuilder {
+        self.storage = Some(storage);
         self
     }
 
-    pub fn with_log_mutable_fraction(&mut self, fraction: f64) -> &mut FasterKvBuilder<'a> {
+    /// Fraction of the log buffer in which records are updated in place. Only used for
+    /// stores persisting to disk.
+    pub fn with_log_mutable_fraction(&mut self, fraction: f64) -> &mut FasterKvBuilder {
         self.log_mutable_fraction = fraction;
         self
     }
 
-    pub fn set_pre_allocate_log(&mut self, pre_allocate_log: bool) -> &mut FasterKvBuilder<'a> {
+    /// Allocates the whole log buffer when the store is built instead of page by page
+    pub fn set_pre_allocate_log(&mut self, pre_allocate_log: bool) -> &mut FasterKvBuilder {
         self.pre_allocate_log = pre_allocate_log;
         self
     }
 
+    /// Records a backtrace whenever a session is started, to find sessions that are never
+    /// stopped with `active_sessions`. Capturing backtraces is slow, so this is meant for
+    /// debugging only.
+    pub fn set_record_session_backtraces(&mut self, record: bool) -> &mut FasterKvBuilder {
+        self.record_session_backtraces = record;
+        self
+    }
+
+    /// Calls `callback` with the sessions that have not been refreshed for `timeout`.
+    /// A thread that stops refreshing its session keeps FASTER's safe epoch from advancing,
+    /// which eventually stalls every other thread.
+    pub fn with_stall_watchdog<F>(&mut self, timeout: Duration, callback: F) -> &mut FasterKvBuilder
+    where
+        F: Fn(&[ActiveSession]) + Send + Sync + 'static,
+    {
+        self.stall_watchdog = Some((timeout, Arc::new(callback)));
+        self
+    }
+
+    /// Calls `callback` with the checkpoints in the storage directory that were never
+    /// completed, e.g. because a previous run crashed while taking them. They are looked
+    /// for when the store is built.
+    pub fn with_orphaned_checkpoints<F>(&mut self, callback: F) -> &mut FasterKvBuilder
+    where
+        F: Fn(&[PathBuf]) + Send + Sync + 'static,
+    {
+        self.orphan_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Removes checkpoints that were never completed from the storage directory when the
+    /// store is built, so repeated crashes don't fill up the directory. No other store may be
+    /// checkpointing to the same directory at that time.
+    pub fn set_remove_orphaned_checkpoints(&mut self, remove: bool) -> &mut FasterKvBuilder {
+        self.remove_orphaned_checkpoints = remove;
+        self
+    }
+
+    /// Rounds the log size up to the nearest size FASTER accepts, a multiple of
+    /// [LOG_PAGE_SIZE](constant.LOG_PAGE_SIZE.html) no smaller than
+    /// [min_log_size](#method.min_log_size), instead of rejecting it. Lets small machines
+    /// ask for a log of a few MB and get the smallest one that works.
+    pub fn set_round_log_size(&mut self, round: bool) -> &mut FasterKvBuilder {
+        self.round_log_size = round;
+        self
+    }
+
+    /// Smallest log size FASTER accepts with the configured storage and mutable fraction.
+    ///
+    /// The log is split into pages of [LOG_PAGE_SIZE](constant.LOG_PAGE_SIZE.html), which is
+    /// fixed when FASTER is compiled, and its mutable fraction has to span at least two of
+    /// them. That is 3 pages (96 MB) for stores kept in memory only, which always use a
+    /// mutable fraction of 0.9, and for disk-backed stores with the default fraction.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKvBuilder, LOG_PAGE_SIZE};
+    /// let mut builder = FasterKvBuilder::new(1 << 15, 0);
+    /// assert_eq!(3 * LOG_PAGE_SIZE, builder.min_log_size());
+    ///
+    /// builder.with_disk("storage").with_log_mutable_fraction(0.5);
+    /// assert_eq!(4 * LOG_PAGE_SIZE, builder.min_log_size());
+    /// ```
+    pub fn min_log_size(&self) -> u64 {
+        let fraction = match self.storage {
+            None => IN_MEMORY_MUTABLE_FRACTION,
+            Some(_) => self.log_mutable_fraction,
+        };
+        let mut pages = std::cmp::max(
+            MIN_MUTABLE_PAGES,
+            (MIN_MUTABLE_PAGES as f64 / fraction).ceil() as u64,
+        );
+        // FASTER truncates the number of mutable pages, which may lose one to rounding
+        if (((pages as f64) * fraction) as u64) < MIN_MUTABLE_PAGES {
+            pages += 1;
+        }
+        pages.saturating_mul(LOG_PAGE_SIZE)
+    }
+
+    // Log size to open the store with, rounded up if requested
+    fn log_size(&self) -> u64 {
+        if !self.round_log_size {
+            return self.log_size;
+        }
+        let pages = self.log_size.div_ceil(LOG_PAGE_SIZE);
+        std::cmp::max(pages * LOG_PAGE_SIZE, self.min_log_size())
+    }
+
+    /// Limits how many background reads may be waiting for IO at the same time before
+    /// further background operations are held back, 64 by default. See
+    /// [OpOptions::with_priority](struct.OpOptions.html#method.with_priority).
+    #[cfg(feature = "typed")]
+    pub fn set_background_pending_limit(&mut self, limit: usize) -> &mut FasterKvBuilder {
+        self.background_pending_limit = limit;
+        self
+    }
+
+    /// Rejects background writes issued through the `*_with_options` variants while more
+    /// than `max_pending` foreground reads are waiting for IO, or while foreground reads
+    /// have recently taken longer than `max_read_latency` on average. Protects read latency
+    /// during ingest spikes: rejected writes return [ABORTED](status/constant.ABORTED.html),
+    /// or [Overloaded](enum.FasterError.html#variant.Overloaded) from the `try_*` variants,
+    /// and can be retried later. Only reads issued with options are monitored.
+    #[cfg(feature = "typed")]
+    pub fn with_admission_control(
+        &mut self,
+        max_pending: usize,
+        max_read_latency: Duration,
+    ) -> &mut FasterKvBuilder {
+        self.overload_limits = Some((max_pending, max_read_latency));
+        self
+    }
+
+    /// Sets how many operations a [FasterKvHandle](struct.FasterKvHandle.html) issues
+    /// between refreshing its session and between completing its pending operations,
+    /// 64 and 1600 by default
+    pub fn with_session_maintenance(
+        &mut self,
+        refresh_interval: u64,
+        complete_pending_interval: u64,
+    ) -> &mut FasterKvBuilder {
+        self.maintenance = Maintenance {
+            refresh_interval,
+            complete_pending_interval,
+        };
+        self
+    }
+
     pub fn build(&self) -> Result<FasterKv, FasterError<'static>> {
+        if !self.table_size.is_power_of_two() {
+            return Err(FasterError::BuilderError(
+                "Table size must be a power of two",
+            ));
+        }
         if !(self.log_mutable_fraction > 0.0 && self.log_mutable_fraction <= 1.0) {
             return Err(FasterError::BuilderError(
                 "Log mutable fraction must be between 0 and 1",
             ));
         }
+        let log_size = self.log_size();
+        if log_size < self.min_log_size() {
+            return Err(FasterError::LogTooSmall {
+                log_size,
+                minimum: self.min_log_size(),
+            });
+        }
+        if !log_size.is_multiple_of(LOG_PAGE_SIZE) {
+            return Err(FasterError::BuilderError(
+                "Log size must be a multiple of the log page size",
+            ));
+        }
+        if self.maintenance.refresh_interval == 0 || self.maintenance.complete_pending_interval == 0
+        {
+            return Err(FasterError::BuilderError(
+                "Session maintenance intervals must be positive",
+            ));
+        }
+        #[cfg(feature = "typed")]
+        {
+            if self.background_pending_limit == 0 {
+                return Err(FasterError::BuilderError(
+                    "Background pending limit must be positive",
+                ));
+            }
+        }
+        // Checkpoints are only left incomplete by earlier runs before this store is opened
+        if let Some(storage) = &self.storage {
+            if self.orphan_callback.is_some() || self.remove_orphaned_checkpoints {
+                let orphaned = storage.orphaned_checkpoints()?;
+                if let Some(callback) = &self.orphan_callback {
+                    callback(&orphaned);
+                }
+                if self.remove_orphaned_checkpoints {
+                    storage.remove_orphaned_checkpoints()?;
+                }
+            }
+        }
+        let sessions = Arc::new(SessionTracker::new(
+            self.record_session_backtraces,
+            self.stall_watchdog.is_some(),
+        ));
+        if let Some((timeout, callback)) = &self.stall_watchdog {
+            spawn_stall_watchdog(Arc::downgrade(&sessions), *timeout, Arc::clone(callback));
+        }
         unsafe {
-            let mut storage_dir = None;
-            let faster_t = match self.storage {
-                None => ffi::faster_open(self.table_size, self.log_size, self.pre_allocate_log),
-                Some(path) => {
-                    let storage_str = CString::new(path).unwrap();
-                    let ptr_raw = storage_str.into_raw();
-                    let ft = ffi::faster_open_with_disk(
+            let faster_t = match &self.storage {
+                None => ffi::faster_open(self.table_size, log_size, self.pre_allocate_log),
+                Some(storage) => {
+                    let storage_str = CString::new(storage.path()).unwrap();
+                    ffi::faster_open_with_disk(
                         self.table_size,
-                        self.log_size,
-                        ptr_raw,
+                        log_size,
+                        storage_str.as_ptr(),
                         self.log_mutable_fraction,
                         self.pre_allocate_log,
-                    );
-                    storage_dir = CString::from_raw(ptr_raw).into_string().ok();
-                    ft
+                    )
                 }
             };
             Ok(FasterKv {
                 faster_t,
-                storage_dir,
+                storage: self.storage.clone(),
+                sessions,
+                maintenance: self.maintenance,
+                #[cfg(feature = "typed")]
+                in_flight_reads: Arc::new(Mutex::new(HashMap::new())),
+                #[cfg(feature = "typed")]
+                pending_io: Arc::new(PendingIo::new(
+                    self.background_pending_limit,
+                    self.overload_limits,
+                )),
+                #[cfg(feature = "typed")]
+                views: RwLock::new(HashMap::new()),
             })
         }
     }
@@ -69,7 +314,10 @@ impl<'a> FasterKvBuilder<'a> {
 
 #[cfg(test)]
 pub mod tests {
-    use super::FasterKvBuilder;
+    use super::{FasterKvBuilder, LOG_PAGE_SIZE};
+    use crate::FasterError;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
     use tempfile::TempDir;
     #[test]
     fn can_build_with_disk() {
@@ -81,7 +329,82 @@ pub mod tests {
             .set_pre_allocate_log(true)
             .with_log_mutable_fraction(0.8);
         let kv = builder.build().unwrap();
-        let storage = &kv.storage_dir;
-        assert_eq!(storage.as_ref().unwrap(), dir_str);
+        let storage = kv.storage.as_ref().unwrap();
+        assert_eq!(storage.path(), dir_str);
+        assert!(!storage.is_temporary());
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        let invalid = &[
+            (1000, LOG_PAGE_SIZE * 4),
+            (1 << 15, LOG_PAGE_SIZE * 4 + 1000),
+        ];
+        for (table_size, log_size) in invalid {
+            match FasterKvBuilder::new(*table_size, *log_size).build() {
+                Err(FasterError::BuilderError(_)) => {}
+                _ => panic!("Expected ({}, {}) to be rejected", table_size, log_size),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_logs_below_minimum_size() {
+        for log_size in &[0, 1000, LOG_PAGE_SIZE, LOG_PAGE_SIZE * 2] {
+            match FasterKvBuilder::new(1 << 15, *log_size).build() {
+                Err(FasterError::LogTooSmall { minimum, .. }) => {
+                    assert_eq!(minimum, LOG_PAGE_SIZE * 3)
+                }
+                _ => panic!("Expected a log of {} bytes to be too small", log_size),
+            }
+        }
+    }
+
+    #[test]
+    fn rounds_small_log_sizes_up() {
+        let mut builder = FasterKvBuilder::new(1 << 15, 40 * 1024 * 1024);
+        assert!(builder.build().is_err());
+        builder.set_round_log_size(true);
+        assert_eq!(builder.log_size(), builder.min_log_size());
+        assert!(builder.build().is_ok());
+
+        let mut builder = FasterKvBuilder::new(1 << 15, LOG_PAGE_SIZE * 4 + 1);
+        builder.set_round_log_size(true);
+        assert_eq!(builder.log_size(), LOG_PAGE_SIZE * 5);
+    }
+
+    #[test]
+    fn rejects_invalid_mutable_fraction() {
+        let mut builder = FasterKvBuilder::new(1 << 15, LOG_PAGE_SIZE * 4);
+        builder.with_log_mutable_fraction(1.5);
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn rejects_zero_session_maintenance_intervals() {
+        for (refresh, complete_pending) in &[(0, 1600), (64, 0)] {
+            let mut builder = FasterKvBuilder::new(1 << 15, LOG_PAGE_SIZE * 4);
+            builder.with_session_maintenance(*refresh, *complete_pending);
+            assert!(builder.build().is_err());
+        }
+    }
+
+    #[test]
+    fn reports_and_removes_orphaned_checkpoints() {
+        let dir = TempDir::new().unwrap();
+        let orphan = dir.path().join("cpr-checkpoints").join("crashed");
+        fs::create_dir_all(&orphan).unwrap();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+        builder
+            .with_disk(dir.path().to_str().unwrap())
+            .with_orphaned_checkpoints(move |orphaned| {
+                sink.lock().unwrap().extend_from_slice(orphaned)
+            })
+            .set_remove_orphaned_checkpoints(true);
+        let _kv = builder.build().unwrap();
+        assert_eq!(*reported.lock().unwrap(), vec![orphan.clone()]);
+        assert!(!orphan.exists());
     }
 }
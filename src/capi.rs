@@ -0,0 +1,231 @@
+// Stable C ABI on top of the Rust wrapper. Keys and values are passed as byte buffers and
+// stored through the raw API, so Read-Modify-Write appends the modification to the value.
+// The matching header `include/faster_rs.h` is generated with cbindgen.
+#![allow(clippy::missing_safety_doc)]
+use crate::{status, FasterKv, FasterKvBuilder};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+/// Returned when a required pointer is null or a string is not valid UTF-8
+pub const INVALID_ARGUMENT: u8 = 255;
+
+unsafe fn to_bytes<'a>(ptr: *const u8, length: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() {
+        if length == 0 {
+            return Some(&[]);
+        }
+        return None;
+    }
+    Some(slice::from_raw_parts(ptr, length))
+}
+
+unsafe fn to_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+fn to_c_string(string: String) -> *mut c_char {
+    match CString::new(string) {
+        Ok(string) => string.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Opens a store, persisting to `storage_dir` unless it is null.
+///
+/// Returns null if the store could not be opened. The store must be closed with
+/// `faster_rs_close`.
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_open(
+    table_size: u64,
+    log_size: u64,
+    storage_dir: *const c_char,
+) -> *mut FasterKv {
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    if !storage_dir.is_null() {
+        match to_str(storage_dir) {
+            Some(dir) => {
+                builder.with_disk(dir);
+            }
+            None => return ptr::null_mut(),
+        }
+    }
+    match builder.build() {
+        Ok(store) => Box::into_raw(Box::new(store)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_close(store: *mut FasterKv) {
+    if !store.is_null() {
+        drop(Box::from_raw(store));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_upsert(
+    store: *const FasterKv,
+    key: *const u8,
+    key_length: usize,
+    value: *const u8,
+    value_length: usize,
+    monotonic_serial_number: u64,
+) -> u8 {
+    match (
+        store.as_ref(),
+        to_bytes(key, key_length),
+        to_bytes(value, value_length),
+    ) {
+        (Some(store), Some(key), Some(value)) => {
+            store.upsert_raw(key, value, monotonic_serial_number)
+        }
+        _ => INVALID_ARGUMENT,
+    }
+}
+
+/// Reads the value of `key`, completing the read if it goes pending.
+///
+/// On `OK` the value is written to `value` and `value_length` and must be released with
+/// `faster_rs_free_value`.
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_read(
+    store: *const FasterKv,
+    key: *const u8,
+    key_length: usize,
+    monotonic_serial_number: u64,
+    value: *mut *mut u8,
+    value_length: *mut usize,
+) -> u8 {
+    let (store, key) = match (store.as_ref(), to_bytes(key, key_length)) {
+        (Some(store), Some(key)) if !value.is_null() && !value_length.is_null() => (store, key),
+        _ => return INVALID_ARGUMENT,
+    };
+    let (status, receiver) = store.read_raw(key, monotonic_serial_number);
+    if status == status::PENDING {
+        store.complete_pending(true);
+    } else if status != status::OK {
+        return status;
+    }
+    match receiver.recv() {
+        Ok(read) => {
+            let read = read.into_boxed_slice();
+            *value_length = read.len();
+            *value = Box::into_raw(read) as *mut u8;
+            status::OK
+        }
+        Err(_) => status::NOT_FOUND,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_free_value(value: *mut u8, value_length: usize) {
+    if !value.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            value,
+            value_length,
+        )));
+    }
+}
+
+/// Appends `modification` to the value of `key`, creating it if it does not exist
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_rmw(
+    store: *const FasterKv,
+    key: *const u8,
+    key_length: usize,
+    modification: *const u8,
+    modification_length: usize,
+    monotonic_serial_number: u64,
+) -> u8 {
+    match (
+        store.as_ref(),
+        to_bytes(key, key_length),
+        to_bytes(modification, modification_length),
+    ) {
+        (Some(store), Some(key), Some(modification)) => {
+            store.rmw_raw(key, modification, monotonic_serial_number)
+        }
+        _ => INVALID_ARGUMENT,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_delete(
+    store: *const FasterKv,
+    key: *const u8,
+    key_length: usize,
+    monotonic_serial_number: u64,
+) -> u8 {
+    match (store.as_ref(), to_bytes(key, key_length)) {
+        (Some(store), Some(key)) => store.delete_raw(key, monotonic_serial_number),
+        _ => INVALID_ARGUMENT,
+    }
+}
+
+/// Starts a session on the calling thread.
+///
+/// Returns the session token, which must be released with `faster_rs_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_start_session(store: *const FasterKv) -> *mut c_char {
+    match store.as_ref() {
+        Some(store) => to_c_string(store.start_session()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Continues a recovered session, returning the last serial number it persisted
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_continue_session(
+    store: *const FasterKv,
+    token: *const c_char,
+) -> u64 {
+    match (store.as_ref(), to_str(token)) {
+        (Some(store), Some(token)) => store.continue_session(token.to_owned()),
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_stop_session(store: *const FasterKv) {
+    if let Some(store) = store.as_ref() {
+        store.stop_session();
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_refresh(store: *const FasterKv) {
+    if let Some(store) = store.as_ref() {
+        store.refresh();
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_complete_pending(store: *const FasterKv, wait: bool) {
+    if let Some(store) = store.as_ref() {
+        store.complete_pending(wait);
+    }
+}
+
+/// Takes a full checkpoint.
+///
+/// Returns the checkpoint token, which must be released with `faster_rs_free_string`, or
+/// null if the store has no storage or the checkpoint failed.
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_checkpoint(store: *const FasterKv) -> *mut c_char {
+    match store.as_ref().map(|store| store.checkpoint()) {
+        Some(Ok(checkpoint)) if checkpoint.checked => to_c_string(checkpoint.token),
+        _ => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn faster_rs_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::faster_traits::read_result;
+use crate::read_handle::ReadResult;
+use crate::status::FasterStatus;
+use crate::FasterError;
+
+use crate::codec::FasterDeserialize;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+/// Reads currently in flight, keyed by value type and encoded key. Each waiter is a
+/// `Sender<ReadResult<V>>` for the value type the read was issued for.
+pub type InFlightReads = Mutex<HashMap<(TypeId, Vec<u8>), Vec<Box<dyn Any + Send>>>>;
+
+pub struct CoalescedRead {
+    pub in_flight: Arc<InFlightReads>,
+    pub key: (TypeId, Vec<u8>),
+}
+
+impl CoalescedRead {
+    // Removes the read from the reads in flight and sends every read waiting on it the
+    // result `result` produces, if any
+    fn complete<T, F>(self, result: F)
+    where
+        T: 'static,
+        F: Fn() -> Option<ReadResult<T>>,
+    {
+        let waiters = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .remove(&self.key)
+            .unwrap_or_default();
+        for waiter in waiters {
+            if let Ok(sender) = waiter.downcast::<Sender<ReadResult<T>>>() {
+                if let Some(result) = result() {
+                    let _ = sender.send(result);
+                }
+            }
+        }
+    }
+
+    /// Fails every read waiting on a read FASTER rejected with `status`
+    pub fn fail<T: 'static>(self, status: u8) {
+        self.complete::<T, _>(|| Some(Err(FasterError::Failed(FasterStatus::from(status)))));
+    }
+}
+
+#[inline(always)]
+pub unsafe extern "C" fn coalesced_read_callback<T>(
+    context: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    T: FasterDeserialize + Send + 'static,
+{
+    let boxed_context = Box::from_raw(context as *mut CoalescedRead);
+    let context = *boxed_context;
+    context.complete::<T, _>(|| read_result(value, length, status));
+}
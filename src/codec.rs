@@ -0,0 +1,87 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Error returned when stored bytes can't be decoded, see
+/// [Deserialize](enum.FasterError.html#variant.Deserialize)
+pub type DecodeError = bincode::Error;
+
+/// Builds a [DecodeError](type.DecodeError.html) with a custom message, for
+/// [FasterDeserialize](trait.FasterDeserialize.html) implementations
+pub fn decode_error<M: Display>(message: M) -> DecodeError {
+    Box::new(bincode::ErrorKind::Custom(message.to_string()))
+}
+
+/// Encoding of keys and values passed to the store.
+///
+/// Every type implementing serde's `Serialize` is encoded with bincode. Other types can
+/// implement this trait, together with [FasterDeserialize](trait.FasterDeserialize.html),
+/// to be stored in their own format, e.g. a hand-rolled encoding for hot keys or one
+/// produced by another serialization framework. A type can only have one encoding, so
+/// types deriving `Serialize` can't implement it.
+///
+/// Keys are compared by their encoding, so equal keys must always encode to the same bytes.
+///
+/// # Example
+/// ```
+/// use faster_rs::{decode_error, status, DecodeError, FasterDeserialize, FasterKv, FasterSerialize};
+///
+/// // Stored as its 4 big-endian bytes instead of bincode's encoding
+/// #[derive(Debug, PartialEq)]
+/// struct Ipv4([u8; 4]);
+///
+/// impl FasterSerialize for Ipv4 {
+///     fn faster_serialize(&self) -> Vec<u8> {
+///         self.0.to_vec()
+///     }
+/// }
+///
+/// impl FasterDeserialize for Ipv4 {
+///     fn faster_deserialize(bytes: &[u8]) -> Result<Ipv4, DecodeError> {
+///         match bytes {
+///             &[a, b, c, d] => Ok(Ipv4([a, b, c, d])),
+///             _ => Err(decode_error("An address has 4 bytes")),
+///         }
+///     }
+/// }
+///
+/// let store = FasterKv::default();
+/// store.upsert(&Ipv4([10, 0, 0, 1]), &String::from("gateway"), 1);
+/// let (res, recv) = store.read::<Ipv4, String>(&Ipv4([10, 0, 0, 1]), 1);
+/// assert_eq!(status::OK, res);
+/// assert_eq!("gateway", recv.recv().unwrap());
+///
+/// // The raw API sees the custom encoding
+/// let (_, recv) = store.read_raw(&[10, 0, 0, 1], 1);
+/// assert!(recv.recv().is_ok());
+/// ```
+pub trait FasterSerialize {
+    fn faster_serialize(&self) -> Vec<u8>;
+}
+
+/// Decoding of values read from the store, the counterpart of
+/// [FasterSerialize](trait.FasterSerialize.html)
+pub trait FasterDeserialize: Sized {
+    fn faster_deserialize(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+// Encodes a key or value to hand over to FASTER, which frees it assuming its capacity is
+// its length
+#[inline]
+pub(crate) fn encode<T: FasterSerialize>(value: &T) -> Vec<u8> {
+    value.faster_serialize().into_boxed_slice().into_vec()
+}
+
+impl<T: Serialize> FasterSerialize for T {
+    #[inline]
+    fn faster_serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
+
+impl<T: DeserializeOwned> FasterDeserialize for T {
+    #[inline]
+    fn faster_deserialize(bytes: &[u8]) -> Result<T, DecodeError> {
+        bincode::deserialize(bytes)
+    }
+}
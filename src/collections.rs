@@ -0,0 +1,104 @@
+use crate::{FasterKey, FasterKv};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::Hash;
+
+// Helpers for values that are collections, built on the FasterRmw implementations of
+// HashSet and BTreeMap
+impl FasterKv {
+    /// Adds `member` to the set stored under `key`
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// use std::collections::HashSet;
+    /// let store = FasterKv::default();
+    ///
+    /// store.add_to_set(&1, &String::from("red"), 1);
+    /// store.add_to_set(&1, &String::from("blue"), 1);
+    /// store.remove_from_set(&1, &String::from("red"), 1);
+    ///
+    /// let (res, recv) = store.read::<i32, HashSet<String>>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(vec!["blue"], recv.recv().unwrap().into_iter().collect::<Vec<_>>());
+    /// ```
+    pub fn add_to_set<K, T>(&self, key: &K, member: &T, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+        T: Clone + Serialize + DeserializeOwned + Hash + Eq,
+    {
+        let mut members = HashSet::with_capacity(1);
+        members.insert(member.clone());
+        self.rmw(key, &members, monotonic_serial_number)
+    }
+
+    /// Removes `member` from the set stored under `key`. A missing key is set to an
+    /// empty set.
+    pub fn remove_from_set<K, T>(&self, key: &K, member: &T, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+        T: Clone + Serialize + DeserializeOwned + Hash + Eq,
+    {
+        self.update_field(
+            key,
+            |members: &mut HashSet<T>| {
+                members.remove(member);
+            },
+            monotonic_serial_number,
+        )
+    }
+
+    /// Inserts `value` under `map_key` into the map stored under `key`, replacing any
+    /// previous value
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// use std::collections::BTreeMap;
+    /// let store = FasterKv::default();
+    ///
+    /// store.map_insert(&1, &String::from("alice"), &3u64, 1);
+    /// store.map_insert(&1, &String::from("bob"), &5u64, 1);
+    /// store.map_remove::<_, _, u64>(&1, &String::from("alice"), 1);
+    ///
+    /// let (res, recv) = store.read::<i32, BTreeMap<String, u64>>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// let scores = recv.recv().unwrap();
+    /// assert_eq!(1, scores.len());
+    /// assert_eq!(Some(&5), scores.get("bob"));
+    /// ```
+    pub fn map_insert<K, K2, V2>(
+        &self,
+        key: &K,
+        map_key: &K2,
+        value: &V2,
+        monotonic_serial_number: u64,
+    ) -> u8
+    where
+        K: FasterKey,
+        K2: Clone + Serialize + DeserializeOwned + Ord,
+        V2: Clone + Serialize + DeserializeOwned,
+    {
+        let mut entries = BTreeMap::new();
+        entries.insert(map_key.clone(), value.clone());
+        self.rmw(key, &entries, monotonic_serial_number)
+    }
+
+    /// Removes `map_key` from the map stored under `key`. A missing key is set to an
+    /// empty map.
+    pub fn map_remove<K, K2, V2>(&self, key: &K, map_key: &K2, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+        K2: Clone + Serialize + DeserializeOwned + Ord,
+        V2: Clone + Serialize + DeserializeOwned,
+    {
+        self.update_field(
+            key,
+            |entries: &mut BTreeMap<K2, V2>| {
+                entries.remove(map_key);
+            },
+            monotonic_serial_number,
+        )
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{status, FasterKey, FasterKv, FasterRmw};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+// Increment that wraps around instead of overflowing. It is encoded like a u64, so FASTER
+// can store it as the initial value of a missing counter and update it in place.
+#[derive(Serialize, Deserialize)]
+struct Increment(u64);
+
+impl FasterRmw for Increment {
+    fn rmw(&self, modification: Self) -> Self {
+        Increment(self.0.wrapping_add(modification.0))
+    }
+}
+
+type DirtyKeys<K> = Mutex<HashSet<K>>;
+
+/// Store of `u64` counters, one of FASTER's flagship use cases.
+///
+/// Increments are Read-Modify-Writes that FASTER applies in place while the counter is in
+/// the mutable region of the log. Decrements stop at zero.
+///
+/// # Example
+/// ```
+/// use faster_rs::{CounterStore, FasterKv};
+/// let counters = CounterStore::new(FasterKv::default());
+/// let views = String::from("page-views");
+///
+/// counters.incr(&views, 3, 1);
+/// counters.decr(&views, 1, 1);
+/// assert_eq!(2, counters.get(&views, 1).unwrap());
+/// assert_eq!(0, counters.get(&String::from("clicks"), 1).unwrap());
+/// ```
+pub struct CounterStore<K> {
+    store: Arc<FasterKv>,
+    dirty: Option<Arc<DirtyKeys<K>>>,
+}
+
+impl<K> CounterStore<K>
+where
+    K: FasterKey + Hash + Eq + Clone + Send + 'static,
+{
+    pub fn new(store: FasterKv) -> CounterStore<K> {
+        CounterStore {
+            store: Arc::new(store),
+            dirty: None,
+        }
+    }
+
+    /// Creates a counter store that passes every counter changed since the previous dump to
+    /// `sink` each `interval`.
+    ///
+    /// The log can't be scanned through FASTER's C interface, so the changed keys are
+    /// tracked in memory until they are dumped. Dumping stops when the store is dropped.
+    pub fn with_dump<F>(store: FasterKv, interval: Duration, sink: F) -> CounterStore<K>
+    where
+        F: Fn(&K, u64) + Send + 'static,
+    {
+        let store = Arc::new(store);
+        let dirty = Arc::new(Mutex::new(HashSet::new()));
+        spawn_dump(
+            Arc::downgrade(&store),
+            Arc::downgrade(&dirty),
+            interval,
+            sink,
+        );
+        CounterStore {
+            store,
+            dirty: Some(dirty),
+        }
+    }
+
+    /// Underlying store, e.g. to manage sessions or take checkpoints
+    pub fn store(&self) -> &FasterKv {
+        &self.store
+    }
+
+    fn changed(&self, key: &K) {
+        if let Some(dirty) = &self.dirty {
+            dirty.lock().unwrap().insert(key.clone());
+        }
+    }
+
+    pub fn incr(&self, key: &K, by: u64, monotonic_serial_number: u64) -> u8 {
+        self.changed(key);
+        self.store.rmw(key, &Increment(by), monotonic_serial_number)
+    }
+
+    pub fn decr(&self, key: &K, by: u64, monotonic_serial_number: u64) -> u8 {
+        self.changed(key);
+        self.store.update_field(
+            key,
+            |count: &mut u64| *count = count.saturating_sub(by),
+            monotonic_serial_number,
+        )
+    }
+
+    /// Current value of the counter, where missing counters are zero
+    pub fn get(&self, key: &K, monotonic_serial_number: u64) -> Result<u64, u8> {
+        read_count(&self.store, key, monotonic_serial_number)
+    }
+
+    pub fn reset(&self, key: &K, monotonic_serial_number: u64) -> u8 {
+        self.changed(key);
+        self.store.upsert(key, &0u64, monotonic_serial_number)
+    }
+}
+
+fn read_count<K: FasterKey>(
+    store: &FasterKv,
+    key: &K,
+    monotonic_serial_number: u64,
+) -> Result<u64, u8> {
+    let (res, recv) = store.read::<K, u64>(key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().unwrap_or(0)),
+        status::NOT_FOUND => Ok(0),
+        res => Err(res),
+    }
+}
+
+fn spawn_dump<K, F>(store: Weak<FasterKv>, dirty: Weak<DirtyKeys<K>>, interval: Duration, sink: F)
+where
+    K: FasterKey + Hash + Eq + Send + 'static,
+    F: Fn(&K, u64) + Send + 'static,
+{
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        let (store, dirty) = match (store.upgrade(), dirty.upgrade()) {
+            (Some(store), Some(dirty)) => (store, dirty),
+            _ => return,
+        };
+        let keys = std::mem::take(&mut *dirty.lock().unwrap());
+        if keys.is_empty() {
+            continue;
+        }
+        store.start_session();
+        for key in keys {
+            if let Ok(count) = read_count(&store, &key, 1) {
+                sink(&key, count);
+            }
+        }
+        store.stop_session();
+    });
+}
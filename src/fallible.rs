@@ -0,0 +1,221 @@
+use crate::codec::encode;
+use crate::read_handle::{read_channel, ReadResult};
+use crate::status::{self, FasterStatus};
+use crate::{FasterError, FasterKey, FasterKv, FasterRmw, FasterValue, OpOptions};
+
+use std::sync::mpsc::Sender;
+
+// Reports whether a record was found, without deserializing its value
+#[inline(always)]
+unsafe extern "C" fn exists_callback(
+    sender: *mut libc::c_void,
+    _value: *const u8,
+    _length: u64,
+    status: u32,
+) {
+    let boxed_sender = Box::from_raw(sender as *mut Sender<ReadResult<bool>>);
+    let sender = *boxed_sender;
+    if status == u32::from(status::OK) {
+        let _ = sender.send(Ok(true));
+    } else if status == u32::from(status::NOT_FOUND) {
+        let _ = sender.send(Ok(false));
+    }
+}
+
+// Operations returning a Result instead of a status code. Pending operations are completed
+// before returning.
+impl FasterKv {
+    /// Like [upsert](#method.upsert), but returns an error for failed upserts
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterError, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.try_upsert(&1, &42, 1).unwrap();
+    /// assert_eq!(42, store.try_read::<i32, i32>(&1, 1).unwrap());
+    /// match store.try_read::<i32, i32>(&2, 1) {
+    ///     Err(FasterError::NotFound) => {}
+    ///     other => panic!("Unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn try_upsert<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let status = self.upsert(key, value, monotonic_serial_number);
+        self.complete(status)
+    }
+
+    /// Like [read](#method.read), but waits for the value
+    pub fn try_read<K, V>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<V, FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let (status, recv) = self.read(key, monotonic_serial_number);
+        match self.complete(status) {
+            Ok(()) => recv.recv(),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Like [rmw](#method.rmw), but returns an error for failed Read-Modify-Writes
+    pub fn try_rmw<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterRmw,
+    {
+        let status = self.rmw(key, value, monotonic_serial_number);
+        self.complete(status)
+    }
+
+    /// Like [delete](#method.delete), but returns an error for failed deletes
+    pub fn try_delete<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+    {
+        let status = self.delete(key, monotonic_serial_number);
+        self.complete(status)
+    }
+
+    /// Like [upsert_with_options](#method.upsert_with_options), but returns an error for
+    /// failed upserts, including [Overloaded](enum.FasterError.html#variant.Overloaded) for
+    /// background upserts rejected by admission control
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterError, FasterKvBuilder, OpOptions, Priority};
+    /// use std::time::Duration;
+    ///
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_admission_control(16, Duration::from_millis(5))
+    ///     .build()
+    ///     .unwrap();
+    /// let ingest = OpOptions::new().with_priority(Priority::Background);
+    /// match store.try_upsert_with_options(&1, &42, 1, &ingest) {
+    ///     Ok(()) => {}
+    ///     Err(FasterError::Overloaded) => { /* back off and retry */ }
+    ///     Err(err) => panic!("{}", err),
+    /// }
+    /// ```
+    pub fn try_upsert_with_options<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        self.admit_write(options)?;
+        self.try_upsert(key, value, monotonic_serial_number)
+    }
+
+    /// Like [rmw_with_options](#method.rmw_with_options), but returns an error for failed
+    /// Read-Modify-Writes, see [try_upsert_with_options](#method.try_upsert_with_options)
+    pub fn try_rmw_with_options<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterRmw,
+    {
+        self.admit_write(options)?;
+        self.try_rmw(key, value, monotonic_serial_number)
+    }
+
+    /// Like [delete_with_options](#method.delete_with_options), but returns an error for
+    /// failed deletes, see [try_upsert_with_options](#method.try_upsert_with_options)
+    pub fn try_delete_with_options<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> Result<(), FasterError<'static>>
+    where
+        K: FasterKey,
+    {
+        self.admit_write(options)?;
+        self.try_delete(key, monotonic_serial_number)
+    }
+
+    /// Whether `key` has a value, without deserializing it. Deleted keys have none.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &vec![0u8; 1024], 1);
+    /// assert!(store.contains_key(&1u64, 1).unwrap());
+    /// store.delete(&1u64, 1);
+    /// assert!(!store.contains_key(&1u64, 1).unwrap());
+    /// ```
+    pub fn contains_key<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        K: FasterKey,
+    {
+        let mut encoded_key = encode(key);
+        let encoded_key_length = encoded_key.len();
+        let encoded_key_ptr = encoded_key.as_mut_ptr();
+        let (sender, receiver) = read_channel::<bool>();
+        let sender_ptr: *mut Sender<ReadResult<bool>> = Box::into_raw(Box::new(sender));
+        std::mem::forget(encoded_key);
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                encoded_key_ptr,
+                encoded_key_length as u64,
+                monotonic_serial_number,
+                Some(exists_callback),
+                sender_ptr as *mut libc::c_void,
+            )
+        };
+        match self.complete(status) {
+            Ok(()) | Err(FasterError::NotFound) => receiver.recv(),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn complete(&self, status: u8) -> Result<(), FasterError<'static>> {
+        match FasterStatus::from(status) {
+            FasterStatus::Ok => Ok(()),
+            FasterStatus::Pending => {
+                self.complete_pending(true);
+                Ok(())
+            }
+            FasterStatus::NotFound => Err(FasterError::NotFound),
+            status => Err(FasterError::Failed(status)),
+        }
+    }
+}
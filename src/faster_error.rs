@@ -1,3 +1,5 @@
+use crate::builder::LOG_PAGE_SIZE;
+use crate::status::FasterStatus;
 use std::error::Error;
 use std::{fmt, io};
 
@@ -8,6 +10,33 @@ pub enum FasterError<'a> {
     RecoveryError,
     CheckpointError,
     BuilderError(&'a str),
+    /// The log size is below the smallest one FASTER accepts, see
+    /// [FasterKvBuilder::min_log_size](struct.FasterKvBuilder.html#method.min_log_size)
+    LogTooSmall {
+        log_size: u64,
+        minimum: u64,
+    },
+    /// FASTER can't persist to the directory at `path`, see [self_test](fn.self_test.html)
+    UnusableStorage {
+        path: String,
+        reason: String,
+    },
+    /// The key read does not exist
+    NotFound,
+    /// The value read could not be deserialized as the requested type. The stored bytes
+    /// are returned so the record can be inspected and repaired, e.g. with `upsert_raw`.
+    #[cfg(feature = "typed")]
+    Deserialize {
+        bytes: Vec<u8>,
+        source: bincode::Error,
+    },
+    /// The read was dropped without a result
+    Disconnected,
+    /// A background write was rejected by
+    /// [admission control](struct.FasterKvBuilder.html#method.with_admission_control)
+    Overloaded,
+    /// The operation failed with the given status
+    Failed(FasterStatus),
 }
 
 impl<'a> fmt::Display for FasterError<'a> {
@@ -18,6 +47,28 @@ impl<'a> fmt::Display for FasterError<'a> {
             FasterError::RecoveryError => write!(f, "Failed to recover"),
             FasterError::CheckpointError => write!(f, "Checkpoint failed"),
             FasterError::BuilderError(err) => write!(f, "Builder error: {}", err),
+            FasterError::LogTooSmall { log_size, minimum } => write!(
+                f,
+                "Log size of {} bytes is too small, FASTER needs at least {} bytes ({} pages of {} bytes)",
+                log_size,
+                minimum,
+                minimum / LOG_PAGE_SIZE,
+                LOG_PAGE_SIZE
+            ),
+            FasterError::UnusableStorage { path, reason } => {
+                write!(f, "Storage directory {} is unusable: {}", path, reason)
+            }
+            FasterError::NotFound => write!(f, "Key not found"),
+            #[cfg(feature = "typed")]
+            FasterError::Deserialize { bytes, source } => write!(
+                f,
+                "Failed to deserialize value of {} bytes: {}",
+                bytes.len(),
+                source
+            ),
+            FasterError::Disconnected => write!(f, "Read completed without a result"),
+            FasterError::Overloaded => write!(f, "Store is overloaded, retry the write later"),
+            FasterError::Failed(status) => write!(f, "Operation failed with status {:?}", status),
         }
     }
 }
@@ -28,4 +79,13 @@ impl<'a> From<io::Error> for FasterError<'a> {
     }
 }
 
-impl<'a> Error for FasterError<'a> {}
+impl<'a> Error for FasterError<'a> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FasterError::IOError(err) => Some(err),
+            #[cfg(feature = "typed")]
+            FasterError::Deserialize { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -2,16 +2,44 @@ extern crate bincode;
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
-use crate::status;
+use crate::op_options::PendingGuard;
+use crate::read_handle::ReadResult;
+use crate::{status, FasterError};
 
-use bincode::deserialize;
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use crate::codec::{FasterDeserialize, FasterSerialize};
+use std::cell::Cell;
+use std::ptr;
 use std::sync::mpsc::Sender;
+use std::time::Instant;
 
-pub trait FasterKey: DeserializeOwned + Serialize {}
+thread_local! {
+    // Closure of the Read-Modify-Write in progress on the current thread, see ClosureGuard
+    static RMW_CLOSURE: Cell<*const ()> = const { Cell::new(ptr::null()) };
+}
+
+pub trait FasterKey: FasterSerialize + FasterDeserialize {}
 
-pub trait FasterValue: DeserializeOwned + Serialize {}
+pub trait FasterValue: FasterSerialize + FasterDeserialize {}
+
+// Result to hand to the ReadHandle of a completed read. Reads failing with any other status
+// leave the handle disconnected.
+pub unsafe fn read_result<T>(value: *const u8, length: u64, status: u32) -> Option<ReadResult<T>>
+where
+    T: FasterDeserialize,
+{
+    if status == u32::from(status::OK) {
+        let bytes = std::slice::from_raw_parts(value, length as usize);
+        let result = T::faster_deserialize(bytes).map_err(|source| FasterError::Deserialize {
+            bytes: bytes.to_vec(),
+            source,
+        });
+        Some(result)
+    } else if status == u32::from(status::NOT_FOUND) {
+        Some(Err(FasterError::NotFound))
+    } else {
+        None
+    }
+}
 
 #[inline(always)]
 pub unsafe extern "C" fn read_callback<T>(
@@ -20,14 +48,43 @@ pub unsafe extern "C" fn read_callback<T>(
     length: u64,
     status: u32,
 ) where
-    T: DeserializeOwned,
+    T: FasterDeserialize,
 {
-    let boxed_sender = Box::from_raw(sender as *mut Sender<T>);
+    let boxed_sender = Box::from_raw(sender as *mut Sender<ReadResult<T>>);
     let sender = *boxed_sender;
-    if status == status::OK.into() {
-        let val = deserialize(std::slice::from_raw_parts(value, length as usize)).unwrap();
-        // TODO: log error
-        let _ = sender.send(val);
+    if let Some(result) = read_result(value, length, status) {
+        let _ = sender.send(result);
+    }
+}
+
+pub struct ReadContext<T> {
+    pub sender: Sender<ReadResult<T>>,
+    pub deadline: Option<Instant>,
+    // Keeps the read counted as in flight until the callback drops the context
+    pub _pending: PendingGuard,
+}
+
+#[inline(always)]
+pub unsafe extern "C" fn read_deadline_callback<T>(
+    context: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    T: FasterDeserialize,
+{
+    let boxed_context = Box::from_raw(context as *mut ReadContext<T>);
+    let context = *boxed_context;
+    let expired = match context.deadline {
+        Some(deadline) => Instant::now() >= deadline,
+        None => false,
+    };
+    // Nobody is waiting for an expired read, so skip deserializing the value
+    if expired {
+        return;
+    }
+    if let Some(result) = read_result(value, length, status) {
+        let _ = context.sender.send(result);
     }
 }
 
@@ -40,16 +97,16 @@ pub unsafe extern "C" fn rmw_callback<T>(
     dst: *mut u8,
 ) -> u64
 where
-    T: Serialize + DeserializeOwned + FasterRmw,
+    T: FasterRmw,
 {
-    let val: T = deserialize(std::slice::from_raw_parts(current, length_current as usize)).unwrap();
-    let modif = deserialize(std::slice::from_raw_parts_mut(
+    let val = T::faster_deserialize(std::slice::from_raw_parts(current, length_current as usize))
+        .unwrap();
+    let modif = T::faster_deserialize(std::slice::from_raw_parts(
         modification,
         length_modification as usize,
     ))
     .unwrap();
-    let modified = val.rmw(modif);
-    let encoded = bincode::serialize(&modified).unwrap();
+    let encoded = val.rmw(modif).faster_serialize();
     let size = encoded.len();
     if dst != std::ptr::null_mut() {
         encoded.as_ptr().copy_to(dst, size);
@@ -57,14 +114,92 @@ where
     size as u64
 }
 
-pub trait FasterRmw: DeserializeOwned + Serialize {
+// Hands a closure to update_callback and rmw_with_callback on the current thread until
+// dropped. FASTER calls them on the thread issuing the Read-Modify-Write, or completing it if
+// it went pending, so the closure is passed on the side and the modification FASTER stores
+// for a missing key is only the encoded initial value.
+pub struct ClosureGuard {
+    previous: *const (),
+}
+
+impl ClosureGuard {
+    pub fn set<F>(closure: &F) -> ClosureGuard {
+        let closure = closure as *const F as *const ();
+        let previous = RMW_CLOSURE.with(|current| current.replace(closure));
+        ClosureGuard { previous }
+    }
+}
+
+impl Drop for ClosureGuard {
+    fn drop(&mut self) {
+        RMW_CLOSURE.with(|current| current.set(self.previous));
+    }
+}
+
+unsafe fn rmw_closure<'a, F>() -> &'a F {
+    let closure = RMW_CLOSURE.with(Cell::get);
+    debug_assert!(!closure.is_null());
+    &*(closure as *const F)
+}
+
+// Read-Modify-Write applying the closure set with a ClosureGuard to the current value
+#[inline(always)]
+pub unsafe extern "C" fn update_callback<T, F>(
+    current: *const u8,
+    length_current: u64,
+    _modification: *mut u8,
+    _length_modification: u64,
+    dst: *mut u8,
+) -> u64
+where
+    T: FasterSerialize + FasterDeserialize,
+    F: Fn(&mut T),
+{
+    let update = rmw_closure::<F>();
+    let mut val =
+        T::faster_deserialize(std::slice::from_raw_parts(current, length_current as usize))
+            .unwrap();
+    update(&mut val);
+    let encoded = val.faster_serialize();
+    let size = encoded.len();
+    if !dst.is_null() {
+        encoded.as_ptr().copy_to(dst, size);
+    }
+    size as u64
+}
+
+// Read-Modify-Write computing the new value from the current one with the closure set with
+// a ClosureGuard
+#[inline(always)]
+pub unsafe extern "C" fn rmw_with_callback<T, F>(
+    current: *const u8,
+    length_current: u64,
+    _modification: *mut u8,
+    _length_modification: u64,
+    dst: *mut u8,
+) -> u64
+where
+    T: FasterSerialize + FasterDeserialize,
+    F: Fn(&T) -> T,
+{
+    let modify = rmw_closure::<F>();
+    let val = T::faster_deserialize(std::slice::from_raw_parts(current, length_current as usize))
+        .unwrap();
+    let encoded = modify(&val).faster_serialize();
+    let size = encoded.len();
+    if !dst.is_null() {
+        encoded.as_ptr().copy_to(dst, size);
+    }
+    size as u64
+}
+
+pub trait FasterRmw: FasterSerialize + FasterDeserialize {
     /// Specify custom Read-Modify-Write logic
     ///
     /// # Example
     /// ```
-    /// use faster_rs::{status, FasterKv, FasterRmw};
+    /// use faster_rs::{status, FasterKv, FasterRmw, ReadHandle};
     /// use serde_derive::{Deserialize, Serialize};
-    /// use std::sync::mpsc::Receiver;
     ///
     /// #[derive(Serialize, Deserialize)]
     /// struct MyU64 {
@@ -84,7 +219,7 @@ pub trait FasterRmw: DeserializeOwned + Serialize {
     /// let modification = MyU64 { value: 17 };
     /// store.upsert(&key, &value, 1);
     /// store.rmw(&key, &modification, 1);
-    /// let (status, recv): (u8, Receiver<MyU64>) = store.read(&key, 1);
+    /// let (status, recv): (u8, ReadHandle<MyU64>) = store.read(&key, 1);
     /// assert!(status == status::OK);
     /// assert_eq!(recv.recv().unwrap().value, value.value + modification.value);
     fn rmw(&self, modification: Self) -> Self;
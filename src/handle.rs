@@ -0,0 +1,122 @@
+#[cfg(feature = "typed")]
+use crate::{FasterKey, FasterRmw, FasterValue};
+use crate::{FasterKv, ReadHandle, RefreshSchedule, Session};
+
+pub(crate) const DEFAULT_REFRESH_INTERVAL: u64 = 64;
+pub(crate) const DEFAULT_COMPLETE_PENDING_INTERVAL: u64 = 1600;
+
+// How often handles maintain their session, see FasterKvBuilder::with_session_maintenance
+#[derive(Clone, Copy)]
+pub(crate) struct Maintenance {
+    pub(crate) refresh_interval: u64,
+    pub(crate) complete_pending_interval: u64,
+}
+
+/// Session of the current thread that takes care of epoch maintenance and serial numbers,
+/// see [handle](struct.FasterKv.html#method.handle).
+///
+/// Every operation is given the next serial number of the session, and the session is
+/// [refreshed](struct.FasterKv.html#method.refresh) and its pending operations completed
+/// without waiting at the intervals set with
+/// [with_session_maintenance](struct.FasterKvBuilder.html#method.with_session_maintenance).
+/// Like [Session](struct.Session.html), a handle belongs to the thread it was created on
+/// and stops its session when dropped. Threads sharing a store each create their own.
+pub struct FasterKvHandle<'a> {
+    session: Session<'a>,
+    refresh: RefreshSchedule,
+    complete_pending: RefreshSchedule,
+    serial: u64,
+}
+
+impl<'a> FasterKvHandle<'a> {
+    pub(crate) fn new(store: &'a FasterKv) -> FasterKvHandle<'a> {
+        let maintenance = store.maintenance;
+        FasterKvHandle {
+            session: store.session(),
+            refresh: RefreshSchedule::new(maintenance.refresh_interval),
+            complete_pending: RefreshSchedule::new(maintenance.complete_pending_interval),
+            serial: 0,
+        }
+    }
+
+    /// Serial number of the last operation issued through the handle
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    /// Session of the handle, e.g. to checkpoint or to issue operations the handle has no
+    /// method for
+    pub fn session(&self) -> &Session<'a> {
+        &self.session
+    }
+
+    // Counts an operation, maintaining the session when due, and returns its serial number
+    fn next_serial(&mut self) -> u64 {
+        if self.refresh.tick() {
+            self.session.refresh();
+        }
+        if self.complete_pending.tick() {
+            self.session.complete_pending(false);
+        }
+        self.serial += 1;
+        self.serial
+    }
+
+    pub fn upsert_raw(&mut self, key: &[u8], value: &[u8]) -> u8 {
+        let serial = self.next_serial();
+        self.session.upsert_raw(key, value, serial)
+    }
+
+    pub fn read_raw(&mut self, key: &[u8]) -> (u8, ReadHandle<Vec<u8>>) {
+        let serial = self.next_serial();
+        self.session.read_raw(key, serial)
+    }
+
+    pub fn rmw_raw(&mut self, key: &[u8], modification: &[u8]) -> u8 {
+        let serial = self.next_serial();
+        self.session.rmw_raw(key, modification, serial)
+    }
+
+    pub fn delete_raw(&mut self, key: &[u8]) -> u8 {
+        let serial = self.next_serial();
+        self.session.delete_raw(key, serial)
+    }
+}
+
+#[cfg(feature = "typed")]
+impl<'a> FasterKvHandle<'a> {
+    pub fn upsert<K, V>(&mut self, key: &K, value: &V) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let serial = self.next_serial();
+        self.session.upsert(key, value, serial)
+    }
+
+    pub fn read<K, V>(&mut self, key: &K) -> (u8, ReadHandle<V>)
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let serial = self.next_serial();
+        self.session.read(key, serial)
+    }
+
+    pub fn rmw<K, V>(&mut self, key: &K, value: &V) -> u8
+    where
+        K: FasterKey,
+        V: FasterRmw,
+    {
+        let serial = self.next_serial();
+        self.session.rmw(key, value, serial)
+    }
+
+    pub fn delete<K>(&mut self, key: &K) -> u8
+    where
+        K: FasterKey,
+    {
+        let serial = self.next_serial();
+        self.session.delete(key, serial)
+    }
+}
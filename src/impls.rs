@@ -1,13 +1,14 @@
+use crate::codec::{FasterDeserialize, FasterSerialize};
 use crate::{FasterKey, FasterRmw, FasterValue};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::Add;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::hash::Hash;
 
-impl<T> FasterKey for T where T: Serialize + DeserializeOwned {}
-impl<T> FasterValue for T where T: Serialize + DeserializeOwned {}
+impl<T> FasterKey for T where T: FasterSerialize + FasterDeserialize {}
+impl<T> FasterValue for T where T: FasterSerialize + FasterDeserialize {}
 
 macro_rules! primitive_impl {
     ($ty:ident, $method:ident $($cast:tt)*) => {
@@ -87,3 +88,16 @@ impl<T: Clone + Serialize + DeserializeOwned + Hash + Eq> FasterRmw for HashSet<
         union.cloned().collect()
     }
 }
+
+impl<K, V> FasterRmw for BTreeMap<K, V>
+where
+    K: Clone + Serialize + DeserializeOwned + Ord,
+    V: Clone + Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn rmw(&self, new: BTreeMap<K, V>) -> BTreeMap<K, V> {
+        let mut merged = self.clone();
+        merged.extend(new);
+        merged
+    }
+}
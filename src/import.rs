@@ -0,0 +1,215 @@
+use crate::status::FasterStatus;
+use crate::{status, FasterError, FasterKey, FasterKv, FasterValue, RefreshSchedule};
+use serde::de::DeserializeOwned;
+use std::io;
+use std::io::{BufRead, Read};
+use std::sync::mpsc::sync_channel;
+use std::sync::Arc;
+use std::thread;
+
+const REFRESH_INTERVAL: u64 = 64;
+// Pending operations are completed every REFRESH_INTERVAL * COMPLETE_PENDING_REFRESHES records
+const COMPLETE_PENDING_REFRESHES: u64 = 25;
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImportStats {
+    /// Rows that were mapped to a key-value pair and upserted
+    pub imported: u64,
+    /// Rows that could not be parsed or for which the mapping returned `None`
+    pub skipped: u64,
+    /// Rows whose upsert FASTER rejected
+    pub failed: u64,
+    /// Status of the first rejected upsert
+    pub first_failure: Option<FasterStatus>,
+}
+
+impl ImportStats {
+    fn record_failure(&mut self, status: u8) {
+        self.failed += 1;
+        if self.first_failure.is_none() {
+            self.first_failure = Some(FasterStatus::from(status));
+        }
+    }
+}
+
+/// Bulk loader for populating a store from CSV or newline-delimited JSON.
+///
+/// Rows are deserialized into a user-defined type and handed to a mapping function that
+/// produces the key-value pair to upsert. Upserts are spread over `num_threads` worker
+/// threads, each of which runs its own session and takes care of refreshing the epoch
+/// and completing pending operations.
+pub struct Importer {
+    num_threads: usize,
+    chunk_size: usize,
+}
+
+impl Importer {
+    pub fn new(num_threads: usize) -> Importer {
+        Importer {
+            num_threads: std::cmp::max(num_threads, 1),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Number of rows handed to a worker thread at a time
+    pub fn with_chunk_size(&mut self, chunk_size: usize) -> &mut Importer {
+        self.chunk_size = std::cmp::max(chunk_size, 1);
+        self
+    }
+
+    /// Imports newline-delimited JSON, deserializing every line as `R`.
+    ///
+    /// Empty lines are ignored. Lines that fail to deserialize are counted as skipped.
+    pub fn ndjson<R, K, V, I, F>(
+        &self,
+        store: &Arc<FasterKv>,
+        input: I,
+        map: F,
+    ) -> Result<ImportStats, FasterError<'static>>
+    where
+        R: DeserializeOwned,
+        K: FasterKey + Send + 'static,
+        V: FasterValue + Send + 'static,
+        I: BufRead,
+        F: Fn(R) -> Option<(K, V)> + Send + Sync + 'static,
+    {
+        let rows = input
+            .lines()
+            .filter(|line| match line {
+                Ok(line) => !line.trim().is_empty(),
+                Err(_) => true,
+            })
+            .map(|line| line.map(Some));
+        self.run(store, rows, move |line: String| {
+            serde_json::from_str(&line).ok().and_then(&map)
+        })
+    }
+
+    /// Imports CSV, deserializing every record as `R`.
+    ///
+    /// Records that fail to deserialize are counted as skipped.
+    pub fn csv<R, K, V, I, F>(
+        &self,
+        store: &Arc<FasterKv>,
+        input: I,
+        delimiter: u8,
+        has_headers: bool,
+        map: F,
+    ) -> Result<ImportStats, FasterError<'static>>
+    where
+        R: DeserializeOwned + Send + 'static,
+        K: FasterKey + Send + 'static,
+        V: FasterValue + Send + 'static,
+        I: Read,
+        F: Fn(R) -> Option<(K, V)> + Send + Sync + 'static,
+    {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(has_headers)
+            .from_reader(input);
+        let rows = reader.deserialize::<R>().map(|record| match record {
+            Ok(row) => Ok(Some(row)),
+            Err(err) => {
+                if err.is_io_error() {
+                    Err(io::Error::from(err))
+                } else {
+                    Ok(None)
+                }
+            }
+        });
+        self.run(store, rows, map)
+    }
+
+    fn run<T, K, V, I, F>(
+        &self,
+        store: &Arc<FasterKv>,
+        rows: I,
+        convert: F,
+    ) -> Result<ImportStats, FasterError<'static>>
+    where
+        T: Send + 'static,
+        K: FasterKey + Send + 'static,
+        V: FasterValue + Send + 'static,
+        I: Iterator<Item = io::Result<Option<T>>>,
+        F: Fn(T) -> Option<(K, V)> + Send + Sync + 'static,
+    {
+        let convert = Arc::new(convert);
+        let mut senders = Vec::with_capacity(self.num_threads);
+        let mut workers = Vec::with_capacity(self.num_threads);
+
+        for _ in 0..self.num_threads {
+            let (sender, receiver) = sync_channel::<Vec<T>>(2);
+            let store = Arc::clone(store);
+            let convert = Arc::clone(&convert);
+            senders.push(sender);
+            workers.push(thread::spawn(move || {
+                let mut stats = ImportStats::default();
+                let mut serial: u64 = 0;
+                let mut refresh = RefreshSchedule::new(REFRESH_INTERVAL);
+                let _session = store.start_session();
+                for chunk in receiver {
+                    for row in chunk {
+                        match convert(row) {
+                            Some((key, value)) => {
+                                serial += 1;
+                                match store.upsert(&key, &value, serial) {
+                                    status::OK | status::PENDING => stats.imported += 1,
+                                    res => stats.record_failure(res),
+                                }
+                                if refresh.tick() {
+                                    store.refresh();
+                                    if refresh
+                                        .refreshes()
+                                        .is_multiple_of(COMPLETE_PENDING_REFRESHES)
+                                    {
+                                        store.complete_pending(false);
+                                    }
+                                }
+                            }
+                            None => stats.skipped += 1,
+                        }
+                    }
+                }
+                store.complete_pending(true);
+                store.stop_session();
+                stats
+            }));
+        }
+
+        let mut stats = ImportStats::default();
+        let mut result = Ok(());
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        let mut next_worker = 0;
+        for row in rows {
+            match row {
+                Ok(Some(row)) => chunk.push(row),
+                Ok(None) => stats.skipped += 1,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+            if chunk.len() == self.chunk_size {
+                let full = std::mem::replace(&mut chunk, Vec::with_capacity(self.chunk_size));
+                // A failed send means the worker panicked, which is reported when joining
+                let _ = senders[next_worker].send(full);
+                next_worker = (next_worker + 1) % senders.len();
+            }
+        }
+        if !chunk.is_empty() {
+            let _ = senders[next_worker].send(chunk);
+        }
+        drop(senders);
+
+        for worker in workers {
+            let worker_stats = worker.join().expect("Import worker panicked");
+            stats.imported += worker_stats.imported;
+            stats.skipped += worker_stats.skipped;
+            stats.failed += worker_stats.failed;
+            stats.first_failure = stats.first_failure.or(worker_stats.first_failure);
+        }
+        result?;
+        Ok(stats)
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    status, CheckpointToken, FasterError, FasterKey, FasterKv, FasterRmw, FasterValue,
+    RefreshSchedule, Session,
+};
+use serde_derive::{Deserialize, Serialize};
+
+const REFRESH_INTERVAL: u64 = 64;
+
+/// Partition of a replayable message log, such as a Kafka topic partition, in which every
+/// record has an offset that increases by one from record to record
+pub trait Source {
+    type Record;
+
+    /// Makes the next [poll](#tymethod.poll) return the record at `offset`
+    fn seek(&mut self, offset: u64);
+
+    /// Next record along with its offset, or `None` if no record is available right now
+    fn poll(&mut self) -> Option<(u64, Self::Record)>;
+}
+
+// Progress of an ingestion, upserted after the writes of every record. The serial number
+// is the one of the upsert itself, so after a recovery the writes persisted beyond it are
+// known to be the first writes of the record at `next_offset`.
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    guid: String,
+    next_offset: u64,
+    serial: u64,
+}
+
+fn progress_key(name: &str) -> String {
+    format!("faster-rs/exactly-once/{}", name)
+}
+
+/// Ingests the records of a [Source](trait.Source.html) into a store exactly once, even
+/// across crashes.
+///
+/// Every write gets its own serial number in the ingestion's session and the offset of the
+/// next record is upserted after the writes of every record. A checkpoint persists a prefix
+/// of the session, so after recovering it the ingestion resumes at the first record whose
+/// writes were not all persisted, and skips those of its writes that were. This requires
+/// the function applying a record to issue the same writes in the same order every time it
+/// is given the record.
+///
+/// The ingestion runs on a session of the current thread, which is stopped when it is
+/// dropped. Its progress is kept under a `String` key derived from its name, which other
+/// keys must not collide with.
+///
+/// # Example
+/// ```
+/// use faster_rs::{ExactlyOnce, FasterKvBuilder, Source, Storage};
+///
+/// struct Topic {
+///     records: Vec<u64>,
+///     position: usize,
+/// }
+///
+/// impl Source for Topic {
+///     type Record = u64;
+///
+///     fn seek(&mut self, offset: u64) {
+///         self.position = offset as usize;
+///     }
+///
+///     fn poll(&mut self) -> Option<(u64, u64)> {
+///         let record = *self.records.get(self.position)?;
+///         self.position += 1;
+///         Some((self.position as u64 - 1, record))
+///     }
+/// }
+///
+/// let storage = Storage::temporary().unwrap();
+/// let open = || {
+///     FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+///         .with_storage(storage.clone())
+///         .build()
+///         .unwrap()
+/// };
+/// let mut topic = Topic { records: vec![1, 2, 3], position: 0 };
+///
+/// let store = open();
+/// let mut ingestion = ExactlyOnce::start(&store, "sum", 0);
+/// ingestion.process(&mut topic, |writes, record| {
+///     writes.rmw(&"sum".to_owned(), &record);
+/// });
+/// let token = ingestion.checkpoint().unwrap();
+/// drop(ingestion);
+/// drop(store);
+///
+/// let recovered = open();
+/// let mut ingestion = ExactlyOnce::recover(&recovered, "sum", &token).unwrap();
+/// assert_eq!(3, ingestion.next_offset());
+/// topic.records.push(4);
+/// assert_eq!(1, ingestion.process(&mut topic, |writes, record| {
+///     writes.rmw(&"sum".to_owned(), &record);
+/// }));
+/// ```
+pub struct ExactlyOnce<'a> {
+    store: &'a FasterKv,
+    session: Session<'a>,
+    progress_key: String,
+    next_offset: u64,
+    serial: u64,
+    // Serial number of the last write the recovered checkpoint persisted
+    persisted: u64,
+    refresh: RefreshSchedule,
+}
+
+impl<'a> ExactlyOnce<'a> {
+    /// Starts ingesting into `store` under `name`, beginning with the record at `first_offset`
+    pub fn start(store: &'a FasterKv, name: &str, first_offset: u64) -> ExactlyOnce<'a> {
+        let mut ingestion = ExactlyOnce {
+            store,
+            session: store.session(),
+            progress_key: progress_key(name),
+            next_offset: first_offset,
+            serial: 0,
+            persisted: 0,
+            refresh: RefreshSchedule::new(REFRESH_INTERVAL),
+        };
+        ingestion.record_progress();
+        ingestion
+    }
+
+    /// Recovers the checkpoint identified by `token` into `store` and resumes the ingestion
+    /// called `name` where the checkpoint left off.
+    ///
+    /// Like [recover_from](struct.FasterKv.html#method.recover_from), this must be called
+    /// from a thread that does not have a session. Fails with
+    /// [RecoveryError](enum.FasterError.html#variant.RecoveryError) if the checkpoint was
+    /// taken before the ingestion started.
+    pub fn recover(
+        store: &'a FasterKv,
+        name: &str,
+        token: &CheckpointToken,
+    ) -> Result<ExactlyOnce<'a>, FasterError<'a>> {
+        store.recover_from(token)?;
+        let progress_key = progress_key(name);
+        let progress = {
+            let _session = store.session();
+            let (res, recv) = store.read::<String, Progress>(&progress_key, 1);
+            if res == status::PENDING {
+                store.complete_pending(true);
+            }
+            recv.recv_optional()?
+        };
+        let progress = progress.ok_or(FasterError::RecoveryError)?;
+        let (session, persisted) = store.resume_session(progress.guid);
+        Ok(ExactlyOnce {
+            store,
+            session,
+            progress_key,
+            next_offset: progress.next_offset,
+            serial: progress.serial,
+            persisted,
+            refresh: RefreshSchedule::new(REFRESH_INTERVAL),
+        })
+    }
+
+    /// Offset of the next record to ingest
+    pub fn next_offset(&self) -> u64 {
+        self.next_offset
+    }
+
+    /// Ingests the records of `source`, from the next offset until it has no more records
+    /// available, and returns how many were ingested. Records redelivered with an offset
+    /// that was ingested already are skipped.
+    pub fn process<S, F>(&mut self, source: &mut S, mut apply: F) -> u64
+    where
+        S: Source,
+        F: FnMut(&mut Writes, S::Record),
+    {
+        source.seek(self.next_offset);
+        let mut processed = 0;
+        while let Some((offset, record)) = source.poll() {
+            if offset < self.next_offset {
+                continue;
+            }
+            let mut writes = Writes {
+                session: &self.session,
+                serial: &mut self.serial,
+                persisted: self.persisted,
+            };
+            apply(&mut writes, record);
+            self.next_offset = offset + 1;
+            self.record_progress();
+            processed += 1;
+            if self.refresh.tick() {
+                self.session.refresh();
+                self.session.complete_pending(false);
+            }
+        }
+        processed
+    }
+
+    /// Checkpoints the store, including every record ingested so far, and returns the
+    /// token to [recover](#method.recover) from after a crash
+    pub fn checkpoint(&self) -> Result<CheckpointToken, FasterError<'a>> {
+        let token = self.store.checkpoint_token()?;
+        self.session.complete_pending(true);
+        Ok(token)
+    }
+
+    fn record_progress(&mut self) {
+        self.serial += 1;
+        let progress = Progress {
+            guid: self.session.guid().to_owned(),
+            next_offset: self.next_offset,
+            serial: self.serial,
+        };
+        self.session
+            .upsert(&self.progress_key, &progress, self.serial);
+    }
+}
+
+/// Writes of a record being ingested by [ExactlyOnce](struct.ExactlyOnce.html).
+///
+/// Writes that a recovered checkpoint already persisted are skipped and return
+/// [OK](status/constant.OK.html).
+pub struct Writes<'s, 'a> {
+    session: &'s Session<'a>,
+    serial: &'s mut u64,
+    persisted: u64,
+}
+
+impl<'s, 'a> Writes<'s, 'a> {
+    fn next_serial(&mut self) -> Option<u64> {
+        *self.serial += 1;
+        if *self.serial > self.persisted {
+            Some(*self.serial)
+        } else {
+            None
+        }
+    }
+
+    pub fn upsert<K, V>(&mut self, key: &K, value: &V) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        match self.next_serial() {
+            Some(serial) => self.session.upsert(key, value, serial),
+            None => status::OK,
+        }
+    }
+
+    pub fn rmw<K, V>(&mut self, key: &K, value: &V) -> u8
+    where
+        K: FasterKey,
+        V: FasterRmw,
+    {
+        match self.next_serial() {
+            Some(serial) => self.session.rmw(key, value, serial),
+            None => status::OK,
+        }
+    }
+
+    pub fn delete<K>(&mut self, key: &K) -> u8
+    where
+        K: FasterKey,
+    {
+        match self.next_serial() {
+            Some(serial) => self.session.delete(key, serial),
+            None => status::OK,
+        }
+    }
+}
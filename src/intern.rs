@@ -0,0 +1,225 @@
+use crate::read_handle::read_channel;
+use crate::{status, FasterError, FasterKv, FasterRmw, FasterValue, ReadHandle};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+// Namespaces of the keys written by a KeyInterner. Values live under the compact id of
+// their key, next to the dictionary translating between strings and ids.
+#[derive(Serialize, Deserialize)]
+enum InternedKey {
+    NextId,
+    Id(String),
+    Name(u64),
+    Value(u64),
+}
+
+// Id assigned to a string, where the first assignment wins
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct InternedId(u64);
+
+impl FasterRmw for InternedId {
+    fn rmw(&self, _modification: Self) -> Self {
+        *self
+    }
+}
+
+// Lower bound on the next id to hand out, so ids stay unique across recovery
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct NextId(u64);
+
+impl FasterRmw for NextId {
+    fn rmw(&self, modification: Self) -> Self {
+        NextId(std::cmp::max(self.0, modification.0))
+    }
+}
+
+fn read_value<V: FasterValue>(
+    store: &FasterKv,
+    key: &InternedKey,
+    monotonic_serial_number: u64,
+) -> Result<Option<V>, u8> {
+    let (res, recv) = store.read(key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        res => Err(res),
+    }
+}
+
+/// Interning layer storing values under compact ids instead of long, repetitive string keys.
+///
+/// The dictionary mapping strings to ids is kept in the same store as the values, so it is
+/// checkpointed and recovered along with them. Keys written through the interner should not
+/// be mixed with keys written to the store directly.
+///
+/// # Example
+/// ```
+/// use faster_rs::{status, FasterKv, KeyInterner};
+/// let store = FasterKv::default();
+/// let interner = KeyInterner::new();
+///
+/// let key = "tenant-42/https://example.com/a/very/long/path";
+/// interner.upsert(&store, key, &7u64, 1).unwrap();
+///
+/// let (res, recv) = interner.read::<u64>(&store, key, 1).unwrap();
+/// assert_eq!(status::OK, res);
+/// assert_eq!(7, recv.recv().unwrap());
+/// ```
+pub struct KeyInterner {
+    ids: RwLock<HashMap<String, u64>>,
+    next_id: AtomicU64,
+}
+
+impl KeyInterner {
+    pub fn new() -> KeyInterner {
+        KeyInterner {
+            ids: RwLock::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates an interner for a recovered store, continuing after the ids already in use
+    pub fn recover(store: &FasterKv, monotonic_serial_number: u64) -> Result<KeyInterner, u8> {
+        let interner = KeyInterner::new();
+        if let Some(NextId(next)) =
+            read_value(store, &InternedKey::NextId, monotonic_serial_number)?
+        {
+            interner.next_id.store(next, Ordering::SeqCst);
+        }
+        Ok(interner)
+    }
+
+    /// Returns the id of `key`, without assigning one if it has not been interned
+    pub fn lookup(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        monotonic_serial_number: u64,
+    ) -> Result<Option<u64>, u8> {
+        if let Some(id) = self.ids.read().unwrap().get(key) {
+            return Ok(Some(*id));
+        }
+        let id: Option<InternedId> = read_value(
+            store,
+            &InternedKey::Id(key.to_owned()),
+            monotonic_serial_number,
+        )?;
+        if let Some(InternedId(id)) = id {
+            self.ids.write().unwrap().insert(key.to_owned(), id);
+        }
+        Ok(id.map(|InternedId(id)| id))
+    }
+
+    /// Returns the id of `key`, assigning a new one if it has not been interned yet
+    pub fn intern(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        monotonic_serial_number: u64,
+    ) -> Result<u64, u8> {
+        if let Some(id) = self.lookup(store, key, monotonic_serial_number)? {
+            return Ok(id);
+        }
+        let candidate = self.next_id.fetch_add(1, Ordering::SeqCst);
+        store.rmw(
+            &InternedKey::NextId,
+            &NextId(candidate + 1),
+            monotonic_serial_number,
+        );
+        store.upsert(
+            &InternedKey::Name(candidate),
+            &key.to_owned(),
+            monotonic_serial_number,
+        );
+        // Another thread may have interned the key concurrently, in which case its id wins
+        let id_key = InternedKey::Id(key.to_owned());
+        if store.rmw(&id_key, &InternedId(candidate), monotonic_serial_number) == status::PENDING {
+            store.complete_pending(true);
+        }
+        match read_value(store, &id_key, monotonic_serial_number)? {
+            Some(InternedId(id)) => {
+                self.ids.write().unwrap().insert(key.to_owned(), id);
+                Ok(id)
+            }
+            None => Err(status::NOT_FOUND),
+        }
+    }
+
+    /// Translates an id back to the string it was assigned to
+    pub fn resolve(
+        &self,
+        store: &FasterKv,
+        id: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<Option<String>, u8> {
+        read_value(store, &InternedKey::Name(id), monotonic_serial_number)
+    }
+
+    pub fn upsert<V: FasterValue>(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<u8, u8> {
+        let id = self.intern(store, key, monotonic_serial_number)?;
+        Ok(store.upsert(&InternedKey::Value(id), value, monotonic_serial_number))
+    }
+
+    /// Reads the value of `key`. Keys that were never interned are reported as
+    /// [NOT_FOUND](status/constant.NOT_FOUND.html) without touching the value namespace.
+    pub fn read<V: FasterValue>(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        monotonic_serial_number: u64,
+    ) -> Result<(u8, ReadHandle<V>), u8> {
+        match self.lookup(store, key, monotonic_serial_number)? {
+            Some(id) => Ok(store.read(&InternedKey::Value(id), monotonic_serial_number)),
+            None => {
+                let (sender, receiver) = read_channel();
+                let _ = sender.send(Err(FasterError::NotFound));
+                Ok((status::NOT_FOUND, receiver))
+            }
+        }
+    }
+
+    pub fn rmw<V: FasterRmw>(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<u8, u8> {
+        let id = self.intern(store, key, monotonic_serial_number)?;
+        Ok(store.rmw(
+            &InternedKey::Value(id),
+            modification,
+            monotonic_serial_number,
+        ))
+    }
+
+    /// Deletes the value of `key`. The key keeps its id, so writing it again reuses it.
+    pub fn delete(
+        &self,
+        store: &FasterKv,
+        key: &str,
+        monotonic_serial_number: u64,
+    ) -> Result<u8, u8> {
+        match self.lookup(store, key, monotonic_serial_number)? {
+            Some(id) => Ok(store.delete(&InternedKey::Value(id), monotonic_serial_number)),
+            None => Ok(status::NOT_FOUND),
+        }
+    }
+}
+
+impl Default for KeyInterner {
+    fn default() -> Self {
+        KeyInterner::new()
+    }
+}
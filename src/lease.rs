@@ -0,0 +1,107 @@
+use crate::util::unix_millis;
+use crate::{status, FasterKey, FasterKv};
+use serde_derive::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::time::Duration;
+
+// Lease stored under a key. Expiry is wall-clock time so leases outlive a recovery.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Lease {
+    owner: u64,
+    expires_at_millis: u64,
+}
+
+// Lease primitives built on conditional updates with Read-Modify-Write
+impl FasterKv {
+    /// Acquires the lease stored under `key` for `owner` for `ttl`, returning whether it was
+    /// acquired. The lease is acquired if it is free, expired or already held by `owner`,
+    /// in which case it is renewed.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// use std::time::Duration;
+    /// let store = FasterKv::default();
+    /// let leader = String::from("leader");
+    ///
+    /// assert!(store.acquire_lease(&leader, 1, Duration::from_secs(10), 1));
+    /// assert!(!store.acquire_lease(&leader, 2, Duration::from_secs(10), 1));
+    /// assert!(store.release_lease(&leader, 1, 1));
+    /// assert!(store.acquire_lease(&leader, 2, Duration::from_secs(10), 1));
+    /// ```
+    pub fn acquire_lease<K>(
+        &self,
+        key: &K,
+        owner: u64,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> bool
+    where
+        K: FasterKey,
+    {
+        self.update_lease(key, monotonic_serial_number, |lease, now| match lease {
+            Some(lease) if lease.owner != owner && lease.expires_at_millis > now => None,
+            _ => Some(Some(Lease {
+                owner,
+                expires_at_millis: now + ttl.as_millis() as u64,
+            })),
+        })
+    }
+
+    /// Extends the lease stored under `key` to expire `ttl` from now, returning whether
+    /// `owner` still held it
+    pub fn renew_lease<K>(
+        &self,
+        key: &K,
+        owner: u64,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> bool
+    where
+        K: FasterKey,
+    {
+        self.update_lease(key, monotonic_serial_number, |lease, now| match lease {
+            Some(lease) if lease.owner == owner && lease.expires_at_millis > now => {
+                Some(Some(Lease {
+                    owner,
+                    expires_at_millis: now + ttl.as_millis() as u64,
+                }))
+            }
+            _ => None,
+        })
+    }
+
+    /// Releases the lease stored under `key`, returning whether `owner` still held it
+    pub fn release_lease<K>(&self, key: &K, owner: u64, monotonic_serial_number: u64) -> bool
+    where
+        K: FasterKey,
+    {
+        self.update_lease(key, monotonic_serial_number, |lease, now| match lease {
+            Some(lease) if lease.owner == owner && lease.expires_at_millis > now => Some(None),
+            _ => None,
+        })
+    }
+
+    // Replaces the lease with the result of `transition` unless it returns None. Returns
+    // whether the lease was replaced by the invocation of `transition` that took effect.
+    fn update_lease<K, F>(&self, key: &K, monotonic_serial_number: u64, transition: F) -> bool
+    where
+        K: FasterKey,
+        F: Fn(Option<Lease>, u64) -> Option<Option<Lease>>,
+    {
+        let now = unix_millis();
+        let replaced = Cell::new(false);
+        let res = self.update_field(
+            key,
+            |lease: &mut Option<Lease>| match transition(*lease, now) {
+                Some(next) => {
+                    *lease = next;
+                    replaced.set(true);
+                }
+                None => replaced.set(false),
+            },
+            monotonic_serial_number,
+        );
+        (res == status::OK || res == status::PENDING) && replaced.get()
+    }
+}
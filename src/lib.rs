@@ -1,24 +1,146 @@
+#[cfg(feature = "typed")]
 extern crate bincode;
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
+mod arena;
+mod auto_checkpoint;
 mod builder;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "typed")]
+mod coalesce;
+#[cfg(feature = "typed")]
+mod codec;
+#[cfg(feature = "typed")]
+mod collections;
+#[cfg(feature = "typed")]
+mod counter;
+#[cfg(feature = "typed")]
+mod fallible;
 mod faster_error;
+#[cfg(feature = "typed")]
 mod faster_traits;
+mod handle;
+#[cfg(feature = "typed")]
 mod impls;
+#[cfg(feature = "import")]
+mod import;
+#[cfg(feature = "ingest")]
+mod ingest;
+#[cfg(feature = "typed")]
+mod intern;
+#[cfg(feature = "typed")]
+mod lease;
+#[cfg(feature = "typed")]
+mod once;
+#[cfg(feature = "typed")]
+mod op_options;
+mod pod;
+#[cfg(feature = "typed")]
+mod queue;
+#[cfg(feature = "typed")]
+mod rate_limiter;
+mod raw;
+#[cfg(feature = "typed")]
+mod read_future;
+mod read_handle;
+mod recovery;
+mod refresh;
+mod self_test;
+mod session;
+#[cfg(feature = "typed")]
+mod soft_delete;
 pub mod status;
+mod storage;
+#[cfg(feature = "typed")]
+mod timeseries;
+#[cfg(feature = "typed")]
+mod ttl;
+#[cfg(feature = "typed")]
+mod typed;
 mod util;
+#[cfg(feature = "typed")]
+mod view;
+#[cfg(feature = "typed")]
+mod window;
 
-pub use crate::builder::FasterKvBuilder;
+pub use crate::auto_checkpoint::{AutoCheckpoint, CheckpointKind};
+pub use crate::builder::{FasterKvBuilder, LOG_PAGE_SIZE};
+#[cfg(feature = "typed")]
+use crate::coalesce::{coalesced_read_callback, CoalescedRead, InFlightReads};
+#[cfg(feature = "typed")]
+use crate::codec::encode;
+#[cfg(feature = "typed")]
+pub use crate::codec::{decode_error, DecodeError, FasterDeserialize, FasterSerialize};
+#[cfg(feature = "typed")]
+pub use crate::counter::CounterStore;
 pub use crate::faster_error::FasterError;
-use crate::faster_traits::{read_callback, rmw_callback};
+#[cfg(feature = "typed")]
+use crate::faster_traits::{
+    read_callback, read_deadline_callback, rmw_callback, rmw_with_callback, update_callback,
+    ClosureGuard, ReadContext,
+};
+#[cfg(feature = "typed")]
 pub use crate::faster_traits::{FasterKey, FasterRmw, FasterValue};
+pub use crate::handle::FasterKvHandle;
+use crate::handle::Maintenance;
+#[cfg(feature = "import")]
+pub use crate::import::{ImportStats, Importer};
+#[cfg(feature = "ingest")]
+pub use crate::ingest::{ExactlyOnce, Source, Writes};
+#[cfg(feature = "typed")]
+pub use crate::intern::KeyInterner;
+#[cfg(feature = "typed")]
+use crate::op_options::PendingIo;
+#[cfg(feature = "typed")]
+pub use crate::op_options::{OpOptions, Priority};
+pub use crate::pod::FasterPod;
+#[cfg(feature = "typed")]
+pub use crate::queue::FasterQueue;
+#[cfg(feature = "typed")]
+pub use crate::rate_limiter::FasterRateLimiter;
+use crate::raw::{raw_append_callback, raw_read_callback};
+#[cfg(feature = "typed")]
+pub use crate::read_future::ReadFuture;
+pub use crate::read_handle::ReadHandle;
+use crate::read_handle::{read_channel, ReadResult};
+pub use crate::recovery::{CheckpointToken, RecoveredSession, Recovery};
+pub use crate::refresh::RefreshSchedule;
+pub use crate::self_test::self_test;
+use crate::session::SessionTracker;
+pub use crate::session::{ActiveSession, Session};
+pub use crate::status::FasterStatus;
+pub use crate::storage::{DiskUsage, Storage};
+#[cfg(feature = "typed")]
+pub use crate::timeseries::TimeSeries;
+#[cfg(feature = "typed")]
+pub use crate::typed::TypedFasterKv;
 use crate::util::*;
+#[cfg(feature = "typed")]
+pub use crate::view::View;
+#[cfg(feature = "typed")]
+use crate::view::Views;
+#[cfg(feature = "typed")]
+pub use crate::window::WindowState;
 
+#[cfg(feature = "typed")]
+use std::any::TypeId;
+#[cfg(feature = "typed")]
+use std::cell::Cell;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::fs;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+#[cfg(feature = "typed")]
+use std::thread;
+#[cfg(feature = "typed")]
+use std::time::Duration;
+
+// Records upserted by upsert_batch between refreshes of the session
+#[cfg(feature = "typed")]
+const BATCH_REFRESH_INTERVAL: usize = 1024;
 
 #[no_mangle]
 pub unsafe extern "C" fn deallocate_vec(vec: *mut u8, length: u64) {
@@ -27,19 +149,40 @@ pub unsafe extern "C" fn deallocate_vec(vec: *mut u8, length: u64) {
 
 pub struct FasterKv {
     faster_t: *mut ffi::faster_t,
-    storage_dir: Option<String>,
+    storage: Option<Storage>,
+    sessions: Arc<SessionTracker>,
+    maintenance: Maintenance,
+    #[cfg(feature = "typed")]
+    in_flight_reads: Arc<InFlightReads>,
+    #[cfg(feature = "typed")]
+    pending_io: Arc<PendingIo>,
+    #[cfg(feature = "typed")]
+    views: Views,
 }
 
+#[cfg(feature = "typed")]
 impl FasterKv {
     pub fn upsert<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
     where
         K: FasterKey,
         V: FasterValue,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
+        let encoded_key = encode(key);
+        let encoded_value = encode(value);
+        // FASTER takes ownership of the encoded record, so views are maintained first
+        self.views_upserted(&encoded_key, &encoded_value, monotonic_serial_number);
+        self.upsert_encoded(encoded_key, encoded_value, monotonic_serial_number)
+    }
+
+    // Upserts an encoded record without maintaining views
+    pub(crate) fn upsert_encoded(
+        &self,
+        mut encoded_key: Vec<u8>,
+        mut encoded_value: Vec<u8>,
+        monotonic_serial_number: u64,
+    ) -> u8 {
         let encoded_key_length = encoded_key.len();
         let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let mut encoded_value = bincode::serialize(value).unwrap();
         let encoded_value_length = encoded_value.len();
         let encoded_value_ptr = encoded_value.as_mut_ptr();
         std::mem::forget(encoded_key);
@@ -56,16 +199,60 @@ impl FasterKv {
         }
     }
 
-    pub fn read<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, Receiver<V>)
+    /// Upserts every record of `records`, the `i`th one with serial number
+    /// `monotonic_serial_number + i`, and returns their statuses in the same order.
+    ///
+    /// All records are serialized before the first one is handed to FASTER, and the session
+    /// is refreshed every `BATCH_REFRESH_INTERVAL` records so long batches don't hold back
+    /// the epoch. faster-c has no batched upsert, so every record is still a separate call.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    /// let _session = store.start_session();
+    ///
+    /// let statuses = store.upsert_batch(&[(1u64, 10u64), (2, 20), (3, 30)], 1);
+    /// assert!(statuses.iter().all(|s| *s == status::OK || *s == status::PENDING));
+    ///
+    /// let (res, recv) = store.read::<u64, u64>(&2, 4);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(20, recv.recv().unwrap());
+    /// store.stop_session();
+    /// ```
+    pub fn upsert_batch<K, V>(&self, records: &[(K, V)], monotonic_serial_number: u64) -> Vec<u8>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let encoded: Vec<(Vec<u8>, Vec<u8>)> = records
+            .iter()
+            .map(|(key, value)| (encode(key), encode(value)))
+            .collect();
+        encoded
+            .into_iter()
+            .enumerate()
+            .map(|(i, (encoded_key, encoded_value))| {
+                if i > 0 && i % BATCH_REFRESH_INTERVAL == 0 {
+                    self.refresh();
+                }
+                let serial = monotonic_serial_number + i as u64;
+                self.views_upserted(&encoded_key, &encoded_value, serial);
+                self.upsert_encoded(encoded_key, encoded_value, serial)
+            })
+            .collect()
+    }
+
+    pub fn read<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, ReadHandle<V>)
     where
         K: FasterKey,
         V: FasterValue,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
+        let mut encoded_key = encode(key);
         let encoded_key_length = encoded_key.len();
         let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let (sender, receiver) = channel();
-        let sender_ptr: *mut Sender<V> = Box::into_raw(Box::new(sender));
+        let (sender, receiver) = read_channel();
+        let sender_ptr: *mut Sender<ReadResult<V>> = Box::into_raw(Box::new(sender));
         std::mem::forget(encoded_key);
         let status = unsafe {
             ffi::faster_read(
@@ -80,15 +267,246 @@ impl FasterKv {
         (status, receiver)
     }
 
+    /// Reads every key of `keys` and returns their values in the same order, `None` for
+    /// keys that don't exist.
+    ///
+    /// All reads are issued before any of them is waited for, so reads going to disk are
+    /// in flight together and completed with a single
+    /// [complete_pending](#method.complete_pending). Must be called from a thread with an
+    /// active session.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// let _session = store.start_session();
+    ///
+    /// store.upsert(&1u64, &10u64, 1);
+    /// store.upsert(&3u64, &30u64, 2);
+    /// let values: Vec<Option<u64>> = store
+    ///     .read_batch(&[1u64, 2, 3], 3)
+    ///     .into_iter()
+    ///     .map(Result::unwrap)
+    ///     .collect();
+    /// assert_eq!(vec![Some(10), None, Some(30)], values);
+    /// store.stop_session();
+    /// ```
+    pub fn read_batch<K, V>(
+        &self,
+        keys: &[K],
+        monotonic_serial_number: u64,
+    ) -> Vec<Result<Option<V>, FasterError<'static>>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let mut any_pending = false;
+        let handles: Vec<ReadHandle<V>> = keys
+            .iter()
+            .map(|key| {
+                let (res, handle) = self.read(key, monotonic_serial_number);
+                any_pending |= res == status::PENDING;
+                handle
+            })
+            .collect();
+        if any_pending {
+            self.complete_pending(true);
+        }
+        handles.iter().map(ReadHandle::recv_optional).collect()
+    }
+
+    // Holds back background operations until they are admitted, see
+    // OpOptions::with_priority. Returns false if the deadline passes first.
+    fn admit(&self, options: &OpOptions) -> bool {
+        if options.is_expired() {
+            return false;
+        }
+        if options.priority() == Priority::Background {
+            while !self.pending_io.admits_background() {
+                self.complete_pending(false);
+                self.refresh();
+                if options.is_expired() {
+                    return false;
+                }
+                thread::yield_now();
+            }
+        }
+        true
+    }
+
+    // Like admit, but also rejects background writes while the store is overloaded
+    pub(crate) fn admit_write(&self, options: &OpOptions) -> Result<(), FasterError<'static>> {
+        if options.priority() == Priority::Background && self.pending_io.is_overloaded() {
+            return Err(FasterError::Overloaded);
+        }
+        if !self.admit(options) {
+            return Err(FasterError::Failed(FasterStatus::Aborted));
+        }
+        Ok(())
+    }
+
+    /// Whether background writes are currently rejected by
+    /// [admission control](struct.FasterKvBuilder.html#method.with_admission_control)
+    pub fn is_overloaded(&self) -> bool {
+        self.pending_io.is_overloaded()
+    }
+
+    /// Moving average of how long foreground reads issued with options have recently taken,
+    /// from issuing them to their result
+    pub fn read_latency(&self) -> Duration {
+        self.pending_io.read_latency()
+    }
+
+    /// Like [upsert](#method.upsert), but honours the given [OpOptions](struct.OpOptions.html).
+    /// The deadline is only checked before the upsert is issued.
+    pub fn upsert_with_options<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        if self.admit_write(options).is_err() {
+            return status::ABORTED;
+        }
+        self.upsert(key, value, monotonic_serial_number)
+    }
+
+    /// Like [read](#method.read), but honours the given [OpOptions](struct.OpOptions.html).
+    ///
+    /// The deadline is checked before the read is issued. A read that goes pending before
+    /// its deadline still completes its IO; once it expires only the deserialization of
+    /// the value is skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv, OpOptions};
+    /// use std::time::Instant;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1, &42, 1);
+    ///
+    /// // The deadline has already passed, so the read is never issued
+    /// let options = OpOptions::new().with_deadline(Instant::now());
+    /// let (res, recv) = store.read_with_options::<i32, i32>(&1, 1, &options);
+    /// assert_eq!(status::ABORTED, res);
+    /// assert!(recv.recv().is_err());
+    /// ```
+    pub fn read_with_options<K, V>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> (u8, ReadHandle<V>)
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let (sender, receiver) = read_channel();
+        if !self.admit(options) {
+            return (status::ABORTED, receiver);
+        }
+        let mut encoded_key = encode(key);
+        let encoded_key_length = encoded_key.len();
+        let encoded_key_ptr = encoded_key.as_mut_ptr();
+        let context = ReadContext {
+            sender,
+            deadline: options.deadline(),
+            _pending: PendingIo::track(&self.pending_io, options.priority()),
+        };
+        let context_ptr: *mut ReadContext<V> = Box::into_raw(Box::new(context));
+        std::mem::forget(encoded_key);
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                encoded_key_ptr,
+                encoded_key_length as u64,
+                monotonic_serial_number,
+                Some(read_deadline_callback::<V>),
+                context_ptr as *mut libc::c_void,
+            )
+        };
+        (status, receiver)
+    }
+
+    /// Like [read](#method.read), but shares a single FASTER read between concurrent
+    /// callers reading the same key as the same value type.
+    ///
+    /// If a coalesced read for the key is already in flight, no new read is issued and
+    /// [PENDING](status/constant.PENDING.html) is returned. The value arrives on the
+    /// `ReadHandle` once the thread that issued the read completes it, so this avoids
+    /// repeated disk IO when many threads read the same cold key at once.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1, &42, 1);
+    ///
+    /// let (res, recv) = store.read_coalesced::<i32, i32>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(42, recv.recv().unwrap());
+    /// ```
+    pub fn read_coalesced<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, ReadHandle<V>)
+    where
+        K: FasterKey,
+        V: FasterValue + Send + 'static,
+    {
+        let mut encoded_key = encode(key);
+        let (sender, receiver) = read_channel();
+        let in_flight_key = (TypeId::of::<V>(), encoded_key.clone());
+        {
+            let mut in_flight = self.in_flight_reads.lock().unwrap();
+            if let Some(waiters) = in_flight.get_mut(&in_flight_key) {
+                waiters.push(Box::new(sender));
+                return (status::PENDING, receiver);
+            }
+            in_flight.insert(in_flight_key.clone(), vec![Box::new(sender)]);
+        }
+        let encoded_key_length = encoded_key.len();
+        let encoded_key_ptr = encoded_key.as_mut_ptr();
+        let context = CoalescedRead {
+            in_flight: Arc::clone(&self.in_flight_reads),
+            key: in_flight_key,
+        };
+        let context_ptr: *mut CoalescedRead = Box::into_raw(Box::new(context));
+        std::mem::forget(encoded_key);
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                encoded_key_ptr,
+                encoded_key_length as u64,
+                monotonic_serial_number,
+                Some(coalesced_read_callback::<V>),
+                context_ptr as *mut libc::c_void,
+            )
+        };
+        match status {
+            status::OK | status::PENDING | status::NOT_FOUND => {}
+            // The callback is not called for failed reads, which would otherwise stay in
+            // flight and leave the reads coalesced into them waiting forever
+            status => {
+                let context = *unsafe { Box::from_raw(context_ptr) };
+                context.fail::<V>(status);
+            }
+        }
+        (status, receiver)
+    }
+
     pub fn rmw<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
     where
         K: FasterKey,
         V: FasterRmw,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
+        let mut encoded_key = encode(key);
         let encoded_key_length = encoded_key.len();
         let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let mut encoded_value = bincode::serialize(value).unwrap();
+        let mut encoded_value = encode(value);
         let encoded_value_length = encoded_value.len();
         let encoded_value_ptr = encoded_value.as_mut_ptr();
         std::mem::forget(encoded_key);
@@ -106,6 +524,216 @@ impl FasterKv {
         }
     }
 
+    /// Updates the value of `key` by applying `update` to it with Read-Modify-Write, without
+    /// having to implement [FasterRmw](trait.FasterRmw.html) for the value type.
+    ///
+    /// A missing key is initialised by applying `update` to `V::default()`. `update` may be
+    /// called more than once for a single operation, so it should only modify the value it
+    /// is given. It is only borrowed for the duration of the call, so a pending update is
+    /// completed before returning.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// use serde_derive::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize, Default)]
+    /// struct Profile {
+    ///     name: String,
+    ///     visits: u64,
+    /// }
+    ///
+    /// let store = FasterKv::default();
+    /// store.update_field(&1, |profile: &mut Profile| profile.visits += 1, 1);
+    /// store.update_field(&1, |profile: &mut Profile| profile.visits += 1, 1);
+    ///
+    /// let (res, recv) = store.read::<i32, Profile>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(2, recv.recv().unwrap().visits);
+    /// ```
+    pub fn update_field<K, V, F>(&self, key: &K, update: F, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue + Default,
+        F: Fn(&mut V),
+    {
+        let mut initial = V::default();
+        update(&mut initial);
+        self.rmw_closure(
+            key,
+            &initial,
+            &update,
+            Some(update_callback::<V, F>),
+            monotonic_serial_number,
+        )
+    }
+
+    /// Read-Modify-Write computing the new value of `key` from its current one with
+    /// `modify`, so the merge logic can differ between call sites. A missing key is
+    /// initialised to `initial`.
+    ///
+    /// `modify` may be called more than once for a single operation. It is only borrowed for
+    /// the duration of the call, so a pending Read-Modify-Write is completed before
+    /// returning.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.rmw_with(&1, &10u64, |current: &u64| current * 2, 1);
+    /// store.rmw_with(&1, &10u64, |current: &u64| current * 2, 1);
+    /// store.rmw_with(&1, &0u64, |current: &u64| current.saturating_sub(5), 1);
+    ///
+    /// let (res, recv) = store.read::<i32, u64>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(15, recv.recv().unwrap());
+    /// ```
+    pub fn rmw_with<K, V, F>(
+        &self,
+        key: &K,
+        initial: &V,
+        modify: F,
+        monotonic_serial_number: u64,
+    ) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+        F: Fn(&V) -> V,
+    {
+        self.rmw_closure(
+            key,
+            initial,
+            &modify,
+            Some(rmw_with_callback::<V, F>),
+            monotonic_serial_number,
+        )
+    }
+
+    /// Replaces the value of `key` with `new` only if it currently equals `expected`,
+    /// returning whether it was replaced. A compare-and-swap for optimistic concurrency,
+    /// without the race of a read followed by an upsert.
+    ///
+    /// A missing key counts as holding `V::default()`, so it is set to `new` if `expected`
+    /// is the default and left missing otherwise. Store `Option<V>` to tell missing keys
+    /// apart. The value is read first, so a mismatch returns without writing, and only a
+    /// match is swapped with [update_field](#method.update_field). Pending operations are
+    /// completed before returning.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1, &String::from("draft"), 1);
+    /// assert!(store.upsert_if(&1, &String::from("draft"), &String::from("published"), 2));
+    /// assert!(!store.upsert_if(&1, &String::from("draft"), &String::from("archived"), 3));
+    ///
+    /// let (_, recv) = store.read::<i32, String>(&1, 4);
+    /// assert_eq!("published", recv.recv().unwrap());
+    /// ```
+    pub fn upsert_if<K, V>(
+        &self,
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> bool
+    where
+        K: FasterKey,
+        V: FasterValue + Default + PartialEq + Clone,
+    {
+        let (res, recv) = self.read::<K, V>(key, monotonic_serial_number);
+        if res == status::PENDING {
+            self.complete_pending(true);
+        }
+        let matches = match recv.recv() {
+            Ok(current) => current == *expected,
+            Err(FasterError::NotFound) => *expected == V::default(),
+            Err(_) => false,
+        };
+        if !matches {
+            return false;
+        }
+        // The value may have changed since the read, so the update compares again. It is set
+        // by every invocation, so it ends up reflecting the one that took effect: the one
+        // applied to the current value, or the initial one for a missing key.
+        let swapped = Cell::new(false);
+        let res = self.update_field(
+            key,
+            |current: &mut V| {
+                if current == expected {
+                    *current = new.clone();
+                    swapped.set(true);
+                } else {
+                    swapped.set(false);
+                }
+            },
+            monotonic_serial_number,
+        );
+        (res == status::OK || res == status::PENDING) && swapped.get()
+    }
+
+    // Read-Modify-Write inserting `initial` for a missing key and otherwise running
+    // `callback`, which finds `closure` through a ClosureGuard
+    fn rmw_closure<K, V, F>(
+        &self,
+        key: &K,
+        initial: &V,
+        closure: &F,
+        callback: ffi::rmw_callback,
+        monotonic_serial_number: u64,
+    ) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let mut encoded_key = encode(key);
+        let encoded_key_length = encoded_key.len();
+        let encoded_key_ptr = encoded_key.as_mut_ptr();
+        let mut encoded_initial = encode(initial);
+        let encoded_initial_length = encoded_initial.len();
+        let encoded_initial_ptr = encoded_initial.as_mut_ptr();
+        std::mem::forget(encoded_key);
+        std::mem::forget(encoded_initial);
+        // Kept until the operation is completed, as pending ones run the callback later
+        let _closure = ClosureGuard::set(closure);
+        let status = unsafe {
+            ffi::faster_rmw(
+                self.faster_t,
+                encoded_key_ptr,
+                encoded_key_length as u64,
+                encoded_initial_ptr,
+                encoded_initial_length as u64,
+                monotonic_serial_number,
+                callback,
+            )
+        };
+        if status == status::PENDING {
+            self.complete_pending(true);
+        }
+        status
+    }
+
+    /// Like [rmw](#method.rmw), but honours the given [OpOptions](struct.OpOptions.html).
+    /// The deadline is only checked before the Read-Modify-Write is issued.
+    pub fn rmw_with_options<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> u8
+    where
+        K: FasterKey,
+        V: FasterRmw,
+    {
+        if self.admit_write(options).is_err() {
+            return status::ABORTED;
+        }
+        self.rmw(key, value, monotonic_serial_number)
+    }
+
     /// Deletes a previously inserted key.
     ///
     /// Returns [NOT_FOUND](status/constant.NOT_FOUND.html) for un-inserted keys.
@@ -122,7 +750,7 @@ impl FasterKv {
     /// store.upsert(&key, &value, 1);
     ///
     /// // Read key-value
-    /// let (res, recv) = store.read(&key, 1);
+    /// let (res, recv) = store.read::<i32, i32>(&key, 1);
     /// assert_eq!(status::OK, res);
     /// assert_eq!(value, recv.recv().unwrap());
     ///
@@ -138,7 +766,17 @@ impl FasterKv {
     where
         K: FasterKey,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
+        let encoded_key = encode(key);
+        self.views_deleted(&encoded_key, monotonic_serial_number);
+        self.delete_encoded(encoded_key, monotonic_serial_number)
+    }
+
+    // Deletes an encoded key without maintaining views
+    pub(crate) fn delete_encoded(
+        &self,
+        mut encoded_key: Vec<u8>,
+        monotonic_serial_number: u64,
+    ) -> u8 {
         let encoded_key_length = encoded_key.len();
         let encoded_key_ptr = encoded_key.as_mut_ptr();
         std::mem::forget(encoded_key);
@@ -152,12 +790,159 @@ impl FasterKv {
         }
     }
 
+    /// Like [delete](#method.delete), but honours the given [OpOptions](struct.OpOptions.html).
+    /// The deadline is only checked before the delete is issued.
+    pub fn delete_with_options<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        options: &OpOptions,
+    ) -> u8
+    where
+        K: FasterKey,
+    {
+        if self.admit_write(options).is_err() {
+            return status::ABORTED;
+        }
+        self.delete(key, monotonic_serial_number)
+    }
+}
+
+impl FasterKv {
+    /// Creates a store on FASTER's null disk device, which never creates any files. Records
+    /// evicted from the `log_size` bytes of log buffer are lost, so the buffer has to hold the
+    /// whole data set. In-memory stores can't be checkpointed.
+    pub fn new_in_memory(table_size: u64, log_size: u64) -> Result<FasterKv, FasterError<'static>> {
+        FasterKvBuilder::new(table_size, log_size).build()
+    }
+
+    /// Creates a store persisting to a newly created, uniquely named temporary directory.
+    /// The directory is removed when the store is dropped.
+    pub fn new_tempfile_backed(
+        table_size: u64,
+        log_size: u64,
+    ) -> Result<FasterKv, FasterError<'static>> {
+        FasterKvBuilder::new(table_size, log_size)
+            .with_storage(Storage::temporary()?)
+            .build()
+    }
+
+    /// Upserts `value` under `key` as raw bytes, without serialization.
+    ///
+    /// Raw values are stored as-is and can only be accessed through the raw API, as the
+    /// typed API expects values in their [FasterSerialize](trait.FasterSerialize.html) encoding.
+    pub fn upsert_raw(&self, key: &[u8], value: &[u8], monotonic_serial_number: u64) -> u8 {
+        let mut key = key.to_vec();
+        let key_length = key.len();
+        let key_ptr = key.as_mut_ptr();
+        let mut value = value.to_vec();
+        let value_length = value.len();
+        let value_ptr = value.as_mut_ptr();
+        std::mem::forget(key);
+        std::mem::forget(value);
+        unsafe {
+            ffi::faster_upsert(
+                self.faster_t,
+                key_ptr,
+                key_length as u64,
+                value_ptr,
+                value_length as u64,
+                monotonic_serial_number,
+            )
+        }
+    }
+
+    /// Reads the bytes stored under `key` without deserializing them, e.g. to inspect a
+    /// record the typed API fails to read. Keys written through the typed API have to be
+    /// passed in their [FasterSerialize](trait.FasterSerialize.html) encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterError, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &42u64, 1);
+    /// let (_, handle) = store.read::<u64, String>(&1, 2);
+    /// match handle.recv() {
+    ///     Err(FasterError::Deserialize { .. }) => {}
+    ///     _ => panic!("42u64 is not a String"),
+    /// }
+    ///
+    /// let (res, handle) = store.read_raw(&1u64.to_le_bytes(), 3);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(42u64.to_le_bytes().to_vec(), handle.recv().unwrap());
+    /// ```
+    pub fn read_raw(&self, key: &[u8], monotonic_serial_number: u64) -> (u8, ReadHandle<Vec<u8>>) {
+        let mut key = key.to_vec();
+        let key_length = key.len();
+        let key_ptr = key.as_mut_ptr();
+        let (sender, receiver) = read_channel();
+        let sender_ptr: *mut Sender<ReadResult<Vec<u8>>> = Box::into_raw(Box::new(sender));
+        std::mem::forget(key);
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                key_ptr,
+                key_length as u64,
+                monotonic_serial_number,
+                Some(raw_read_callback),
+                sender_ptr as *mut libc::c_void,
+            )
+        };
+        (status, receiver)
+    }
+
+    /// Appends `modification` to the raw bytes stored under `key`, inserting it if the key
+    /// does not exist.
+    pub fn rmw_raw(&self, key: &[u8], modification: &[u8], monotonic_serial_number: u64) -> u8 {
+        let mut key = key.to_vec();
+        let key_length = key.len();
+        let key_ptr = key.as_mut_ptr();
+        let mut modification = modification.to_vec();
+        let modification_length = modification.len();
+        let modification_ptr = modification.as_mut_ptr();
+        std::mem::forget(key);
+        std::mem::forget(modification);
+        unsafe {
+            ffi::faster_rmw(
+                self.faster_t,
+                key_ptr,
+                key_length as u64,
+                modification_ptr,
+                modification_length as u64,
+                monotonic_serial_number,
+                Some(raw_append_callback),
+            )
+        }
+    }
+
+    /// Deletes a key inserted through the raw API.
+    pub fn delete_raw(&self, key: &[u8], monotonic_serial_number: u64) -> u8 {
+        let mut key = key.to_vec();
+        let key_length = key.len();
+        let key_ptr = key.as_mut_ptr();
+        std::mem::forget(key);
+        unsafe {
+            ffi::faster_delete(
+                self.faster_t,
+                key_ptr,
+                key_length as u64,
+                monotonic_serial_number,
+            )
+        }
+    }
+
     pub fn size(&self) -> u64 {
         unsafe { ffi::faster_size(self.faster_t) }
     }
 
+    /// Starts a full checkpoint of the hash index and the hybrid log, both identified by
+    /// the returned token. `checked` is `false` if FASTER could not start the checkpoint,
+    /// e.g. because another one is still in progress.
+    ///
+    /// The checkpoint completes as the store's sessions [refresh](#method.refresh).
     pub fn checkpoint(&self) -> Result<CheckPoint, FasterError> {
-        if self.storage_dir.is_none() {
+        if self.storage.is_none() {
             return Err(FasterError::InvalidType);
         }
 
@@ -178,8 +963,13 @@ impl FasterKv {
         }
     }
 
+    /// Starts a checkpoint of the hash index only.
+    ///
+    /// Index checkpoints are expensive for large tables but only need to be taken rarely:
+    /// a store can be recovered from an index checkpoint together with any later hybrid
+    /// log checkpoint, by passing both tokens to [recover](#method.recover).
     pub fn checkpoint_index(&self) -> Result<CheckPoint, FasterError> {
-        if self.storage_dir.is_none() {
+        if self.storage.is_none() {
             return Err(FasterError::InvalidType);
         }
 
@@ -200,8 +990,39 @@ impl FasterKv {
         }
     }
 
+    /// Starts a checkpoint of the hybrid log only, which is cheap enough to be taken
+    /// frequently. Recovering it requires the token of an earlier index checkpoint, see
+    /// [checkpoint_index](#method.checkpoint_index).
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{CheckpointToken, FasterKvBuilder, Storage};
+    /// let storage = Storage::temporary().unwrap();
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_storage(storage.clone())
+    ///     .build()
+    ///     .unwrap();
+    /// store.start_session();
+    /// store.upsert(&1u64, &1u64, 1);
+    /// let index = store.checkpoint_index().unwrap();
+    /// store.complete_pending(true);
+    /// store.upsert(&1u64, &2u64, 2);
+    /// let hybrid_log = store.checkpoint_hybrid_log().unwrap();
+    /// store.complete_pending(true);
+    /// store.stop_session();
+    ///
+    /// let recovered = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_storage(storage)
+    ///     .build()
+    ///     .unwrap();
+    /// let token = CheckpointToken {
+    ///     index: index.token,
+    ///     hybrid_log: hybrid_log.token,
+    /// };
+    /// assert!(recovered.recover_from(&token).is_ok());
+    /// ```
     pub fn checkpoint_hybrid_log(&self) -> Result<CheckPoint, FasterError> {
-        if self.storage_dir.is_none() {
+        if self.storage.is_none() {
             return Err(FasterError::InvalidType);
         }
 
@@ -227,7 +1048,7 @@ impl FasterKv {
         index_token: String,
         hybrid_log_token: String,
     ) -> Result<Recover, FasterError> {
-        if self.storage_dir.is_none() {
+        if self.storage.is_none() {
             return Err(FasterError::InvalidType);
         }
         let index_token_c = CString::new(index_token).unwrap();
@@ -272,15 +1093,32 @@ impl FasterKv {
         unsafe { ffi::faster_complete_pending(self.faster_t, b) }
     }
 
+    /// Starts a session on the current thread and returns its GUID, under which its
+    /// progress is recorded in checkpoints. Keep it to resume the session after recovering
+    /// with [continue_session](#method.continue_session).
     pub fn start_session(&self) -> String {
-        unsafe {
+        debug_assert!(
+            !self.sessions.on_current_thread(),
+            "A session is already active on this thread"
+        );
+        let guid = unsafe {
             let c_guid = ffi::faster_start_session(self.faster_t);
-            let rust_str = CStr::from_ptr(c_guid).to_str().unwrap().to_owned();
-            rust_str
-        }
+            CStr::from_ptr(c_guid).to_str().unwrap().to_owned()
+        };
+        self.sessions.started(&guid);
+        guid
     }
 
+    /// Continues the session with GUID `token` on the current thread after a recovery,
+    /// returning the serial number of its last operation that the recovered checkpoint
+    /// persisted. Operations after it were lost and have to be reissued for exactly-once
+    /// processing.
     pub fn continue_session(&self, token: String) -> u64 {
+        debug_assert!(
+            !self.sessions.on_current_thread(),
+            "A session is already active on this thread"
+        );
+        self.sessions.started(&token);
         let token_str = CString::new(token).unwrap();
         let token_ptr = token_str.into_raw();
         unsafe {
@@ -290,14 +1128,105 @@ impl FasterKv {
         }
     }
 
+    /// Starts a session on the current thread, which is stopped when the returned
+    /// [Session](struct.Session.html) is dropped
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// {
+    ///     let session = store.session();
+    ///     session.upsert(&1u64, &42u64, 1);
+    ///     session.refresh();
+    ///     assert_eq!(1, store.active_sessions().len());
+    /// }
+    /// assert!(store.active_sessions().is_empty());
+    /// ```
+    pub fn session(&self) -> Session<'_> {
+        Session::start(self)
+    }
+
+    /// Like [continue_session](#method.continue_session), but returns the continued
+    /// session as a [Session](struct.Session.html) that is stopped when dropped, along with
+    /// the serial number of its last persisted operation.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKvBuilder, Storage};
+    /// let storage = Storage::temporary().unwrap();
+    /// let open = || {
+    ///     FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///         .with_storage(storage.clone())
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    ///
+    /// let store = open();
+    /// let session = store.session();
+    /// for serial in 1..=10 {
+    ///     session.upsert_raw(&[serial as u8], b"done", serial);
+    /// }
+    /// let guid = session.guid().to_owned();
+    /// let token = session.checkpoint_token().unwrap();
+    /// drop(session);
+    /// drop(store);
+    ///
+    /// let recovered = open();
+    /// recovered.recover_from(&token).unwrap();
+    /// let (session, serial_num) = recovered.resume_session(guid);
+    /// assert_eq!(10, serial_num);
+    /// // Carry on exactly where the checkpoint left off
+    /// session.upsert_raw(&[11], b"done", serial_num + 1);
+    /// ```
+    pub fn resume_session(&self, guid: String) -> (Session<'_>, u64) {
+        Session::resume(self, guid)
+    }
+
+    /// Starts a session on the current thread that numbers its operations and refreshes
+    /// itself, so applications don't have to. The returned
+    /// [FasterKvHandle](struct.FasterKvHandle.html) stops the session when dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    /// let mut handle = store.handle();
+    /// for i in 0..1000u64 {
+    ///     handle.upsert_raw(&i.to_le_bytes(), b"value");
+    /// }
+    /// let (res, recv) = handle.read_raw(&42u64.to_le_bytes());
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(b"value".to_vec(), recv.recv().unwrap());
+    /// assert_eq!(1001, handle.serial());
+    /// ```
+    pub fn handle(&self) -> FasterKvHandle<'_> {
+        FasterKvHandle::new(self)
+    }
+
     pub fn stop_session(&self) -> () {
+        debug_assert!(
+            self.sessions.on_current_thread(),
+            "No session is active on this thread, was it started on another one?"
+        );
         unsafe { ffi::faster_stop_session(self.faster_t) }
+        self.sessions.stopped();
+    }
+
+    /// Sessions that were started or continued and have not been stopped yet, oldest first
+    pub fn active_sessions(&self) -> Vec<ActiveSession> {
+        self.sessions.active()
     }
 
     pub fn refresh(&self) -> () {
+        debug_assert!(
+            self.sessions.on_current_thread(),
+            "No session is active on this thread, was it started on another one?"
+        );
         unsafe {
             ffi::faster_refresh_session(self.faster_t);
         }
+        self.sessions.refreshed();
     }
 
     pub fn dump_distribution(&self) -> () {
@@ -306,16 +1235,65 @@ impl FasterKv {
         }
     }
 
+    /// Starts doubling the number of buckets of the hash index, so stores created with a
+    /// small `table_size` don't degrade into long hash chains as they fill up.
+    ///
+    /// Returns `false` if the index could not start growing, e.g. because it is already
+    /// growing or a checkpoint is in progress. Growing happens online: it starts while the
+    /// calling thread holds a session and progresses as the store's sessions call
+    /// [refresh](#method.refresh) or [complete_pending](#method.complete_pending), with
+    /// operations proceeding in the meantime. faster-c does not report when growing has
+    /// finished.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    /// let _session = store.start_session();
+    ///
+    /// store.upsert(&1u64, &42u64, 1);
+    /// assert!(store.grow_index());
+    /// store.complete_pending(true);
+    ///
+    /// let (res, recv) = store.read::<u64, u64>(&1, 2);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(42, recv.recv().unwrap());
+    /// store.stop_session();
+    /// ```
     pub fn grow_index(&self) -> bool {
         unsafe { ffi::faster_grow_index(self.faster_t) }
     }
 
-    // Warning: Calling this will remove the stored data
-    pub fn clean_storage(&self) -> Result<(), FasterError> {
-        match &self.storage_dir {
+    /// Directory the store persists to, if it is not in-memory only
+    pub fn storage(&self) -> Option<&Storage> {
+        self.storage.as_ref()
+    }
+
+    /// Bytes used on disk by the log segments and checkpoints of the store
+    pub fn disk_usage(&self) -> Result<DiskUsage, FasterError<'_>> {
+        match &self.storage {
             None => Err(FasterError::InvalidType),
-            Some(dir) => {
-                fs::remove_dir_all(dir)?;
+            Some(storage) => Ok(storage.disk_usage()?),
+        }
+    }
+
+    /// Removes checkpoints that were never completed, returning the number of bytes freed.
+    /// Log segments are only truncated by FASTER itself, so they are left alone. Must not be
+    /// called while a checkpoint is being taken.
+    pub fn gc_orphaned_files(&self) -> Result<u64, FasterError<'_>> {
+        match &self.storage {
+            None => Err(FasterError::InvalidType),
+            Some(storage) => Ok(storage.remove_orphaned_checkpoints()?),
+        }
+    }
+
+    // Warning: Calling this will remove the stored data. In-memory stores have no files, so
+    // there is nothing to remove for them.
+    pub fn clean_storage(&self) -> Result<(), FasterError> {
+        match &self.storage {
+            None => Ok(()),
+            Some(storage) => {
+                fs::remove_dir_all(storage.path())?;
                 Ok(())
             }
         }
@@ -330,9 +1308,7 @@ impl FasterKv {
 
 impl Default for FasterKv {
     fn default() -> Self {
-        FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
-            .build()
-            .unwrap()
+        FasterKv::new_in_memory(1 << 15, 1024 * 1024 * 1024).unwrap()
     }
 }
 
@@ -0,0 +1,96 @@
+use crate::codec::encode;
+use crate::util::unix_millis;
+use crate::{status, FasterError, FasterKey, FasterKv, FasterValue};
+use serde_derive::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::time::Duration;
+
+// Result recorded under an idempotency key. Expiry is wall-clock time so results outlive a
+// recovery.
+#[derive(Serialize, Deserialize, Clone)]
+struct Recorded {
+    expires_at_millis: u64,
+    result: Vec<u8>,
+}
+
+fn decode<V: FasterValue>(result: Vec<u8>) -> Result<V, FasterError<'static>> {
+    V::faster_deserialize(&result).map_err(|source| FasterError::Deserialize {
+        bytes: result,
+        source,
+    })
+}
+
+impl FasterKv {
+    /// Runs `compute` once per idempotency `key` and returns its result, recording it under
+    /// `key` for `ttl`. Later calls with the same key return the recorded result instead of
+    /// running `compute` again, until it expires.
+    ///
+    /// Callers racing on a key that has no result yet may each run `compute`, but only the
+    /// first result to be recorded is kept and returned to all of them. An expired result
+    /// is replaced by the next call with its key; until then it keeps its space in the log.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// use std::time::Duration;
+    /// let store = FasterKv::default();
+    /// let request = String::from("payment-42");
+    /// let ttl = Duration::from_secs(3600);
+    ///
+    /// let charged = store.once(&request, ttl, || 100u64, 1).unwrap();
+    /// let retried = store.once(&request, ttl, || 200u64, 1).unwrap();
+    /// assert_eq!(100, charged);
+    /// assert_eq!(100, retried);
+    /// ```
+    pub fn once<K, V, F>(
+        &self,
+        key: &K,
+        ttl: Duration,
+        compute: F,
+        monotonic_serial_number: u64,
+    ) -> Result<V, FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+        F: FnOnce() -> V,
+    {
+        // Read the same type update_field stores below
+        let (res, recv) = self.read::<K, Option<Recorded>>(key, monotonic_serial_number);
+        if res == status::PENDING {
+            self.complete_pending(true);
+        }
+        match recv.recv() {
+            Ok(Some(recorded)) if recorded.expires_at_millis > unix_millis() => {
+                return decode(recorded.result)
+            }
+            Ok(_) | Err(FasterError::NotFound) => {}
+            Err(err) => return Err(err),
+        }
+
+        let result = encode(&compute());
+        let now = unix_millis();
+        let expires_at_millis = now + ttl.as_millis() as u64;
+        // Result kept under the key by the invocation of the update that took effect
+        let kept = RefCell::new(None);
+        self.update_field(
+            key,
+            |recorded: &mut Option<Recorded>| {
+                match recorded {
+                    Some(existing) if existing.expires_at_millis > now => {}
+                    _ => {
+                        *recorded = Some(Recorded {
+                            expires_at_millis,
+                            result: result.clone(),
+                        })
+                    }
+                }
+                *kept.borrow_mut() = recorded.as_ref().map(|recorded| recorded.result.clone());
+            },
+            monotonic_serial_number,
+        );
+        match kept.into_inner() {
+            Some(result) => decode(result),
+            None => Err(FasterError::Disconnected),
+        }
+    }
+}
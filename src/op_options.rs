@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Scheduling class of an operation, see [with_priority](struct.OpOptions.html#method.with_priority)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// Latency sensitive traffic, issued right away
+    #[default]
+    Foreground,
+    /// Bulk work such as loads and scans, queued behind foreground reads waiting for IO
+    Background,
+}
+
+/// Per-operation options that can be passed to the `*_with_options` variants of the
+/// store operations.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpOptions {
+    deadline: Option<Instant>,
+    priority: Priority,
+}
+
+impl OpOptions {
+    pub fn new() -> OpOptions {
+        OpOptions::default()
+    }
+
+    /// Abandon the operation if it has not completed by `deadline`.
+    ///
+    /// Operations whose deadline has already passed are not issued to FASTER at all and
+    /// return [ABORTED](status/constant.ABORTED.html). Pending reads that complete after
+    /// their deadline are dropped without deserializing the value, so the `ReadHandle`
+    /// is disconnected instead of receiving a result.
+    ///
+    /// The deadline does not cancel anything: it is only checked before an operation is
+    /// issued, and FASTER runs an issued operation to completion, including its IO, even
+    /// once the deadline has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> OpOptions {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Shorthand for `with_deadline(Instant::now() + timeout)`.
+    pub fn with_timeout(self, timeout: Duration) -> OpOptions {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Schedules the operation as `priority`, foreground by default.
+    ///
+    /// Background operations are held back while any foreground read issued with options
+    /// is waiting for IO, and while the store already has as many background reads
+    /// waiting for IO as the builder's
+    /// [background pending limit](struct.FasterKvBuilder.html#method.set_background_pending_limit).
+    /// While held back the calling thread keeps refreshing its session and completing its
+    /// own pending operations, so it never stalls the store. Background operations whose
+    /// deadline passes while they wait return [ABORTED](status/constant.ABORTED.html).
+    ///
+    /// Only operations issued through the `*_with_options` variants are accounted for, so
+    /// serving paths should use them to be prioritised over background jobs.
+    ///
+    /// With [admission control](struct.FasterKvBuilder.html#method.with_admission_control),
+    /// background writes are rejected outright while the store is overloaded.
+    pub fn with_priority(mut self, priority: Priority) -> OpOptions {
+        self.priority = priority;
+        self
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub(crate) fn is_expired(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+}
+
+/// Reads of either priority that are currently in flight, and how long foreground reads
+/// have recently taken
+pub struct PendingIo {
+    foreground: AtomicUsize,
+    background: AtomicUsize,
+    background_limit: usize,
+    // Foreground reads in flight and read latency above which the store is overloaded
+    overload_limits: Option<(usize, Duration)>,
+    // Moving average of foreground read latencies
+    read_latency_nanos: AtomicU64,
+}
+
+impl PendingIo {
+    pub fn new(background_limit: usize, overload_limits: Option<(usize, Duration)>) -> PendingIo {
+        PendingIo {
+            foreground: AtomicUsize::new(0),
+            background: AtomicUsize::new(0),
+            background_limit,
+            overload_limits,
+            read_latency_nanos: AtomicU64::new(0),
+        }
+    }
+
+    fn counter(&self, priority: Priority) -> &AtomicUsize {
+        match priority {
+            Priority::Foreground => &self.foreground,
+            Priority::Background => &self.background,
+        }
+    }
+
+    pub fn admits_background(&self) -> bool {
+        self.foreground.load(Ordering::Acquire) == 0
+            && self.background.load(Ordering::Acquire) < self.background_limit
+    }
+
+    pub fn read_latency(&self) -> Duration {
+        Duration::from_nanos(self.read_latency_nanos.load(Ordering::Acquire))
+    }
+
+    /// Whether background writes should be rejected to protect foreground reads
+    pub fn is_overloaded(&self) -> bool {
+        match self.overload_limits {
+            Some((max_pending, max_read_latency)) => {
+                self.foreground.load(Ordering::Acquire) > max_pending
+                    || self.read_latency() > max_read_latency
+            }
+            None => false,
+        }
+    }
+
+    /// Counts a read as in flight until the returned guard is dropped by its callback
+    pub fn track(pending_io: &Arc<PendingIo>, priority: Priority) -> PendingGuard {
+        pending_io.counter(priority).fetch_add(1, Ordering::AcqRel);
+        PendingGuard {
+            pending_io: Arc::clone(pending_io),
+            priority,
+            issued: Instant::now(),
+        }
+    }
+
+    fn record_read_latency(&self, latency: Duration) {
+        let sample = latency.as_nanos() as u64;
+        // Weighs each read by 1/8, so a few slow reads don't count as overload
+        let _ =
+            self.read_latency_nanos
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |average| {
+                    Some(average - average / 8 + sample / 8)
+                });
+    }
+}
+
+pub struct PendingGuard {
+    pending_io: Arc<PendingIo>,
+    priority: Priority,
+    issued: Instant,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending_io
+            .counter(self.priority)
+            .fetch_sub(1, Ordering::AcqRel);
+        if self.priority == Priority::Foreground {
+            self.pending_io.record_read_latency(self.issued.elapsed());
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::read_handle::{read_channel, ReadResult};
+#[cfg(feature = "typed")]
+use crate::FasterRmw;
+use crate::{status, FasterError, FasterKv, ReadHandle};
+
+use std::sync::mpsc::Sender;
+
+/// Fixed-size types that are copied into FASTER as their little-endian bytes, without
+/// going through serde.
+///
+/// The bytes are the same bincode produces, so records written with the `_pod` operations
+/// can be read with the typed ones and vice versa.
+pub trait FasterPod: Copy {
+    /// Number of bytes of the encoding
+    const SIZE: usize;
+
+    /// Writes the encoding to `dst`, which is `SIZE` bytes long
+    fn write_bytes(&self, dst: &mut [u8]);
+
+    /// Reads a value from `src`, which is `SIZE` bytes long
+    fn read_bytes(src: &[u8]) -> Self;
+}
+
+macro_rules! pod_impl {
+    ($($ty:ident)*) => {
+        $(
+            impl FasterPod for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                #[inline]
+                fn write_bytes(&self, dst: &mut [u8]) {
+                    dst.copy_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline]
+                fn read_bytes(src: &[u8]) -> Self {
+                    let mut bytes = [0; std::mem::size_of::<$ty>()];
+                    bytes.copy_from_slice(src);
+                    $ty::from_le_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+pod_impl!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64);
+
+impl<T: FasterPod, const N: usize> FasterPod for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    #[inline]
+    fn write_bytes(&self, dst: &mut [u8]) {
+        for (i, element) in self.iter().enumerate() {
+            element.write_bytes(&mut dst[i * T::SIZE..(i + 1) * T::SIZE]);
+        }
+    }
+
+    #[inline]
+    fn read_bytes(src: &[u8]) -> Self {
+        let mut offset = 0;
+        [(); N].map(|_| {
+            offset += T::SIZE;
+            T::read_bytes(&src[offset - T::SIZE..offset])
+        })
+    }
+}
+
+// FASTER takes ownership of the buffers it is given, so every operation still needs an
+// allocation of exactly the encoded size
+#[inline]
+fn encode<T: FasterPod>(value: &T) -> (*mut u8, u64) {
+    let mut bytes = vec![0; T::SIZE];
+    value.write_bytes(&mut bytes);
+    let ptr = bytes.as_mut_ptr();
+    std::mem::forget(bytes);
+    (ptr, T::SIZE as u64)
+}
+
+#[inline(always)]
+unsafe extern "C" fn pod_read_callback<T: FasterPod>(
+    sender: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) {
+    let boxed_sender = Box::from_raw(sender as *mut Sender<ReadResult<T>>);
+    let sender = *boxed_sender;
+    if status == u32::from(status::OK) {
+        let result = match length as usize == T::SIZE {
+            true => Ok(T::read_bytes(std::slice::from_raw_parts(
+                value,
+                length as usize,
+            ))),
+            // The record was not written as a T
+            false => Err(FasterError::InvalidType),
+        };
+        let _ = sender.send(result);
+    } else if status == u32::from(status::NOT_FOUND) {
+        let _ = sender.send(Err(FasterError::NotFound));
+    }
+}
+
+#[cfg(feature = "typed")]
+#[inline(always)]
+unsafe extern "C" fn pod_rmw_callback<T: FasterPod + FasterRmw>(
+    current: *const u8,
+    length_current: u64,
+    modification: *mut u8,
+    length_modification: u64,
+    dst: *mut u8,
+) -> u64 {
+    // FASTER first asks for the size of the new value, which is always the same
+    if !dst.is_null() {
+        let modification = std::slice::from_raw_parts(modification, length_modification as usize);
+        let dst = std::slice::from_raw_parts_mut(dst, T::SIZE);
+        // The modification is always encoded by rmw_pod, the current value may be anything
+        if length_current as usize == T::SIZE {
+            let current = T::read_bytes(std::slice::from_raw_parts(current, T::SIZE));
+            current.rmw(T::read_bytes(modification)).write_bytes(dst);
+        } else {
+            // The record was not written as a T, so the modification replaces it like the
+            // value of a missing key
+            dst.copy_from_slice(modification);
+        }
+    }
+    T::SIZE as u64
+}
+
+impl FasterKv {
+    /// Like [upsert](#method.upsert), but copies the key and value into FASTER without
+    /// serializing them
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert_pod(&1u64, &[1u32, 2, 3], 1);
+    /// let (res, recv) = store.read_pod::<u64, [u32; 3]>(&1, 1);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!([1, 2, 3], recv.recv().unwrap());
+    /// ```
+    pub fn upsert_pod<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterPod,
+        V: FasterPod,
+    {
+        let (key_ptr, key_length) = encode(key);
+        let (value_ptr, value_length) = encode(value);
+        unsafe {
+            ffi::faster_upsert(
+                self.faster_t,
+                key_ptr,
+                key_length,
+                value_ptr,
+                value_length,
+                monotonic_serial_number,
+            )
+        }
+    }
+
+    /// Like [read](#method.read), but for keys and values stored as their bytes. Values of
+    /// a different size are received as
+    /// [InvalidType](enum.FasterError.html#variant.InvalidType).
+    pub fn read_pod<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, ReadHandle<V>)
+    where
+        K: FasterPod,
+        V: FasterPod,
+    {
+        let (key_ptr, key_length) = encode(key);
+        let (sender, receiver) = read_channel();
+        let sender_ptr: *mut Sender<ReadResult<V>> = Box::into_raw(Box::new(sender));
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                key_ptr,
+                key_length,
+                monotonic_serial_number,
+                Some(pod_read_callback::<V>),
+                sender_ptr as *mut libc::c_void,
+            )
+        };
+        (status, receiver)
+    }
+
+    /// Like [rmw](#method.rmw), but for keys and values stored as their bytes. A record
+    /// that was not written as a `V` is replaced with `value`.
+    #[cfg(feature = "typed")]
+    pub fn rmw_pod<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterPod,
+        V: FasterPod + FasterRmw,
+    {
+        let (key_ptr, key_length) = encode(key);
+        let (value_ptr, value_length) = encode(value);
+        unsafe {
+            ffi::faster_rmw(
+                self.faster_t,
+                key_ptr,
+                key_length,
+                value_ptr,
+                value_length,
+                monotonic_serial_number,
+                Some(pod_rmw_callback::<V>),
+            )
+        }
+    }
+
+    /// Like [delete](#method.delete), but for keys stored as their bytes
+    pub fn delete_pod<K>(&self, key: &K, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterPod,
+    {
+        let (key_ptr, key_length) = encode(key);
+        unsafe { ffi::faster_delete(self.faster_t, key_ptr, key_length, monotonic_serial_number) }
+    }
+}
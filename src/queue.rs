@@ -0,0 +1,164 @@
+use crate::{status, FasterKv, FasterRmw, FasterValue};
+use serde_derive::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Serialize, Deserialize)]
+enum QueueKey {
+    // Highest position allocated to an entry
+    Tail(String),
+    // Lowest position that has not been trimmed
+    Head(String),
+    Entry(String, u64),
+    Cursor(String, String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Position(u64);
+
+impl FasterRmw for Position {
+    fn rmw(&self, modification: Self) -> Self {
+        Position(std::cmp::max(self.0, modification.0))
+    }
+}
+
+fn read_value<V: FasterValue>(
+    store: &FasterKv,
+    key: &QueueKey,
+    monotonic_serial_number: u64,
+) -> Result<Option<V>, u8> {
+    let (res, recv) = store.read(key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        res => Err(res),
+    }
+}
+
+/// Persistent multi-producer queue stored as records next to the rest of a store.
+///
+/// Entries are stored under consecutive positions starting at 1, and each consumer keeps
+/// its own cursor record holding the next position to read, so any number of consumers can
+/// read the queue independently. Positions are allocated in memory and the highest one is
+/// recorded in the store, so a queue should be opened once per store and shared between
+/// its producers. A cursor should only be used by one consumer at a time.
+///
+/// The queue is as durable as the store: entries and cursors written before a checkpoint
+/// are available after recovering it and opening the queue again.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, FasterQueue};
+/// let store = FasterKv::default();
+/// let queue = FasterQueue::open(&store, "jobs", 1).unwrap();
+///
+/// queue.enqueue(&store, &String::from("resize"), 1);
+/// queue.enqueue(&store, &String::from("upload"), 1);
+/// assert_eq!(
+///     vec![(1, String::from("resize"))],
+///     queue.dequeue_from(&store, "worker", 1, 1).unwrap()
+/// );
+/// assert_eq!(
+///     vec![(2, String::from("upload"))],
+///     queue.dequeue_from(&store, "worker", 10, 1).unwrap()
+/// );
+/// assert!(queue.dequeue_from(&store, "worker", 10, 1).unwrap().is_empty());
+/// ```
+pub struct FasterQueue<T> {
+    name: String,
+    tail: AtomicU64,
+    entries: PhantomData<T>,
+}
+
+impl<T> FasterQueue<T>
+where
+    T: FasterValue,
+{
+    /// Opens the queue called `name`, continuing after its last entry if it already exists
+    pub fn open(
+        store: &FasterKv,
+        name: &str,
+        monotonic_serial_number: u64,
+    ) -> Result<FasterQueue<T>, u8> {
+        let tail_key = QueueKey::Tail(name.to_owned());
+        let tail = read_value(store, &tail_key, monotonic_serial_number)?;
+        Ok(FasterQueue {
+            name: name.to_owned(),
+            tail: AtomicU64::new(tail.map_or(0, |Position(tail)| tail)),
+            entries: PhantomData,
+        })
+    }
+
+    /// Appends `value` to the queue, returning its position
+    pub fn enqueue(&self, store: &FasterKv, value: &T, monotonic_serial_number: u64) -> u64 {
+        let position = self.tail.fetch_add(1, Ordering::SeqCst) + 1;
+        let entry_key = QueueKey::Entry(self.name.clone(), position);
+        store.upsert(&entry_key, value, monotonic_serial_number);
+        let tail_key = QueueKey::Tail(self.name.clone());
+        store.rmw(&tail_key, &Position(position), monotonic_serial_number);
+        position
+    }
+
+    /// Reads up to `max` entries after the position of `cursor` and advances the cursor
+    /// past them. A new cursor starts at the oldest entry that has not been trimmed.
+    ///
+    /// Reading stops early at an entry that is still being enqueued.
+    pub fn dequeue_from(
+        &self,
+        store: &FasterKv,
+        cursor: &str,
+        max: usize,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<(u64, T)>, u8> {
+        let cursor_key = QueueKey::Cursor(self.name.clone(), cursor.to_owned());
+        let head = self.head(store, monotonic_serial_number)?;
+        let start = match read_value(store, &cursor_key, monotonic_serial_number)? {
+            Some(Position(next)) => std::cmp::max(next, head),
+            None => head,
+        };
+        let tail = self.tail.load(Ordering::SeqCst);
+        let mut entries = Vec::new();
+        let mut next = start;
+        while next <= tail && entries.len() < max {
+            let entry_key = QueueKey::Entry(self.name.clone(), next);
+            match read_value(store, &entry_key, monotonic_serial_number)? {
+                Some(value) => entries.push((next, value)),
+                None => break,
+            }
+            next += 1;
+        }
+        if next != start {
+            store.upsert(&cursor_key, &Position(next), monotonic_serial_number);
+        }
+        Ok(entries)
+    }
+
+    /// Deletes the entries before position `before`. Cursors behind it skip the deleted
+    /// entries.
+    pub fn trim(
+        &self,
+        store: &FasterKv,
+        before: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<(), u8> {
+        let head = self.head(store, monotonic_serial_number)?;
+        for position in head..before {
+            let entry_key = QueueKey::Entry(self.name.clone(), position);
+            store.delete(&entry_key, monotonic_serial_number);
+        }
+        if before > head {
+            let head_key = QueueKey::Head(self.name.clone());
+            store.rmw(&head_key, &Position(before), monotonic_serial_number);
+        }
+        Ok(())
+    }
+
+    fn head(&self, store: &FasterKv, monotonic_serial_number: u64) -> Result<u64, u8> {
+        let head_key = QueueKey::Head(self.name.clone());
+        let head = read_value(store, &head_key, monotonic_serial_number)?;
+        Ok(head.map_or(1, |Position(head)| head))
+    }
+}
@@ -0,0 +1,90 @@
+use crate::util::unix_millis;
+use crate::{status, FasterKey, FasterKv};
+use serde_derive::{Deserialize, Serialize};
+use std::cell::Cell;
+
+// Token bucket of a key. It has a fixed encoded size, so FASTER updates it in place while
+// it is in the mutable region of the log.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at_millis: u64,
+}
+
+/// Token bucket rate limiter keeping one bucket per key.
+///
+/// Buckets hold up to `capacity` tokens and are refilled at `refill_per_sec` tokens per
+/// second. Taking tokens is a single Read-Modify-Write that refills the bucket based on the
+/// time since it was last updated, so no background refilling is needed. Buckets are
+/// refilled by wall-clock time, and keys that were never limited start with a full bucket.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, FasterRateLimiter};
+/// let store = FasterKv::default();
+/// let limiter = FasterRateLimiter::new(2, 0.5);
+/// let client = String::from("10.0.0.1");
+///
+/// assert!(limiter.try_acquire(&store, &client, 1, 1));
+/// assert!(limiter.try_acquire(&store, &client, 1, 1));
+/// assert!(!limiter.try_acquire(&store, &client, 1, 1));
+/// ```
+pub struct FasterRateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+}
+
+impl FasterRateLimiter {
+    pub fn new(capacity: u64, refill_per_sec: f64) -> FasterRateLimiter {
+        assert!(capacity > 0, "Capacity must be positive");
+        assert!(refill_per_sec >= 0.0, "Refill rate can't be negative");
+        FasterRateLimiter {
+            capacity: capacity as f64,
+            refill_per_sec,
+        }
+    }
+
+    /// Takes `tokens` from the bucket of `key`, returning whether there were enough. The
+    /// bucket is left unchanged, apart from refilling it, if there weren't.
+    pub fn try_acquire<K: FasterKey>(
+        &self,
+        store: &FasterKv,
+        key: &K,
+        tokens: u64,
+        monotonic_serial_number: u64,
+    ) -> bool {
+        let now = unix_millis();
+        let acquired = Cell::new(false);
+        let res = store.update_field(
+            key,
+            |bucket: &mut Option<TokenBucket>| {
+                let mut refilled = self.refill(*bucket, now);
+                let enough = refilled.tokens >= tokens as f64;
+                if enough {
+                    refilled.tokens -= tokens as f64;
+                }
+                *bucket = Some(refilled);
+                acquired.set(enough);
+            },
+            monotonic_serial_number,
+        );
+        (res == status::OK || res == status::PENDING) && acquired.get()
+    }
+
+    fn refill(&self, bucket: Option<TokenBucket>, now: u64) -> TokenBucket {
+        match bucket {
+            Some(bucket) => {
+                let elapsed = now.saturating_sub(bucket.refilled_at_millis) as f64 / 1000.0;
+                TokenBucket {
+                    tokens: (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity),
+                    // Clocks going backwards don't move the refill time back
+                    refilled_at_millis: now.max(bucket.refilled_at_millis),
+                }
+            }
+            None => TokenBucket {
+                tokens: self.capacity,
+                refilled_at_millis: now,
+            },
+        }
+    }
+}
@@ -0,0 +1,43 @@
+extern crate libc;
+
+use crate::read_handle::ReadResult;
+use crate::{status, FasterError};
+
+use std::sync::mpsc::Sender;
+
+#[inline(always)]
+pub unsafe extern "C" fn raw_read_callback(
+    sender: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) {
+    let boxed_sender = Box::from_raw(sender as *mut Sender<ReadResult<Vec<u8>>>);
+    let sender = *boxed_sender;
+    if status == u32::from(status::OK) {
+        let value = std::slice::from_raw_parts(value, length as usize).to_vec();
+        let _ = sender.send(Ok(value));
+    } else if status == u32::from(status::NOT_FOUND) {
+        let _ = sender.send(Err(FasterError::NotFound));
+    }
+}
+
+// Read-Modify-Write on raw values appends the modification to the current value
+#[inline(always)]
+pub unsafe extern "C" fn raw_append_callback(
+    current: *const u8,
+    length_current: u64,
+    modification: *mut u8,
+    length_modification: u64,
+    dst: *mut u8,
+) -> u64 {
+    let size = length_current + length_modification;
+    if !dst.is_null() {
+        current.copy_to(dst, length_current as usize);
+        modification.copy_to(
+            dst.add(length_current as usize),
+            length_modification as usize,
+        );
+    }
+    size
+}
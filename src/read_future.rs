@@ -0,0 +1,131 @@
+use crate::codec::{encode, FasterDeserialize};
+use crate::faster_traits::read_result;
+use crate::status::FasterStatus;
+use crate::{status, FasterError, FasterKey, FasterKv, FasterValue};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+type AsyncReadResult<T> = Result<Option<T>, FasterError<'static>>;
+
+struct ReadState<T> {
+    result: Option<AsyncReadResult<T>>,
+    waker: Option<Waker>,
+}
+
+/// Future resolving to the result of a read, see [read_async](struct.FasterKv.html#method.read_async)
+pub struct ReadFuture<T> {
+    state: Arc<Mutex<ReadState<T>>>,
+}
+
+impl<T> Future for ReadFuture<T> {
+    type Output = AsyncReadResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[inline(always)]
+unsafe extern "C" fn read_future_callback<T>(
+    state: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    T: FasterDeserialize,
+{
+    let state = Box::from_raw(state as *mut Arc<Mutex<ReadState<T>>>);
+    let result = match read_result(value, length, status) {
+        Some(Ok(value)) => Ok(Some(value)),
+        Some(Err(FasterError::NotFound)) => Ok(None),
+        Some(Err(err)) => Err(err),
+        None => Err(FasterError::Failed(FasterStatus::from(status as u8))),
+    };
+    let mut state = state.lock().unwrap();
+    state.result = Some(result);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+impl FasterKv {
+    /// Like [read](#method.read), but returns a `Future` resolving to the value, or `None`
+    /// if the key does not exist, instead of a status and a `ReadHandle`.
+    ///
+    /// Values in memory are available as soon as `read_async` returns. Reads that go pending
+    /// resolve once the thread that issued them calls
+    /// [complete_pending](#method.complete_pending), as FASTER only completes pending
+    /// operations on the session that issued them; the future itself can be awaited on any
+    /// thread, e.g. from a tokio task. Reads FASTER rejects outright resolve right away to
+    /// [Failed](enum.FasterError.html#variant.Failed) with the status it returned.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::sync::Arc;
+    /// use std::task::{Context, Poll, Wake, Waker};
+    ///
+    /// struct NoopWaker;
+    /// impl Wake for NoopWaker {
+    ///     fn wake(self: Arc<Self>) {}
+    /// }
+    ///
+    /// let store = FasterKv::default();
+    /// store.upsert(&1u64, &42u64, 1);
+    ///
+    /// let waker = Waker::from(Arc::new(NoopWaker));
+    /// let mut read = store.read_async::<u64, u64>(&1, 1);
+    /// store.complete_pending(true);
+    /// match Pin::new(&mut read).poll(&mut Context::from_waker(&waker)) {
+    ///     Poll::Ready(value) => assert_eq!(Some(42), value.unwrap()),
+    ///     Poll::Pending => panic!("The read was completed"),
+    /// }
+    /// ```
+    pub fn read_async<K, V>(&self, key: &K, monotonic_serial_number: u64) -> ReadFuture<V>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let mut encoded_key = encode(key);
+        let encoded_key_length = encoded_key.len();
+        let encoded_key_ptr = encoded_key.as_mut_ptr();
+        let state = Arc::new(Mutex::new(ReadState {
+            result: None,
+            waker: None,
+        }));
+        let state_ptr: *mut Arc<Mutex<ReadState<V>>> = Box::into_raw(Box::new(Arc::clone(&state)));
+        std::mem::forget(encoded_key);
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                encoded_key_ptr,
+                encoded_key_length as u64,
+                monotonic_serial_number,
+                Some(read_future_callback::<V>),
+                state_ptr as *mut libc::c_void,
+            )
+        };
+        match status {
+            status::OK | status::PENDING | status::NOT_FOUND => {}
+            status => {
+                // The callback is not called for failed reads, so the future resolves now
+                drop(unsafe { Box::from_raw(state_ptr) });
+                let failed = Err(FasterError::Failed(FasterStatus::from(status)));
+                state.lock().unwrap().result = Some(failed);
+            }
+        }
+        ReadFuture { state }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::FasterError;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::time::Duration;
+
+pub type ReadResult<T> = Result<T, FasterError<'static>>;
+
+pub fn read_channel<T>() -> (Sender<ReadResult<T>>, ReadHandle<T>) {
+    let (sender, receiver) = channel();
+    (sender, ReadHandle { receiver })
+}
+
+/// Result of a read, which may still be pending.
+///
+/// Unlike a plain `Receiver`, receiving from a `ReadHandle` tells apart why no value was
+/// returned: [NotFound](enum.FasterError.html#variant.NotFound) if the key does not exist,
+/// [Deserialize](enum.FasterError.html#variant.Deserialize) if the stored value is not of
+/// the requested type, and [Disconnected](enum.FasterError.html#variant.Disconnected) if
+/// the read was dropped without a result, e.g. because it was aborted or expired.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterError, FasterKv};
+/// let store = FasterKv::default();
+///
+/// let (_, handle) = store.read::<u64, u64>(&1, 1);
+/// match handle.recv() {
+///     Err(FasterError::NotFound) => {}
+///     _ => panic!("Key 1 was never written"),
+/// }
+/// ```
+pub struct ReadHandle<T> {
+    receiver: Receiver<ReadResult<T>>,
+}
+
+impl<T> ReadHandle<T> {
+    /// Blocks until the read completes
+    pub fn recv(&self) -> ReadResult<T> {
+        match self.receiver.recv() {
+            Ok(result) => result,
+            Err(_) => Err(FasterError::Disconnected),
+        }
+    }
+
+    /// Blocks until the read completes, returning `None` instead of an error if the key
+    /// does not exist. FASTER reports deleted keys the same as keys that were never
+    /// written, so the two can't be told apart.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &42u64, 1);
+    /// store.delete(&1u64, 1);
+    /// let (_, handle) = store.read::<u64, u64>(&1, 1);
+    /// assert_eq!(None, handle.recv_optional().unwrap());
+    /// ```
+    pub fn recv_optional(&self) -> ReadResult<Option<T>> {
+        match self.recv() {
+            Ok(value) => Ok(Some(value)),
+            Err(FasterError::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the result if the read has completed, or `None` if it is still pending
+    pub fn try_recv(&self) -> Option<ReadResult<T>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(FasterError::Disconnected)),
+        }
+    }
+
+    /// Blocks until the read completes or `timeout` elapses, returning `None` on timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<ReadResult<T>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(FasterError::Disconnected)),
+        }
+    }
+}
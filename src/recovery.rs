@@ -0,0 +1,97 @@
+use crate::status::FasterStatus;
+use crate::{FasterError, FasterKv};
+
+/// Tokens of the index and hybrid log checkpoints to recover a store from.
+///
+/// Both are plain strings, so they can be persisted anywhere and passed to
+/// [recover_from](struct.FasterKv.html#method.recover_from) after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CheckpointToken {
+    pub index: String,
+    pub hybrid_log: String,
+}
+
+impl CheckpointToken {
+    /// Tokens of a full checkpoint, which uses the same token for the index and the log
+    pub fn full(token: &str) -> CheckpointToken {
+        CheckpointToken {
+            index: token.to_owned(),
+            hybrid_log: token.to_owned(),
+        }
+    }
+}
+
+/// Session that was active when a recovered checkpoint was taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredSession {
+    pub guid: String,
+    /// Serial number of the last operation of the session that is part of the checkpoint.
+    /// Operations with higher serial numbers have to be replayed.
+    pub serial_num: u64,
+}
+
+/// Result of a successful recovery
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    pub version: u32,
+    pub sessions: Vec<RecoveredSession>,
+}
+
+impl FasterKv {
+    /// Takes a full checkpoint, returning the tokens to recover it with
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKvBuilder, Storage};
+    /// let storage = Storage::temporary().unwrap();
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_storage(storage.clone())
+    ///     .build()
+    ///     .unwrap();
+    /// store.start_session();
+    /// store.upsert(&1u64, &42u64, 1);
+    /// let token = store.checkpoint_token().unwrap();
+    /// store.stop_session();
+    ///
+    /// let recovered = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_storage(storage)
+    ///     .build()
+    ///     .unwrap();
+    /// let recovery = recovered.recover_from(&token).unwrap();
+    /// assert_eq!(recovery.sessions.len(), 1);
+    /// ```
+    pub fn checkpoint_token(&self) -> Result<CheckpointToken, FasterError<'_>> {
+        let checkpoint = self.checkpoint()?;
+        if !checkpoint.checked {
+            return Err(FasterError::CheckpointError);
+        }
+        Ok(CheckpointToken::full(&checkpoint.token))
+    }
+
+    /// Recovers the checkpoints identified by `token`, along with the serial number each
+    /// session had reached.
+    ///
+    /// Serial numbers are looked up by continuing and stopping every recovered session on
+    /// the calling thread, which therefore must not have a session of its own. The sessions
+    /// can be continued again afterwards to resume them.
+    pub fn recover_from(&self, token: &CheckpointToken) -> Result<Recovery, FasterError<'_>> {
+        let recover = self.recover(token.index.clone(), token.hybrid_log.clone())?;
+        match FasterStatus::from(recover.status) {
+            FasterStatus::Ok => {}
+            status => return Err(FasterError::Failed(status)),
+        }
+        let sessions = recover
+            .session_ids
+            .into_iter()
+            .map(|guid| {
+                let serial_num = self.continue_session(guid.clone());
+                self.stop_session();
+                RecoveredSession { guid, serial_num }
+            })
+            .collect();
+        Ok(Recovery {
+            version: recover.version,
+            sessions,
+        })
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Spreads the offsets of successively created schedules evenly over any interval
+const OFFSET_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+static SCHEDULES: AtomicU64 = AtomicU64::new(0);
+
+/// Decides when a thread should [refresh](struct.FasterKv.html#method.refresh) its session:
+/// once every `interval` operations, starting at an offset that differs between schedules.
+///
+/// Threads refreshing whenever a shared operation counter hits a multiple of the interval
+/// all bump the epoch at the same moments and contend on it. Giving each thread its own
+/// schedule staggers their refreshes instead.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, RefreshSchedule};
+/// let store = FasterKv::default();
+/// let _session = store.start_session();
+///
+/// let mut schedule = RefreshSchedule::new(64);
+/// for i in 0..1000u64 {
+///     store.upsert(&i, &i, i);
+///     if schedule.tick() {
+///         store.refresh();
+///     }
+/// }
+/// assert!(schedule.refreshes() >= 15);
+/// store.stop_session();
+/// ```
+pub struct RefreshSchedule {
+    interval: u64,
+    countdown: u64,
+    refreshes: u64,
+}
+
+impl RefreshSchedule {
+    pub fn new(interval: u64) -> RefreshSchedule {
+        assert!(interval > 0, "Refresh interval must be positive");
+        let n = SCHEDULES.fetch_add(1, Ordering::Relaxed);
+        RefreshSchedule {
+            interval,
+            countdown: n.wrapping_mul(OFFSET_MULTIPLIER) % interval + 1,
+            refreshes: 0,
+        }
+    }
+
+    /// Counts an operation, returning whether the session should be refreshed now
+    pub fn tick(&mut self) -> bool {
+        self.countdown -= 1;
+        if self.countdown > 0 {
+            return false;
+        }
+        self.countdown = self.interval;
+        self.refreshes += 1;
+        true
+    }
+
+    /// Number of times [tick](#method.tick) returned `true`
+    pub fn refreshes(&self) -> u64 {
+        self.refreshes
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{status, FasterError, FasterKv, FasterKvBuilder, Storage};
+use std::path::Path;
+use std::{fs, io};
+
+const SELF_TEST_TABLE_SIZE: u64 = 1 << 10;
+const SELF_TEST_RECORDS: u64 = 64;
+
+fn record(i: u64) -> Vec<u8> {
+    format!("faster-rs self-test record {}", i).into_bytes()
+}
+
+// Describes an IO error in terms of what to do about it
+fn io_problem(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => "it does not exist, create it first".to_owned(),
+        io::ErrorKind::PermissionDenied => {
+            "the process may not create files in it, grant it read and write access".to_owned()
+        }
+        _ => err.to_string(),
+    }
+}
+
+// FASTER opens its log with O_DIRECT on Linux, which some filesystems reject
+#[cfg(target_os = "linux")]
+fn check_direct_io(dir: &Path) -> Result<(), String> {
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let probe = dir.join("direct-io-probe");
+    let res = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .custom_flags(libc::O_DIRECT)
+        .open(&probe);
+    let _ = fs::remove_file(&probe);
+    match res {
+        Ok(_) => Ok(()),
+        Err(ref err) if err.raw_os_error() == Some(libc::EINVAL) => Err(
+            "its filesystem does not support direct IO (O_DIRECT), which FASTER uses for its \
+             log, use a directory on a local disk (e.g. ext4 or xfs) rather than tmpfs or a \
+             network filesystem"
+                .to_owned(),
+        ),
+        Err(err) => Err(io_problem(&err)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_direct_io(_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn records_match(store: &FasterKv, monotonic_serial_number: u64) -> bool {
+    (0..SELF_TEST_RECORDS).all(|i| {
+        let (res, recv) = store.read_raw(&i.to_le_bytes(), monotonic_serial_number);
+        if res == status::PENDING {
+            store.complete_pending(true);
+        }
+        recv.recv().ok() == Some(record(i))
+    })
+}
+
+// Writes records, checkpoints them and reads them back after recovering
+fn check_store(storage: &Storage) -> Result<(), String> {
+    let open = || {
+        FasterKvBuilder::new(SELF_TEST_TABLE_SIZE, 0)
+            .with_storage(storage.clone())
+            .set_round_log_size(true)
+            .build()
+            .map_err(|err| format!("opening a store failed: {}", err))
+    };
+    let store = open()?;
+    store.start_session();
+    for i in 0..SELF_TEST_RECORDS {
+        store.upsert_raw(&i.to_le_bytes(), &record(i), i + 1);
+    }
+    store.complete_pending(true);
+    if !records_match(&store, SELF_TEST_RECORDS + 1) {
+        store.stop_session();
+        return Err("records written could not be read back".to_owned());
+    }
+    let token = store.checkpoint_token().map_err(|err| err.to_string());
+    store.complete_pending(true);
+    store.stop_session();
+    let token = token.map_err(|err| format!("checkpointing failed: {}", err))?;
+    drop(store);
+
+    let recovered = open()?;
+    if let Err(err) = recovered.recover_from(&token) {
+        return Err(format!("recovering a checkpoint failed: {}", err));
+    }
+    recovered.start_session();
+    let recovered_records = records_match(&recovered, 1);
+    recovered.stop_session();
+    if !recovered_records {
+        return Err("records were lost by a checkpoint and recovery".to_owned());
+    }
+    Ok(())
+}
+
+/// Checks that FASTER can persist to the directory at `dir`, to find problems with it when
+/// an application starts rather than once it relies on the directory.
+///
+/// A tiny store is opened in a temporary directory inside `dir`, which is removed
+/// afterwards. Records are written, read, checkpointed and read again after recovering the
+/// checkpoint. Missing directories, missing permissions and filesystems without the direct
+/// IO FASTER uses on Linux are reported as
+/// [UnusableStorage](enum.FasterError.html#variant.UnusableStorage) with what to change.
+///
+/// Like [recover_from](struct.FasterKv.html#method.recover_from), this must be called from
+/// a thread that does not have a session.
+///
+/// # Example
+/// ```
+/// let dir = tempfile::TempDir::new().unwrap();
+/// faster_rs::self_test(dir.path().to_str().unwrap()).unwrap();
+///
+/// assert!(faster_rs::self_test("/does/not/exist").is_err());
+/// ```
+pub fn self_test(dir: &str) -> Result<(), FasterError<'static>> {
+    let unusable = |reason: String| FasterError::UnusableStorage {
+        path: dir.to_owned(),
+        reason,
+    };
+    match fs::metadata(dir) {
+        Ok(ref metadata) if metadata.is_dir() => {}
+        Ok(_) => return Err(unusable("it is not a directory".to_owned())),
+        Err(err) => return Err(unusable(io_problem(&err))),
+    }
+    let storage = Storage::temporary_in(Path::new(dir)).map_err(|err| match err {
+        FasterError::IOError(err) => unusable(io_problem(&err)),
+        err => err,
+    })?;
+    check_direct_io(Path::new(storage.path())).map_err(unusable)?;
+    check_store(&storage).map_err(unusable)
+}
@@ -0,0 +1,245 @@
+use crate::arena::Arena;
+use crate::FasterKv;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+thread_local! {
+    // Refresh timestamps of the current thread's sessions, by the tracker they belong to
+    static LAST_REFRESH: RefCell<HashMap<usize, Arc<AtomicU64>>> = RefCell::new(HashMap::new());
+}
+
+/// Session that was started on a store and has not been stopped yet.
+///
+/// Sessions that are never stopped keep FASTER from advancing its epochs, which prevents
+/// memory from being reclaimed.
+#[derive(Clone, Debug)]
+pub struct ActiveSession {
+    pub guid: String,
+    pub thread: ThreadId,
+    pub thread_name: Option<String>,
+    pub started: Instant,
+    /// Last time the session was refreshed, only tracked when the store has a stall watchdog
+    pub last_refresh: Instant,
+    /// Where the session was started, if the store was built to record session backtraces
+    pub backtrace: Option<String>,
+}
+
+/// Session of the current thread, stopped when dropped.
+///
+/// FASTER keeps the state of a session in thread-local storage, so a session can't be moved
+/// to or continued on another thread while it is active. `Session` is therefore neither
+/// `Send` nor `Sync`: a task of a multi-threaded async runtime can't hold one across an
+/// `.await`, where it may be resumed on a different thread. Such tasks should instead start
+/// a session for each batch of operations that runs without yielding.
+///
+/// ```compile_fail
+/// use faster_rs::FasterKv;
+/// fn assert_send<T: Send>(_: T) {}
+/// let store = FasterKv::default();
+/// assert_send(store.session());
+/// ```
+pub struct Session<'a> {
+    store: &'a FasterKv,
+    guid: String,
+    // Only allocates once values are read into it
+    pub(crate) arena: Arena,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a> Session<'a> {
+    pub(crate) fn start(store: &'a FasterKv) -> Session<'a> {
+        Session {
+            guid: store.start_session(),
+            store,
+            arena: Arena::new(),
+            _not_send: PhantomData,
+        }
+    }
+
+    pub(crate) fn resume(store: &'a FasterKv, guid: String) -> (Session<'a>, u64) {
+        let serial_num = store.continue_session(guid.clone());
+        let session = Session {
+            guid,
+            store,
+            arena: Arena::new(),
+            _not_send: PhantomData,
+        };
+        (session, serial_num)
+    }
+
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+}
+
+impl<'a> Deref for Session<'a> {
+    type Target = FasterKv;
+
+    fn deref(&self) -> &FasterKv {
+        self.store
+    }
+}
+
+impl<'a> Drop for Session<'a> {
+    fn drop(&mut self) {
+        self.store.complete_pending(true);
+        self.store.stop_session();
+    }
+}
+
+pub type StallCallback = Arc<dyn Fn(&[ActiveSession]) + Send + Sync>;
+
+struct TrackedSession {
+    session: ActiveSession,
+    // Nanoseconds since the tracker was created
+    last_refresh: Arc<AtomicU64>,
+}
+
+// FASTER keeps one session per thread, so sessions are tracked by the thread they belong to
+pub struct SessionTracker {
+    sessions: Mutex<HashMap<ThreadId, TrackedSession>>,
+    created: Instant,
+    record_backtraces: bool,
+    track_refreshes: bool,
+}
+
+impl SessionTracker {
+    pub fn new(record_backtraces: bool, track_refreshes: bool) -> SessionTracker {
+        SessionTracker {
+            sessions: Mutex::new(HashMap::new()),
+            created: Instant::now(),
+            record_backtraces,
+            track_refreshes,
+        }
+    }
+
+    fn id(&self) -> usize {
+        self as *const SessionTracker as usize
+    }
+
+    fn now(&self) -> u64 {
+        self.created.elapsed().as_nanos() as u64
+    }
+
+    pub fn started(&self, guid: &str) {
+        let current = thread::current();
+        let backtrace = if self.record_backtraces {
+            Some(Backtrace::force_capture().to_string())
+        } else {
+            None
+        };
+        let started = Instant::now();
+        let last_refresh = Arc::new(AtomicU64::new(self.now()));
+        if self.track_refreshes {
+            LAST_REFRESH.with(|refreshes| {
+                refreshes
+                    .borrow_mut()
+                    .insert(self.id(), Arc::clone(&last_refresh))
+            });
+        }
+        let session = ActiveSession {
+            guid: guid.to_owned(),
+            thread: current.id(),
+            thread_name: current.name().map(String::from),
+            started,
+            last_refresh: started,
+            backtrace,
+        };
+        self.sessions.lock().unwrap().insert(
+            current.id(),
+            TrackedSession {
+                session,
+                last_refresh,
+            },
+        );
+    }
+
+    pub fn refreshed(&self) {
+        if self.track_refreshes {
+            LAST_REFRESH.with(|refreshes| {
+                if let Some(last_refresh) = refreshes.borrow().get(&self.id()) {
+                    last_refresh.store(self.now(), Ordering::Relaxed);
+                }
+            });
+        }
+    }
+
+    pub fn stopped(&self) {
+        if self.track_refreshes {
+            LAST_REFRESH.with(|refreshes| refreshes.borrow_mut().remove(&self.id()));
+        }
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(&thread::current().id());
+    }
+
+    pub fn on_current_thread(&self) -> bool {
+        self.sessions
+            .lock()
+            .unwrap()
+            .contains_key(&thread::current().id())
+    }
+
+    pub fn active(&self) -> Vec<ActiveSession> {
+        let mut sessions: Vec<ActiveSession> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|tracked| {
+                let mut session = tracked.session.clone();
+                if self.track_refreshes {
+                    let nanos = tracked.last_refresh.load(Ordering::Relaxed);
+                    session.last_refresh = self.created + Duration::from_nanos(nanos);
+                }
+                session
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.started);
+        sessions
+    }
+}
+
+/// Periodically checks for sessions that have not been refreshed within `timeout`, which
+/// keeps FASTER's safe epoch from advancing. Stalled sessions are passed to `callback` once
+/// per stall. The watchdog stops when the tracker is dropped.
+pub fn spawn_stall_watchdog(
+    tracker: Weak<SessionTracker>,
+    timeout: Duration,
+    callback: StallCallback,
+) {
+    let interval = std::cmp::max(timeout / 4, Duration::from_millis(10));
+    thread::spawn(move || {
+        let mut reported = HashSet::new();
+        loop {
+            thread::sleep(interval);
+            let tracker = match tracker.upgrade() {
+                Some(tracker) => tracker,
+                None => return,
+            };
+            let now = Instant::now();
+            let stalled: Vec<ActiveSession> = tracker
+                .active()
+                .into_iter()
+                .filter(|session| now.duration_since(session.last_refresh) > timeout)
+                .collect();
+            let new_stalls: Vec<ActiveSession> = stalled
+                .iter()
+                .filter(|session| !reported.contains(&session.guid))
+                .cloned()
+                .collect();
+            reported = stalled.into_iter().map(|session| session.guid).collect();
+            if !new_stalls.is_empty() {
+                callback(&new_stalls);
+            }
+        }
+    });
+}
@@ -0,0 +1,121 @@
+use crate::codec::encode;
+use crate::{status, FasterKey, FasterKv};
+
+// Soft-deleted records are kept under their encoded key behind this marker, which no
+// key encoded by the typed API starts with in practice
+const DELETED_MARKER: &[u8] = b"\0faster-rs/soft-deleted\0";
+
+fn deleted_key(encoded_key: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(DELETED_MARKER.len() + encoded_key.len());
+    key.extend_from_slice(DELETED_MARKER);
+    key.extend_from_slice(encoded_key);
+    key
+}
+
+// Reads the bytes stored under an encoded key, waiting for the read if it goes pending
+fn read_encoded(
+    store: &FasterKv,
+    encoded_key: &[u8],
+    monotonic_serial_number: u64,
+) -> Result<Option<Vec<u8>>, u8> {
+    let (res, recv) = store.read_raw(encoded_key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        res => Err(res),
+    }
+}
+
+// Moves the record under `from` to `to`, returning NOT_FOUND if there is none
+fn move_record(store: &FasterKv, from: &[u8], to: &[u8], monotonic_serial_number: u64) -> u8 {
+    let value = match read_encoded(store, from, monotonic_serial_number) {
+        Ok(Some(value)) => value,
+        Ok(None) => return status::NOT_FOUND,
+        Err(res) => return res,
+    };
+    let res = store.upsert_encoded(to.to_vec(), value, monotonic_serial_number);
+    if res != status::OK && res != status::PENDING {
+        return res;
+    }
+    store.delete_encoded(from.to_vec(), monotonic_serial_number)
+}
+
+impl FasterKv {
+    /// Deletes `key` such that it can be brought back with [restore](#method.restore).
+    ///
+    /// Reads treat the key as deleted, while its value is kept aside until the key is
+    /// restored, [purged](#method.purge) or soft-deleted again. Returns
+    /// [NOT_FOUND](status/constant.NOT_FOUND.html) if the key does not exist.
+    ///
+    /// Moving the value aside takes a read, an upsert and a delete, which are not atomic:
+    /// writes to the key from other threads in between may be lost. Views see a soft delete
+    /// as a delete and a restore as an upsert.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &42u64, 1);
+    /// assert_eq!(status::OK, store.soft_delete(&1u64, 2));
+    /// let (res, _) = store.read::<u64, u64>(&1, 3);
+    /// assert_eq!(status::NOT_FOUND, res);
+    ///
+    /// assert_eq!(status::OK, store.restore(&1u64, 4));
+    /// let (res, recv) = store.read::<u64, u64>(&1, 5);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(42, recv.recv().unwrap());
+    /// ```
+    pub fn soft_delete<K>(&self, key: &K, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+    {
+        let encoded_key = encode(key);
+        let res = move_record(
+            self,
+            &encoded_key,
+            &deleted_key(&encoded_key),
+            monotonic_serial_number,
+        );
+        if res == status::OK || res == status::PENDING {
+            self.views_deleted(&encoded_key, monotonic_serial_number);
+        }
+        res
+    }
+
+    /// Brings back the value of a [soft-deleted](#method.soft_delete) key, overwriting any
+    /// value written under the key since. Returns
+    /// [NOT_FOUND](status/constant.NOT_FOUND.html) if the key is not soft-deleted.
+    pub fn restore<K>(&self, key: &K, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+    {
+        let encoded_key = encode(key);
+        let deleted_key = deleted_key(&encoded_key);
+        let value = match read_encoded(self, &deleted_key, monotonic_serial_number) {
+            Ok(Some(value)) => value,
+            Ok(None) => return status::NOT_FOUND,
+            Err(res) => return res,
+        };
+        self.views_upserted(&encoded_key, &value, monotonic_serial_number);
+        let res = self.upsert_encoded(encoded_key, value, monotonic_serial_number);
+        if res != status::OK && res != status::PENDING {
+            return res;
+        }
+        self.delete_encoded(deleted_key, monotonic_serial_number)
+    }
+
+    /// Permanently drops the value of a [soft-deleted](#method.soft_delete) key, so it can
+    /// no longer be restored. Returns [NOT_FOUND](status/constant.NOT_FOUND.html) if the key
+    /// is not soft-deleted.
+    pub fn purge<K>(&self, key: &K, monotonic_serial_number: u64) -> u8
+    where
+        K: FasterKey,
+    {
+        let encoded_key = encode(key);
+        self.delete_encoded(deleted_key(&encoded_key), monotonic_serial_number)
+    }
+}
@@ -7,3 +7,53 @@ pub const OUT_OF_MEMORY: u8 = 3;
 pub const IO_ERROR: u8 = 4;
 pub const CORRUPTION: u8 = 5;
 pub const ABORTED: u8 = 6;
+
+/// Status values as an enum, to match on instead of comparing codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FasterStatus {
+    Ok,
+    Pending,
+    NotFound,
+    OutOfMemory,
+    IoError,
+    Corruption,
+    Aborted,
+}
+
+impl FasterStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            FasterStatus::Ok => OK,
+            FasterStatus::Pending => PENDING,
+            FasterStatus::NotFound => NOT_FOUND,
+            FasterStatus::OutOfMemory => OUT_OF_MEMORY,
+            FasterStatus::IoError => IO_ERROR,
+            FasterStatus::Corruption => CORRUPTION,
+            FasterStatus::Aborted => ABORTED,
+        }
+    }
+
+    /// Whether the operation failed, as opposed to succeeding, going pending or not
+    /// finding its key
+    pub fn is_error(self) -> bool {
+        !matches!(
+            self,
+            FasterStatus::Ok | FasterStatus::Pending | FasterStatus::NotFound
+        )
+    }
+}
+
+impl From<u8> for FasterStatus {
+    fn from(code: u8) -> FasterStatus {
+        match code {
+            OK => FasterStatus::Ok,
+            PENDING => FasterStatus::Pending,
+            NOT_FOUND => FasterStatus::NotFound,
+            OUT_OF_MEMORY => FasterStatus::OutOfMemory,
+            IO_ERROR => FasterStatus::IoError,
+            CORRUPTION => FasterStatus::Corruption,
+            ABORTED => FasterStatus::Aborted,
+            code => panic!("Unknown FASTER status {}", code),
+        }
+    }
+}
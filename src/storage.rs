@@ -0,0 +1,241 @@
+use crate::FasterError;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, io, process};
+
+static UNIQUE_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Creates a new directory inside `parent`. The name combines the process id, a per-process
+// counter and the time, so directories created concurrently never collide.
+fn create_unique_dir(parent: &Path) -> Result<String, FasterError<'static>> {
+    let dir = loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.subsec_nanos())
+            .unwrap_or(0);
+        let name = format!(
+            "faster-rs-{}-{}-{}",
+            process::id(),
+            UNIQUE_DIR_COUNTER.fetch_add(1, Ordering::SeqCst),
+            nanos
+        );
+        let dir = parent.join(name);
+        match fs::create_dir(&dir) {
+            Ok(()) => break dir,
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(FasterError::IOError(err)),
+        }
+    };
+    match dir.into_os_string().into_string() {
+        Ok(path) => Ok(path),
+        Err(dir) => {
+            fs::remove_dir(dir)?;
+            Err(FasterError::BuilderError(
+                "Storage directory path is not valid UTF-8",
+            ))
+        }
+    }
+}
+
+// Layout of FASTER's storage directory
+const LOG_SEGMENT_PREFIX: &str = "log.log";
+const INDEX_CHECKPOINTS_DIR: &str = "index-checkpoints";
+const LOG_CHECKPOINTS_DIR: &str = "cpr-checkpoints";
+// Written once a checkpoint is complete
+const CHECKPOINT_METADATA_FILE: &str = "info.dat";
+
+fn size_of_path(path: &Path) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += size_of_path(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+// Checkpoints in `dir`, paired with whether they were completed
+fn checkpoints_in(dir: &Path) -> io::Result<Vec<(PathBuf, bool)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut checkpoints = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let complete = path.join(CHECKPOINT_METADATA_FILE).exists();
+        checkpoints.push((path, complete));
+    }
+    Ok(checkpoints)
+}
+
+/// Bytes used by each component of a storage directory
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub log_segments: u64,
+    pub index_checkpoints: u64,
+    pub log_checkpoints: u64,
+    /// Checkpoints that were never completed, e.g. because the process crashed
+    pub orphaned: u64,
+    /// Files that are not part of FASTER's layout
+    pub other: u64,
+}
+
+impl DiskUsage {
+    pub fn total(&self) -> u64 {
+        self.log_segments
+            + self.index_checkpoints
+            + self.log_checkpoints
+            + self.orphaned
+            + self.other
+    }
+}
+
+struct StorageDir {
+    path: String,
+    temporary: bool,
+}
+
+impl Drop for StorageDir {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Directory a store persists its log and checkpoints to.
+///
+/// Stores only share a directory when they are given the same path or a clone of the same
+/// handle. Temporary directories are removed once the last handle, including the ones held
+/// by stores, is dropped.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKvBuilder, Storage};
+/// let storage = Storage::temporary().unwrap();
+/// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+///     .with_storage(storage.clone())
+///     .build()
+///     .unwrap();
+/// assert!(store.checkpoint().unwrap().checked);
+/// ```
+#[derive(Clone)]
+pub struct Storage {
+    dir: Arc<StorageDir>,
+}
+
+impl Storage {
+    /// Uses the directory at `path`, which is kept after use
+    pub fn at(path: &str) -> Storage {
+        Storage {
+            dir: Arc::new(StorageDir {
+                path: path.to_owned(),
+                temporary: false,
+            }),
+        }
+    }
+
+    /// Creates a uniquely named directory inside `parent`, which is kept after use
+    pub fn unique_in(parent: &str) -> Result<Storage, FasterError<'static>> {
+        Ok(Storage {
+            dir: Arc::new(StorageDir {
+                path: create_unique_dir(Path::new(parent))?,
+                temporary: false,
+            }),
+        })
+    }
+
+    /// Creates a uniquely named directory in the system's temporary directory, which is
+    /// removed once it is no longer used
+    pub fn temporary() -> Result<Storage, FasterError<'static>> {
+        Ok(Storage {
+            dir: Arc::new(StorageDir {
+                path: create_unique_dir(&env::temp_dir())?,
+                temporary: true,
+            }),
+        })
+    }
+
+    // Like `temporary`, but inside `parent`
+    pub(crate) fn temporary_in(parent: &Path) -> Result<Storage, FasterError<'static>> {
+        Ok(Storage {
+            dir: Arc::new(StorageDir {
+                path: create_unique_dir(parent)?,
+                temporary: true,
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.dir.path
+    }
+
+    pub fn is_temporary(&self) -> bool {
+        self.dir.temporary
+    }
+
+    /// Bytes used by the log segments and checkpoints in the directory
+    pub fn disk_usage(&self) -> io::Result<DiskUsage> {
+        let root = Path::new(self.path());
+        let mut usage = DiskUsage::default();
+        for entry in fs::read_dir(root)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == INDEX_CHECKPOINTS_DIR || name == LOG_CHECKPOINTS_DIR {
+                continue;
+            }
+            let size = size_of_path(&entry.path())?;
+            if name.starts_with(LOG_SEGMENT_PREFIX) {
+                usage.log_segments += size;
+            } else {
+                usage.other += size;
+            }
+        }
+        for (path, complete) in checkpoints_in(&root.join(INDEX_CHECKPOINTS_DIR))? {
+            let size = size_of_path(&path)?;
+            match complete {
+                true => usage.index_checkpoints += size,
+                false => usage.orphaned += size,
+            }
+        }
+        for (path, complete) in checkpoints_in(&root.join(LOG_CHECKPOINTS_DIR))? {
+            let size = size_of_path(&path)?;
+            match complete {
+                true => usage.log_checkpoints += size,
+                false => usage.orphaned += size,
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Checkpoint directories that were never completed
+    pub fn orphaned_checkpoints(&self) -> io::Result<Vec<PathBuf>> {
+        let root = Path::new(self.path());
+        let mut orphaned = Vec::new();
+        for dir in &[INDEX_CHECKPOINTS_DIR, LOG_CHECKPOINTS_DIR] {
+            for (path, complete) in checkpoints_in(&root.join(dir))? {
+                if !complete {
+                    orphaned.push(path);
+                }
+            }
+        }
+        Ok(orphaned)
+    }
+
+    /// Removes the checkpoint directories that were never completed, returning the number
+    /// of bytes freed. A checkpoint that is still being taken looks incomplete, so this
+    /// must not be called while a store using the directory is checkpointing.
+    pub fn remove_orphaned_checkpoints(&self) -> io::Result<u64> {
+        let mut freed = 0;
+        for path in self.orphaned_checkpoints()? {
+            freed += size_of_path(&path)?;
+            fs::remove_dir_all(&path)?;
+        }
+        Ok(freed)
+    }
+}
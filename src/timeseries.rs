@@ -0,0 +1,166 @@
+use crate::{status, FasterKv, FasterRmw, FasterValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+#[derive(Serialize, Deserialize)]
+enum SeriesKey {
+    // Points of a series within one time bucket
+    Bucket(String, u64),
+    // Lower bound on the oldest bucket of a series that may hold points
+    Oldest(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct OldestBucket(u64);
+
+impl FasterRmw for OldestBucket {
+    fn rmw(&self, modification: Self) -> Self {
+        OldestBucket(std::cmp::min(self.0, modification.0))
+    }
+}
+
+fn read_value<V: FasterValue>(
+    store: &FasterKv,
+    key: &SeriesKey,
+    monotonic_serial_number: u64,
+) -> Result<Option<V>, u8> {
+    let (res, recv) = store.read(key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        res => Err(res),
+    }
+}
+
+/// Append-mostly storage of time series.
+///
+/// Points are grouped into blocks per series and time bucket of `bucket_size`, keyed by
+/// (series, bucket), so appending is a single Read-Modify-Write and range queries read one
+/// record per bucket in the range. Buckets should be large enough that ranges span few of
+/// them, but small enough that a block is cheap to copy when it is appended to outside
+/// the mutable region.
+///
+/// Retention deletes the blocks before a cutoff. FASTER's C interface does not expose
+/// shifting the log's begin address, so the space of deleted blocks is only reclaimed
+/// once FASTER truncates the log itself.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, TimeSeries};
+/// let store = FasterKv::default();
+/// let series = TimeSeries::<f64>::new(60);
+///
+/// series.append(&store, "cpu.host1", 10, &0.5, 1);
+/// series.append(&store, "cpu.host1", 70, &0.7, 1);
+/// series.append(&store, "cpu.host1", 130, &0.9, 1);
+/// assert_eq!(
+///     vec![(70, 0.7), (130, 0.9)],
+///     series.range(&store, "cpu.host1", 60, 200, 1).unwrap()
+/// );
+///
+/// series.truncate(&store, "cpu.host1", 120, 1).unwrap();
+/// assert_eq!(
+///     vec![(130, 0.9)],
+///     series.range(&store, "cpu.host1", 0, 200, 1).unwrap()
+/// );
+/// ```
+pub struct TimeSeries<V> {
+    bucket_size: u64,
+    values: PhantomData<V>,
+}
+
+impl<V> TimeSeries<V>
+where
+    V: Serialize + DeserializeOwned + Clone,
+{
+    pub fn new(bucket_size: u64) -> TimeSeries<V> {
+        assert!(bucket_size > 0, "Bucket size must be positive");
+        TimeSeries {
+            bucket_size,
+            values: PhantomData,
+        }
+    }
+
+    fn bucket(&self, timestamp: u64) -> u64 {
+        timestamp / self.bucket_size
+    }
+
+    pub fn append(
+        &self,
+        store: &FasterKv,
+        series: &str,
+        timestamp: u64,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> u8 {
+        let bucket = self.bucket(timestamp);
+        store.rmw(
+            &SeriesKey::Oldest(series.to_owned()),
+            &OldestBucket(bucket),
+            monotonic_serial_number,
+        );
+        store.rmw(
+            &SeriesKey::Bucket(series.to_owned(), bucket),
+            &vec![(timestamp, value.clone())],
+            monotonic_serial_number,
+        )
+    }
+
+    /// Points of `series` with timestamps in `[from, to)`, ordered by timestamp
+    pub fn range(
+        &self,
+        store: &FasterKv,
+        series: &str,
+        from: u64,
+        to: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<(u64, V)>, u8> {
+        let mut points = Vec::new();
+        if from >= to {
+            return Ok(points);
+        }
+        for bucket in self.bucket(from)..=self.bucket(to - 1) {
+            let key = SeriesKey::Bucket(series.to_owned(), bucket);
+            let block: Option<Vec<(u64, V)>> = read_value(store, &key, monotonic_serial_number)?;
+            points.extend(
+                block
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(timestamp, _)| *timestamp >= from && *timestamp < to),
+            );
+        }
+        // Points of a bucket are in arrival order, which may differ from timestamp order
+        points.sort_by_key(|(timestamp, _)| *timestamp);
+        Ok(points)
+    }
+
+    /// Deletes the blocks of `series` that only hold points before `before`. Points in the
+    /// bucket containing `before` are kept.
+    pub fn truncate(
+        &self,
+        store: &FasterKv,
+        series: &str,
+        before: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<(), u8> {
+        let oldest_key = SeriesKey::Oldest(series.to_owned());
+        let oldest = match read_value(store, &oldest_key, monotonic_serial_number)? {
+            Some(OldestBucket(oldest)) => oldest,
+            None => return Ok(()),
+        };
+        let cutoff = self.bucket(before);
+        for bucket in oldest..cutoff {
+            let key = SeriesKey::Bucket(series.to_owned(), bucket);
+            store.delete(&key, monotonic_serial_number);
+        }
+        if cutoff > oldest {
+            store.upsert(&oldest_key, &OldestBucket(cutoff), monotonic_serial_number);
+        }
+        Ok(())
+    }
+}
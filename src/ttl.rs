@@ -0,0 +1,95 @@
+use crate::codec::{decode_error, encode};
+use crate::read_handle::read_channel;
+use crate::util::unix_millis;
+use crate::{status, FasterError, FasterKey, FasterKv, FasterValue, ReadHandle};
+use std::time::Duration;
+
+// Records with a TTL are stored as their expiry, in milliseconds since the Unix epoch as 8
+// little-endian bytes, followed by the encoded value. Wall-clock time lets the expiry
+// outlive a recovery.
+const EXPIRY_LENGTH: usize = 8;
+
+// Decodes a record written by upsert_with_ttl, reporting it as not found once expired
+fn decode_unexpired<V: FasterValue>(bytes: Vec<u8>, now: u64) -> Result<V, FasterError<'static>> {
+    if bytes.len() < EXPIRY_LENGTH {
+        return Err(FasterError::Deserialize {
+            bytes,
+            source: decode_error("Record has no expiry"),
+        });
+    }
+    let mut expiry = [0u8; EXPIRY_LENGTH];
+    expiry.copy_from_slice(&bytes[..EXPIRY_LENGTH]);
+    if u64::from_le_bytes(expiry) <= now {
+        return Err(FasterError::NotFound);
+    }
+    V::faster_deserialize(&bytes[EXPIRY_LENGTH..])
+        .map_err(|source| FasterError::Deserialize { bytes, source })
+}
+
+impl FasterKv {
+    /// Upserts `value` under `key` such that it expires `ttl` from now, for cache-style use
+    /// without encoding an expiry in every value type.
+    ///
+    /// The expiry is stored in front of the value, so the record has to be read with
+    /// [read_with_ttl](#method.read_with_ttl) rather than `read`. Expired records are not
+    /// removed by themselves: they keep their space in the log until the key is written or
+    /// deleted again. Views do not see records written with a TTL.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{status, FasterKv};
+    /// use std::time::Duration;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert_with_ttl(&1u64, &42u64, Duration::from_secs(60), 1);
+    /// let (res, recv) = store.read_with_ttl::<u64, u64>(&1, 2);
+    /// assert_eq!(status::OK, res);
+    /// assert_eq!(42, recv.recv().unwrap());
+    ///
+    /// store.upsert_with_ttl(&2u64, &7u64, Duration::from_secs(0), 3);
+    /// let (res, _) = store.read_with_ttl::<u64, u64>(&2, 4);
+    /// assert_eq!(status::NOT_FOUND, res);
+    /// ```
+    pub fn upsert_with_ttl<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> u8
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let expires_at = unix_millis().saturating_add(ttl.as_millis() as u64);
+        let encoded_value = encode(value);
+        let mut record = Vec::with_capacity(EXPIRY_LENGTH + encoded_value.len());
+        record.extend_from_slice(&expires_at.to_le_bytes());
+        record.extend_from_slice(&encoded_value);
+        self.upsert_encoded(encode(key), record, monotonic_serial_number)
+    }
+
+    /// Reads a record written with [upsert_with_ttl](#method.upsert_with_ttl), returning
+    /// [NOT_FOUND](status/constant.NOT_FOUND.html) once it has expired. A read that goes
+    /// pending is completed before returning, so the handle holds the result right away.
+    pub fn read_with_ttl<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, ReadHandle<V>)
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let (sender, handle) = read_channel();
+        let (res, recv) = self.read_raw(&encode(key), monotonic_serial_number);
+        if res == status::PENDING {
+            self.complete_pending(true);
+        }
+        let result = recv
+            .recv()
+            .and_then(|bytes| decode_unexpired(bytes, unix_millis()));
+        let res = match result {
+            Err(FasterError::NotFound) => status::NOT_FOUND,
+            _ => res,
+        };
+        let _ = sender.send(result);
+        (res, handle)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{FasterKey, FasterKv, FasterRmw, FasterValue, ReadHandle};
+use std::marker::PhantomData;
+
+/// Store whose key and value types are fixed at construction.
+///
+/// `FasterKv` accepts any key and value type on each call, so nothing stops a key upserted
+/// with one value type from being read as another. `TypedFasterKv` wraps a store and only
+/// accepts `K` and `V`, so such mismatches are compile errors. The wrapped store is still
+/// available for sessions, checkpoints and recovery.
+///
+/// # Example
+/// ```
+/// use faster_rs::{status, FasterKv, TypedFasterKv};
+/// let store: TypedFasterKv<u64, String> = TypedFasterKv::new(FasterKv::default());
+///
+/// store.upsert(&1, &String::from("Hello, "), 1);
+/// store.rmw(&1, &String::from("World!"), 1);
+/// let (res, recv) = store.read(&1, 1);
+/// assert_eq!(status::OK, res);
+/// assert_eq!("Hello, World!", recv.recv().unwrap());
+/// ```
+pub struct TypedFasterKv<K, V> {
+    store: FasterKv,
+    types: PhantomData<fn(K, V)>,
+}
+
+impl<K, V> TypedFasterKv<K, V>
+where
+    K: FasterKey,
+    V: FasterValue,
+{
+    pub fn new(store: FasterKv) -> TypedFasterKv<K, V> {
+        TypedFasterKv {
+            store,
+            types: PhantomData,
+        }
+    }
+
+    /// Underlying store, e.g. to manage sessions or take checkpoints
+    pub fn store(&self) -> &FasterKv {
+        &self.store
+    }
+
+    pub fn into_inner(self) -> FasterKv {
+        self.store
+    }
+
+    pub fn upsert(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8 {
+        self.store.upsert(key, value, monotonic_serial_number)
+    }
+
+    pub fn read(&self, key: &K, monotonic_serial_number: u64) -> (u8, ReadHandle<V>) {
+        self.store.read(key, monotonic_serial_number)
+    }
+
+    pub fn rmw(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
+    where
+        V: FasterRmw,
+    {
+        self.store.rmw(key, value, monotonic_serial_number)
+    }
+
+    pub fn delete(&self, key: &K, monotonic_serial_number: u64) -> u8 {
+        self.store.delete(key, monotonic_serial_number)
+    }
+}
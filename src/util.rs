@@ -1,6 +1,9 @@
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
+#[cfg(feature = "typed")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
 pub struct CheckPoint {
     pub checked: bool,
     pub token: String,
@@ -11,3 +14,11 @@ pub struct Recover {
     pub version: u32,
     pub session_ids: Vec<String>,
 }
+
+// Wall-clock time for state that has to outlive a recovery
+#[cfg(feature = "typed")]
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64)
+}
@@ -0,0 +1,268 @@
+use crate::codec::{FasterDeserialize, FasterSerialize};
+use crate::{status, FasterKey, FasterKv, FasterValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+#[derive(Serialize, Deserialize)]
+enum ViewKey {
+    // Contributions of the records in a group, keyed by encoded record key
+    Group(String, Vec<u8>),
+    // Encoded group a record currently contributes to
+    Member(String, Vec<u8>),
+}
+
+/// Views registered on a store, by name
+pub type Views = RwLock<HashMap<String, Arc<dyn ViewMaintainer>>>;
+
+pub trait ViewMaintainer: Send + Sync {
+    fn upserted(&self, store: &FasterKv, key: &[u8], value: &[u8], monotonic_serial_number: u64);
+    fn deleted(&self, store: &FasterKv, key: &[u8], monotonic_serial_number: u64);
+    // The view's ViewReader, for readers that know its types
+    fn reader(&self) -> &dyn Any;
+}
+
+struct ViewReader<G, M> {
+    reduce: fn(M, M) -> M,
+    groups: PhantomData<fn(G)>,
+}
+
+struct MaterializedView<K, V, G, M> {
+    name: String,
+    map: fn(&K, &V) -> (G, M),
+    reader: ViewReader<G, M>,
+    records: PhantomData<fn(K, V)>,
+}
+
+fn read_value<V: FasterValue>(
+    store: &FasterKv,
+    key: &ViewKey,
+    monotonic_serial_number: u64,
+) -> Result<Option<V>, u8> {
+    let (res, recv) = store.read(key, monotonic_serial_number);
+    if res == status::PENDING {
+        store.complete_pending(true);
+    }
+    match res {
+        status::OK | status::PENDING => Ok(recv.recv().ok()),
+        status::NOT_FOUND => Ok(None),
+        res => Err(res),
+    }
+}
+
+// Decodes `bytes` only if they are exactly the encoding of a T, so that records of other
+// types are left out of a view
+fn decode_exact<T: FasterSerialize + FasterDeserialize>(bytes: &[u8]) -> Option<T> {
+    let decoded = T::faster_deserialize(bytes).ok()?;
+    if decoded.faster_serialize().len() == bytes.len() {
+        Some(decoded)
+    } else {
+        None
+    }
+}
+
+impl<K, V, G, M> MaterializedView<K, V, G, M>
+where
+    M: Clone + Serialize + DeserializeOwned,
+{
+    // Removes the contribution of the record under `key` from its group, unless it is
+    // `unless_group`
+    fn leave_group(
+        &self,
+        store: &FasterKv,
+        key: &[u8],
+        unless_group: Option<&[u8]>,
+        monotonic_serial_number: u64,
+    ) {
+        let member_key = ViewKey::Member(self.name.clone(), key.to_vec());
+        let group: Vec<u8> = match read_value(store, &member_key, monotonic_serial_number) {
+            Ok(Some(group)) => group,
+            _ => return,
+        };
+        if unless_group != Some(&group[..]) {
+            let group_key = ViewKey::Group(self.name.clone(), group);
+            store.map_remove::<_, _, M>(&group_key, &key.to_vec(), monotonic_serial_number);
+        }
+    }
+}
+
+impl<K, V, G, M> ViewMaintainer for MaterializedView<K, V, G, M>
+where
+    K: FasterKey + 'static,
+    V: FasterValue + 'static,
+    G: Serialize + 'static,
+    M: Clone + Serialize + DeserializeOwned + 'static,
+{
+    fn upserted(&self, store: &FasterKv, key: &[u8], value: &[u8], monotonic_serial_number: u64) {
+        let (record_key, record_value) = match (decode_exact::<K>(key), decode_exact::<V>(value)) {
+            (Some(record_key), Some(record_value)) => (record_key, record_value),
+            _ => return,
+        };
+        let (group, contribution) = (self.map)(&record_key, &record_value);
+        let group = bincode::serialize(&group).unwrap();
+        self.leave_group(store, key, Some(&group), monotonic_serial_number);
+        let group_key = ViewKey::Group(self.name.clone(), group.clone());
+        store.map_insert(
+            &group_key,
+            &key.to_vec(),
+            &contribution,
+            monotonic_serial_number,
+        );
+        let member_key = ViewKey::Member(self.name.clone(), key.to_vec());
+        store.upsert_encoded(
+            bincode::serialize(&member_key).unwrap(),
+            bincode::serialize(&group).unwrap(),
+            monotonic_serial_number,
+        );
+    }
+
+    fn deleted(&self, store: &FasterKv, key: &[u8], monotonic_serial_number: u64) {
+        if decode_exact::<K>(key).is_none() {
+            return;
+        }
+        self.leave_group(store, key, None, monotonic_serial_number);
+        let member_key = ViewKey::Member(self.name.clone(), key.to_vec());
+        store.delete_encoded(
+            bincode::serialize(&member_key).unwrap(),
+            monotonic_serial_number,
+        );
+    }
+
+    fn reader(&self) -> &dyn Any {
+        &self.reader
+    }
+}
+
+/// Handle for reading a materialized view, see [create_view](struct.FasterKv.html#method.create_view)
+pub struct View<'a, G, M> {
+    store: &'a FasterKv,
+    name: String,
+    reduce: fn(M, M) -> M,
+    groups: PhantomData<G>,
+}
+
+impl<'a, G, M> View<'a, G, M>
+where
+    G: Serialize,
+    M: Clone + Serialize + DeserializeOwned,
+{
+    /// Reduced value of the records in `group`, or `None` if the group is empty
+    pub fn read(&self, group: &G, monotonic_serial_number: u64) -> Result<Option<M>, u8> {
+        let group_key = ViewKey::Group(self.name.clone(), bincode::serialize(group).unwrap());
+        let contributions: Option<BTreeMap<Vec<u8>, M>> =
+            read_value(self.store, &group_key, monotonic_serial_number)?;
+        Ok(contributions
+            .unwrap_or_default()
+            .into_values()
+            .fold(None, |reduced, contribution| match reduced {
+                Some(reduced) => Some((self.reduce)(reduced, contribution)),
+                None => Some(contribution),
+            }))
+    }
+}
+
+// Materialized aggregation views, maintained on upserts and deletes
+impl FasterKv {
+    /// Registers a view called `name` that groups records with `map` and combines the
+    /// values of each group with `reduce`. Returns false if a view with that name exists.
+    ///
+    /// The view is maintained on every upsert and delete of a record whose key and value
+    /// are encodings of `K` and `V`, so the types of a view should identify the records it
+    /// covers. Each record's contribution to its group is stored in the store itself, so
+    /// the view reflects records written after it was registered, and groups are reduced
+    /// when read. Records changed with Read-Modify-Write or through the raw API are not
+    /// reflected.
+    ///
+    /// Records whose key or value is not exactly the encoding of a `K` and `V` are skipped
+    /// without any error, as they are assumed to belong to other types. This includes
+    /// records written with a type that differs only in width, such as an `i32` literal
+    /// where the view expects a `u64`, so annotate the types of keys and values written for
+    /// a view.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// // Orders are keyed by (tenant, order id), and the view sums their amounts per tenant
+    /// store.create_view(
+    ///     "sum_by_tenant",
+    ///     |key: &(String, u64), amount: &u64| (key.0.clone(), *amount),
+    ///     |a: u64, b: u64| a + b,
+    /// );
+    ///
+    /// store.upsert(&(String::from("acme"), 1u64), &10u64, 1);
+    /// store.upsert(&(String::from("acme"), 2u64), &5u64, 1);
+    /// store.upsert(&(String::from("acme"), 1u64), &20u64, 1);
+    /// store.delete(&(String::from("acme"), 2u64), 1);
+    ///
+    /// let view = store.view::<String, u64>("sum_by_tenant").unwrap();
+    /// assert_eq!(Some(20), view.read(&String::from("acme"), 1).unwrap());
+    /// assert_eq!(None, view.read(&String::from("initech"), 1).unwrap());
+    /// ```
+    pub fn create_view<K, V, G, M>(
+        &self,
+        name: &str,
+        map: fn(&K, &V) -> (G, M),
+        reduce: fn(M, M) -> M,
+    ) -> bool
+    where
+        K: FasterKey + 'static,
+        V: FasterValue + 'static,
+        G: Serialize + 'static,
+        M: Clone + Serialize + DeserializeOwned + 'static,
+    {
+        let mut views = self.views.write().unwrap();
+        if views.contains_key(name) {
+            return false;
+        }
+        let view = MaterializedView {
+            name: name.to_owned(),
+            map,
+            reader: ViewReader {
+                reduce,
+                groups: PhantomData,
+            },
+            records: PhantomData,
+        };
+        views.insert(name.to_owned(), Arc::new(view));
+        true
+    }
+
+    /// Handle for reading the view called `name`, or `None` if there is no such view with
+    /// groups of type `G` and values of type `M`
+    pub fn view<G, M>(&self, name: &str) -> Option<View<'_, G, M>>
+    where
+        G: Serialize + 'static,
+        M: Clone + Serialize + DeserializeOwned + 'static,
+    {
+        let views = self.views.read().unwrap();
+        let reader = views
+            .get(name)?
+            .reader()
+            .downcast_ref::<ViewReader<G, M>>()?;
+        Some(View {
+            store: self,
+            name: name.to_owned(),
+            reduce: reader.reduce,
+            groups: PhantomData,
+        })
+    }
+
+    pub(crate) fn views_upserted(&self, key: &[u8], value: &[u8], monotonic_serial_number: u64) {
+        let views = self.views.read().unwrap();
+        for view in views.values() {
+            view.upserted(self, key, value, monotonic_serial_number);
+        }
+    }
+
+    pub(crate) fn views_deleted(&self, key: &[u8], monotonic_serial_number: u64) {
+        let views = self.views.read().unwrap();
+        for view in views.values() {
+            view.deleted(self, key, monotonic_serial_number);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{status, FasterKey, FasterKv};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+// Partial aggregates of the most recent windows, where window `i` lives in slot
+// `i % slots.len()` until a newer window takes its slot
+#[derive(Serialize, Deserialize, Default)]
+struct WindowRing<A> {
+    slots: Vec<Option<(u64, A)>>,
+}
+
+/// Sliding-window aggregation over the values of a key, as used in stream processing.
+///
+/// The partial aggregates of the last `num_windows` tumbling windows of `window_size`
+/// are kept in a ring buffer stored as the value of the key, and updated with
+/// Read-Modify-Write. Values arriving for a window that has already been evicted from the
+/// ring are dropped. Timestamps can be in any unit, as long as it is used consistently.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, WindowState};
+/// let store = FasterKv::default();
+/// // Sum of the values in each of the last 3 windows of 10 time units
+/// let windows = WindowState::new(10, 3, |sum: &mut u64, value: &u64| *sum += value);
+///
+/// windows.add(&store, &1, 3, &5, 1);
+/// windows.add(&store, &1, 7, &2, 1);
+/// windows.add(&store, &1, 12, &1, 1);
+/// assert_eq!(vec![(0, 7), (10, 1)], windows.windows(&store, &1, 12, 1).unwrap());
+///
+/// // By time 40 the windows starting at 0 and 10 have expired
+/// windows.add(&store, &1, 40, &4, 1);
+/// assert_eq!(vec![(40, 4)], windows.windows(&store, &1, 40, 1).unwrap());
+/// ```
+pub struct WindowState<T, A> {
+    window_size: u64,
+    num_windows: usize,
+    aggregate: fn(&mut A, &T),
+    values: PhantomData<T>,
+}
+
+impl<T, A> WindowState<T, A>
+where
+    A: Serialize + DeserializeOwned + Default + Clone,
+{
+    pub fn new(window_size: u64, num_windows: usize, aggregate: fn(&mut A, &T)) -> Self {
+        assert!(window_size > 0, "Window size must be positive");
+        assert!(num_windows > 0, "At least one window must be kept");
+        WindowState {
+            window_size,
+            num_windows,
+            aggregate,
+            values: PhantomData,
+        }
+    }
+
+    fn window(&self, timestamp: u64) -> u64 {
+        timestamp / self.window_size
+    }
+
+    // Oldest window that has not expired at `now`
+    fn oldest_live_window(&self, now: u64) -> u64 {
+        (self.window(now) + 1).saturating_sub(self.num_windows as u64)
+    }
+
+    /// Adds `value` to the aggregate of the window containing `timestamp`
+    pub fn add<K: FasterKey>(
+        &self,
+        store: &FasterKv,
+        key: &K,
+        timestamp: u64,
+        value: &T,
+        monotonic_serial_number: u64,
+    ) -> u8 {
+        let window = self.window(timestamp);
+        let slot = (window % self.num_windows as u64) as usize;
+        store.update_field(
+            key,
+            |ring: &mut WindowRing<A>| {
+                ring.slots.resize(self.num_windows, None);
+                match &mut ring.slots[slot] {
+                    Some((current, aggregate)) if *current == window => {
+                        (self.aggregate)(aggregate, value)
+                    }
+                    // The slot holds a newer window, so this window has been evicted
+                    Some((current, _)) if *current > window => {}
+                    entry => {
+                        let mut aggregate = A::default();
+                        (self.aggregate)(&mut aggregate, value);
+                        *entry = Some((window, aggregate));
+                    }
+                }
+            },
+            monotonic_serial_number,
+        )
+    }
+
+    /// Start timestamps and aggregates of the windows that have not expired at `now`,
+    /// oldest first
+    pub fn windows<K: FasterKey>(
+        &self,
+        store: &FasterKv,
+        key: &K,
+        now: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<(u64, A)>, u8> {
+        let (res, recv) = store.read::<K, WindowRing<A>>(key, monotonic_serial_number);
+        if res == status::PENDING {
+            store.complete_pending(true);
+        }
+        let ring = match res {
+            status::OK | status::PENDING => recv.recv().unwrap_or_default(),
+            status::NOT_FOUND => WindowRing::default(),
+            res => return Err(res),
+        };
+        let oldest = self.oldest_live_window(now);
+        let mut windows: Vec<(u64, A)> = ring
+            .slots
+            .into_iter()
+            .flatten()
+            .filter(|(window, _)| *window >= oldest && *window <= self.window(now))
+            .map(|(window, aggregate)| (window * self.window_size, aggregate))
+            .collect();
+        windows.sort_by_key(|(start, _)| *start);
+        Ok(windows)
+    }
+
+    /// Clears the windows that have expired at `now`, so their aggregates no longer take up
+    /// space in the log
+    pub fn expire<K: FasterKey>(
+        &self,
+        store: &FasterKv,
+        key: &K,
+        now: u64,
+        monotonic_serial_number: u64,
+    ) -> u8 {
+        let oldest = self.oldest_live_window(now);
+        store.update_field(
+            key,
+            |ring: &mut WindowRing<A>| {
+                for entry in ring.slots.iter_mut() {
+                    if let Some((window, _)) = entry {
+                        if *window < oldest {
+                            *entry = None;
+                        }
+                    }
+                }
+            },
+            monotonic_serial_number,
+        )
+    }
+}
@@ -1,8 +1,16 @@
+#![cfg(feature = "typed")]
 extern crate faster_rs;
 
-use faster_rs::{status, FasterKv};
-use std::collections::HashSet;
-use std::sync::mpsc::Receiver;
+use faster_rs::{
+    status, FasterError, FasterKv, FasterKvBuilder, FasterStatus, OpOptions, Priority, ReadHandle,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
 
 #[test]
 fn faster_check() {
@@ -28,7 +36,7 @@ fn faster_read_inserted_value() {
     let upsert = store.upsert(&key, &value, 1);
     assert!((upsert == status::OK || upsert == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::OK);
     assert!(recv.recv().unwrap() == value);
 }
@@ -38,11 +46,74 @@ fn faster_read_missing_value_recv_error() {
     let store = FasterKv::default();
     let key: u64 = 1;
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::NOT_FOUND);
     assert!(recv.recv().is_err());
 }
 
+#[test]
+fn faster_read_errors_distinguish_cause() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    match recv.recv() {
+        Err(FasterError::NotFound) => {}
+        other => panic!("Expected NotFound, got {:?}", other),
+    }
+
+    // A single byte can't be deserialized as a u64
+    store.upsert(&key, &7u8, 1);
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    match recv.recv() {
+        Err(FasterError::Deserialize { bytes, .. }) => assert_eq!(bytes, vec![7]),
+        other => panic!("Expected Deserialize, got {:?}", other),
+    }
+
+    let expired = OpOptions::new().with_deadline(Instant::now());
+    let (_, recv): (u8, ReadHandle<u8>) = store.read_with_options(&key, 1, &expired);
+    match recv.recv() {
+        Err(FasterError::Disconnected) => {}
+        other => panic!("Expected Disconnected, got {:?}", other),
+    }
+}
+
+#[test]
+fn faster_read_raw_repairs_bad_record() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.upsert(&key, &7u8, 1);
+    let (res, recv) = store.read_raw(&key.to_le_bytes(), 1);
+    assert!(res == status::OK);
+    assert_eq!(recv.recv().unwrap(), vec![7]);
+
+    store.upsert(&key, &7u64, 1);
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(recv.recv().unwrap(), 7);
+}
+
+#[test]
+fn faster_try_operations_return_results() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.try_upsert(&key, &1337u64, 1).unwrap();
+    store.try_rmw(&key, &5u64, 1).unwrap();
+    assert_eq!(store.try_read::<u64, u64>(&key, 1).unwrap(), 1342);
+
+    store.try_delete(&key, 1).unwrap();
+    match store.try_read::<u64, u64>(&key, 1) {
+        Err(FasterError::NotFound) => {}
+        other => panic!("Expected NotFound, got {:?}", other),
+    }
+
+    let expired = OpOptions::new().with_deadline(Instant::now());
+    let upsert = store.upsert_with_options(&key, &1u64, 1, &expired);
+    assert_eq!(FasterStatus::from(upsert), FasterStatus::Aborted);
+    assert!(FasterStatus::from(upsert).is_error());
+}
+
 #[test]
 fn faster_rmw_changes_values() {
     let store = FasterKv::default();
@@ -53,14 +124,14 @@ fn faster_rmw_changes_values() {
     let upsert = store.upsert(&key, &value, 1);
     assert!((upsert == status::OK || upsert == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::OK);
     assert!(recv.recv().unwrap() == value);
 
     let rmw = store.rmw(&key, &modification, 1);
     assert!((rmw == status::OK || rmw == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::OK);
     assert!(recv.recv().unwrap() == value + modification);
 }
@@ -74,7 +145,7 @@ fn faster_rmw_without_upsert() {
     let rmw = store.rmw(&key, &modification, 1);
     assert!((rmw == status::OK || rmw == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::OK);
     assert!(recv.recv().unwrap() == modification);
 }
@@ -89,14 +160,14 @@ fn faster_rmw_string() {
     let upsert = store.upsert(&key, &value, 1);
     assert!(upsert == status::OK || upsert == status::PENDING);
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<String>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), value);
 
     let rmw = store.rmw(&key, &modification, 1);
     assert!(rmw == status::OK || rmw == status::PENDING);
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<String>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), String::from("Hello, World!"));
 }
@@ -112,21 +183,21 @@ fn faster_rmw_vec() {
     let upsert = store.upsert(&key, &value, 1);
     assert!(upsert == status::OK || upsert == status::PENDING);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<Vec<i32>>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), value);
 
     let rmw = store.rmw(&key, &modification, 1);
     assert!(rmw == status::OK || rmw == status::PENDING);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<Vec<i32>>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), vec![0, 1, 2, 3, 4, 5]);
 
     let rmw = store.rmw(&key, &modification2, 1);
     assert!(rmw == status::OK || rmw == status::PENDING);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<Vec<i32>>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
 }
@@ -141,7 +212,7 @@ fn faster_rmw_grow_string() {
         store.rmw(&key, &letter, 1);
     }
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<String>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     assert_eq!(recv.recv().unwrap(), final_string);
 }
@@ -158,7 +229,7 @@ fn faster_rmw_hashset() {
         let b: HashSet<i32> = [4, 2, 3, 4, 5].iter().cloned().collect();
         store.rmw(&key, &b, 1);
     }
-    let (res, recv): (u8, Receiver<HashSet<i32>>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<HashSet<i32>>) = store.read(&key, 1);
     assert_eq!(res, status::OK);
     let hash_set = recv.recv().unwrap();
     assert_eq!(hash_set.len(), 5);
@@ -169,6 +240,35 @@ fn faster_rmw_hashset() {
     assert!(hash_set.contains(&5));
 }
 
+#[test]
+fn faster_set_helpers() {
+    let store = FasterKv::default();
+    let key = String::from("set");
+    store.add_to_set(&key, &1, 1);
+    store.add_to_set(&key, &2, 1);
+    store.add_to_set(&key, &2, 1);
+    store.remove_from_set(&key, &1, 1);
+    let (res, recv): (u8, ReadHandle<HashSet<i32>>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    let expected: HashSet<i32> = [2].iter().cloned().collect();
+    assert_eq!(recv.recv().unwrap(), expected);
+}
+
+#[test]
+fn faster_map_helpers() {
+    let store = FasterKv::default();
+    let key = String::from("map");
+    store.map_insert(&key, &1, &String::from("one"), 1);
+    store.map_insert(&key, &2, &String::from("two"), 1);
+    store.map_insert(&key, &1, &String::from("uno"), 1);
+    store.map_remove::<_, _, String>(&key, &2, 1);
+    let (res, recv): (u8, ReadHandle<BTreeMap<i32, String>>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    let map = recv.recv().unwrap();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&1).unwrap(), "uno");
+}
+
 #[test]
 fn faster_delete_inserted_value() {
     let store = FasterKv::default();
@@ -178,14 +278,411 @@ fn faster_delete_inserted_value() {
     let upsert = store.upsert(&key, &value, 1);
     assert!((upsert == status::OK || upsert == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::OK);
     assert!(recv.recv().unwrap() == value);
 
     let delete = store.delete(&key, 1);
     assert!((delete == status::OK || delete == status::PENDING) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
     assert!(res == status::NOT_FOUND);
     assert!(recv.recv().is_err());
 }
+
+#[test]
+fn faster_soft_delete_and_restore() {
+    let store = FasterKv::default();
+    let key = String::from("order-7");
+    let value = vec![1u32, 2, 3];
+
+    assert_eq!(status::NOT_FOUND, store.soft_delete(&key, 1));
+    assert_eq!(status::NOT_FOUND, store.restore(&key, 1));
+
+    store.upsert(&key, &value, 1);
+    assert_eq!(status::OK, store.soft_delete(&key, 2));
+    let (res, _): (u8, ReadHandle<Vec<u32>>) = store.read(&key, 3);
+    assert_eq!(res, status::NOT_FOUND);
+
+    assert_eq!(status::OK, store.restore(&key, 4));
+    let (res, recv): (u8, ReadHandle<Vec<u32>>) = store.read(&key, 5);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), value);
+    assert_eq!(status::NOT_FOUND, store.restore(&key, 6));
+
+    // Purged keys can't be restored
+    store.soft_delete(&key, 7);
+    assert_eq!(status::OK, store.purge(&key, 8));
+    assert_eq!(status::NOT_FOUND, store.restore(&key, 9));
+    let (res, _): (u8, ReadHandle<Vec<u32>>) = store.read(&key, 10);
+    assert_eq!(res, status::NOT_FOUND);
+}
+
+#[test]
+fn faster_upsert_after_delete() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.upsert(&key, &1337u64, 1);
+    let delete = store.delete(&key, 1);
+    assert!(delete == status::OK || delete == status::PENDING);
+
+    let upsert = store.upsert(&key, &42u64, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 42);
+}
+
+#[test]
+fn faster_expired_deadline_aborts() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+    let expired = OpOptions::new().with_deadline(Instant::now());
+
+    let upsert = store.upsert_with_options(&key, &value, 1, &expired);
+    assert_eq!(upsert, status::ABORTED);
+
+    let (res, recv): (u8, ReadHandle<u64>) = store.read_with_options(&key, 1, &expired);
+    assert_eq!(res, status::ABORTED);
+    assert!(recv.recv().is_err());
+
+    let options = OpOptions::new().with_timeout(Duration::from_secs(60));
+    let upsert = store.upsert_with_options(&key, &value, 1, &options);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let (res, recv): (u8, ReadHandle<u64>) = store.read_with_options(&key, 1, &options);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), value);
+}
+
+#[test]
+fn faster_background_operations_are_admitted_when_idle() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .set_background_pending_limit(1)
+        .build()
+        .unwrap();
+    let background = OpOptions::new().with_priority(Priority::Background);
+    assert_eq!(Priority::Foreground, OpOptions::new().priority());
+
+    // Reads served from memory complete right away, so they never hold back later ones
+    for key in 0..10u64 {
+        let upsert = store.upsert_with_options(&key, &(key * 2), key + 1, &background);
+        assert!(upsert == status::OK || upsert == status::PENDING);
+        let (res, recv): (u8, ReadHandle<u64>) =
+            store.read_with_options(&key, key + 1, &OpOptions::new());
+        assert_eq!(res, status::OK);
+        assert_eq!(recv.recv().unwrap(), key * 2);
+        let (res, recv): (u8, ReadHandle<u64>) =
+            store.read_with_options(&key, key + 1, &background);
+        assert_eq!(res, status::OK);
+        assert_eq!(recv.recv().unwrap(), key * 2);
+    }
+
+    let res = store.delete_with_options(&0u64, 11, &background.with_deadline(Instant::now()));
+    assert_eq!(res, status::ABORTED);
+    assert!(FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .set_background_pending_limit(0)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn faster_upsert_batch() {
+    let store = FasterKv::default();
+    let records: Vec<(u64, String)> = (0..3000).map(|i| (i, i.to_string())).collect();
+    store.start_session();
+
+    let statuses = store.upsert_batch(&records, 1);
+    assert_eq!(records.len(), statuses.len());
+    assert!(statuses
+        .iter()
+        .all(|s| *s == status::OK || *s == status::PENDING));
+    for (key, value) in &records {
+        let (res, recv): (u8, ReadHandle<String>) = store.read(key, 3001);
+        assert_eq!(res, status::OK);
+        assert_eq!(&recv.recv().unwrap(), value);
+    }
+    assert!(store.upsert_batch::<u64, u64>(&[], 3001).is_empty());
+    store.stop_session();
+}
+
+#[test]
+fn faster_read_batch() {
+    let store = FasterKv::default();
+    store.start_session();
+    let records: Vec<(u64, u64)> = (0..100)
+        .filter(|i| i % 3 != 0)
+        .map(|i| (i, i * i))
+        .collect();
+    store.upsert_batch(&records, 1);
+
+    let keys: Vec<u64> = (0..100).collect();
+    let values = store.read_batch::<u64, u64>(&keys, 101);
+    assert_eq!(keys.len(), values.len());
+    for (key, value) in keys.iter().zip(values) {
+        let expected = if key % 3 == 0 { None } else { Some(key * key) };
+        assert_eq!(expected, value.unwrap());
+    }
+
+    // Values of the wrong type are reported per key
+    store.upsert(&0u64, &1u8, 102);
+    let values = store.read_batch::<u64, u64>(&[0, 1], 103);
+    assert!(values[0].is_err());
+    assert_eq!(Some(1), *values[1].as_ref().unwrap());
+    store.stop_session();
+}
+
+#[test]
+fn faster_read_coalesced() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    let upsert = store.upsert(&key, &value, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let (res, recv): (u8, ReadHandle<u64>) = store.read_coalesced(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), value);
+
+    let (res, recv): (u8, ReadHandle<u64>) = store.read_coalesced(&2u64, 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn faster_update_field() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    // Missing keys start from the default value
+    let update = store.update_field(&key, |value: &mut Vec<u64>| value.push(1), 1);
+    assert!(update == status::OK || update == status::PENDING);
+    let update = store.update_field(&key, |value: &mut Vec<u64>| value.push(2), 1);
+    assert!(update == status::OK || update == status::PENDING);
+
+    let (res, recv): (u8, ReadHandle<Vec<u64>>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), vec![1, 2]);
+
+    // Only the encoded value is stored for a missing key
+    store.update_field(&2u64, |value: &mut u64| *value += 7, 1);
+    let (res, recv) = store.read_raw(&2u64.to_le_bytes(), 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 7u64.to_le_bytes().to_vec());
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// Minimal executor, parking the current thread until the future is woken
+fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match Pin::new(&mut future).poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn faster_read_async() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    let upsert = store.upsert(&key, &value, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let found = store.read_async::<u64, u64>(&key, 1);
+    let missing = store.read_async::<u64, u64>(&2, 1);
+    store.complete_pending(true);
+    // The futures don't depend on the session, so they can be awaited elsewhere
+    let awaited = thread::spawn(move || (block_on(found), block_on(missing)));
+    let (found, missing) = awaited.join().unwrap();
+    assert_eq!(found.unwrap(), Some(value));
+    assert_eq!(missing.unwrap(), None);
+
+    match block_on(store.read_async::<u64, String>(&key, 1)) {
+        Err(FasterError::Deserialize { .. }) => {}
+        other => panic!("Unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn faster_pod_operations() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let upsert = store.upsert_pod(&key, &1337u64, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+    let rmw = store.rmw_pod(&key, &5u64, 1);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    // Plain old data is stored as bincode would encode it
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 1342);
+    store.upsert(&2u64, &[1u16, 2, 3, 4], 1);
+    let (res, recv) = store.read_pod::<u64, [u16; 4]>(&2, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), [1, 2, 3, 4]);
+
+    let (_, recv) = store.read_pod::<u64, u32>(&key, 1);
+    match recv.recv() {
+        Err(FasterError::InvalidType) => {}
+        other => panic!("Unexpected {:?}", other.map(|_| ())),
+    }
+
+    // Records of another size are replaced by the modification
+    store.upsert(&3u64, &7u8, 1);
+    let rmw = store.rmw_pod(&3u64, &5u64, 1);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+    let (_, recv) = store.read_pod::<u64, u64>(&3, 1);
+    assert_eq!(recv.recv().unwrap(), 5);
+
+    let delete = store.delete_pod(&key, 1);
+    assert!(delete == status::OK || delete == status::PENDING);
+    let (res, recv) = store.read_pod::<u64, u64>(&key, 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn faster_missing_keys_are_not_errors() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    assert!(!store.contains_key(&key, 1).unwrap());
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(recv.recv_optional().unwrap(), None);
+
+    store.upsert(&key, &value, 1);
+    assert!(store.contains_key(&key, 1).unwrap());
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(recv.recv_optional().unwrap(), Some(value));
+
+    // Deleted keys read the same as keys that were never written
+    store.delete(&key, 1);
+    assert!(!store.contains_key(&key, 1).unwrap());
+    let (_, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(recv.recv_optional().unwrap(), None);
+
+    // Values of the wrong type are still errors
+    store.upsert(&key, &value, 1);
+    let (_, recv): (u8, ReadHandle<String>) = store.read(&key, 1);
+    assert!(recv.recv_optional().is_err());
+}
+
+#[test]
+fn faster_rmw_with_closures() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    // Missing keys take the initial value as-is
+    let rmw = store.rmw_with(&key, &vec![1u64], |current: &Vec<u64>| current.clone(), 1);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    let limit = 3;
+    for i in 2..10 {
+        let rmw = store.rmw_with(
+            &key,
+            &Vec::new(),
+            |current: &Vec<u64>| {
+                let mut next = current.clone();
+                if next.len() < limit {
+                    next.push(i);
+                }
+                next
+            },
+            1,
+        );
+        assert!(rmw == status::OK || rmw == status::PENDING);
+    }
+    let rmw = store.rmw_with(
+        &key,
+        &Vec::new(),
+        |current: &Vec<u64>| current[1..].to_vec(),
+        1,
+    );
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    let (res, recv): (u8, ReadHandle<Vec<u64>>) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), vec![2, 3]);
+}
+
+#[test]
+fn faster_rmw_with_stores_only_initial_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.rmw_with(&key, &10u64, |current: &u64| current * 2, 1);
+    let (res, recv) = store.read_raw(&key.to_le_bytes(), 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 10u64.to_le_bytes().to_vec());
+}
+
+#[test]
+fn faster_upsert_if() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &10u64, 1);
+
+    assert!(!store.upsert_if(&key, &11u64, &20u64, 2));
+    assert!(store.upsert_if(&key, &10u64, &20u64, 3));
+    let (res, recv): (u8, ReadHandle<u64>) = store.read(&key, 4);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 20);
+
+    // Missing keys hold the default, and are left missing when the swap fails
+    assert!(!store.upsert_if(&2u64, &5u64, &6u64, 5));
+    let (res, _): (u8, ReadHandle<u64>) = store.read(&2u64, 5);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(store.upsert_if(&3u64, &None, &Some(7u64), 6));
+    let (_, recv): (u8, ReadHandle<Option<u64>>) = store.read(&3u64, 7);
+    assert_eq!(recv.recv().unwrap(), Some(7));
+}
+
+#[test]
+fn faster_admission_control_rejects_background_writes() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_admission_control(16, Duration::from_nanos(0))
+        .build()
+        .unwrap();
+    let background = OpOptions::new().with_priority(Priority::Background);
+    assert!(!store.is_overloaded());
+    assert!(store
+        .try_upsert_with_options(&1u64, &1u64, 1, &background)
+        .is_ok());
+
+    // Any completed foreground read exceeds a latency limit of zero
+    let (_, recv): (u8, ReadHandle<u64>) = store.read_with_options(&1u64, 2, &OpOptions::new());
+    assert_eq!(recv.recv().unwrap(), 1);
+    assert!(store.read_latency() > Duration::from_nanos(0));
+    assert!(store.is_overloaded());
+
+    match store.try_upsert_with_options(&2u64, &2u64, 3, &background) {
+        Err(FasterError::Overloaded) => {}
+        other => panic!("Unexpected {:?}", other),
+    }
+    assert_eq!(
+        store.rmw_with_options(&1u64, &1u64, 4, &background),
+        status::ABORTED
+    );
+    // Foreground writes are still admitted
+    assert!(store
+        .try_upsert_with_options(&2u64, &2u64, 5, &OpOptions::new())
+        .is_ok());
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "capi")]
+extern crate faster_rs;
+
+use faster_rs::capi::*;
+use faster_rs::status;
+use std::ptr;
+
+#[test]
+fn capi_upsert_read_rmw_delete() {
+    unsafe {
+        let store = faster_rs_open(1 << 15, 1024 * 1024 * 1024, ptr::null());
+        assert!(!store.is_null());
+        let session = faster_rs_start_session(store);
+        assert!(!session.is_null());
+
+        let key = b"key";
+        let value = b"hello";
+        let upsert = faster_rs_upsert(
+            store,
+            key.as_ptr(),
+            key.len(),
+            value.as_ptr(),
+            value.len(),
+            1,
+        );
+        assert_eq!(upsert, status::OK);
+        let modification = b" world";
+        let rmw = faster_rs_rmw(
+            store,
+            key.as_ptr(),
+            key.len(),
+            modification.as_ptr(),
+            modification.len(),
+            2,
+        );
+        assert_eq!(rmw, status::OK);
+
+        let mut read: *mut u8 = ptr::null_mut();
+        let mut read_length = 0;
+        let res = faster_rs_read(
+            store,
+            key.as_ptr(),
+            key.len(),
+            3,
+            &mut read,
+            &mut read_length,
+        );
+        assert_eq!(res, status::OK);
+        assert_eq!(
+            std::slice::from_raw_parts(read, read_length),
+            b"hello world"
+        );
+        faster_rs_free_value(read, read_length);
+
+        assert_eq!(
+            faster_rs_delete(store, key.as_ptr(), key.len(), 4),
+            status::OK
+        );
+        let res = faster_rs_read(
+            store,
+            key.as_ptr(),
+            key.len(),
+            5,
+            &mut read,
+            &mut read_length,
+        );
+        assert_eq!(res, status::NOT_FOUND);
+
+        faster_rs_stop_session(store);
+        faster_rs_free_string(session);
+        faster_rs_close(store);
+    }
+}
+
+#[test]
+fn capi_rejects_null_arguments() {
+    unsafe {
+        let store = faster_rs_open(1 << 15, 1024 * 1024 * 1024, ptr::null());
+        let res = faster_rs_upsert(store, ptr::null(), 3, ptr::null(), 0, 1);
+        assert_eq!(res, INVALID_ARGUMENT);
+        let res = faster_rs_upsert(ptr::null(), b"key".as_ptr(), 3, ptr::null(), 0, 1);
+        assert_eq!(res, INVALID_ARGUMENT);
+        faster_rs_close(store);
+    }
+}
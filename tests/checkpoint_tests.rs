@@ -1,7 +1,14 @@
+#![cfg(feature = "typed")]
 extern crate faster_rs;
 extern crate tempfile;
 
-use faster_rs::{FasterError, FasterKv, FasterKvBuilder};
+use faster_rs::{
+    CheckpointKind, CheckpointToken, FasterError, FasterKv, FasterKvBuilder, RecoveredSession,
+    Storage,
+};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 
 #[test]
@@ -119,3 +126,112 @@ fn in_memory_checkpoint_hybrid_log_errors() {
         _ => assert!(false, "Should give InvalidType Error"),
     }
 }
+
+#[test]
+fn in_memory_clean_storage_is_noop() {
+    let store = FasterKv::new_in_memory(1 << 14, 1073741824).unwrap();
+    assert!(store.storage().is_none());
+    assert!(store.clean_storage().is_ok());
+}
+
+#[test]
+fn checkpoint_tempfile_backed_stores() {
+    let first = FasterKv::new_tempfile_backed(1 << 14, 1073741824).unwrap();
+    let second = FasterKv::new_tempfile_backed(1 << 14, 1073741824).unwrap();
+    let value: u64 = 100;
+
+    for key in 0..1000 {
+        first.upsert(&key, &value, key);
+        second.upsert(&key, &value, key);
+    }
+
+    assert!(first.checkpoint().unwrap().checked);
+    assert!(second.checkpoint().unwrap().checked);
+}
+
+#[test]
+fn recover_from_checkpoint_token() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 1073741824)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let value: u64 = 100;
+
+    let session = store.start_session();
+    for key in 1..=1000 {
+        store.upsert(&key, &value, key);
+    }
+    let token = store.checkpoint_token().unwrap();
+    assert_eq!(token, CheckpointToken::full(&token.index));
+    store.stop_session();
+    drop(store);
+
+    let recovered = FasterKvBuilder::new(1 << 14, 1073741824)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let recovery = recovered.recover_from(&token).unwrap();
+    assert_eq!(
+        recovery.sessions,
+        vec![RecoveredSession {
+            guid: session.clone(),
+            serial_num: 1000,
+        }]
+    );
+
+    // The session can still be resumed after its serial number was looked up
+    assert_eq!(recovered.continue_session(session), 1000);
+    let (status, recv) = recovered.read::<u64, u64>(&1000, 1001);
+    assert!(status == faster_rs::status::OK || status == faster_rs::status::PENDING);
+    recovered.complete_pending(true);
+    assert_eq!(recv.recv().unwrap(), value);
+    recovered.stop_session();
+}
+
+#[test]
+fn auto_checkpoint_records_latest_token() {
+    let storage = Storage::temporary().unwrap();
+    let store = Arc::new(
+        FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+            .with_storage(storage.clone())
+            .build()
+            .unwrap(),
+    );
+    let checkpoints = store
+        .enable_auto_checkpoint(Duration::from_millis(20), CheckpointKind::Full)
+        .unwrap();
+    store.start_session();
+    store.upsert(&1u64, &42u64, 1);
+
+    // Checkpoints progress as the session refreshes
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let token = loop {
+        store.refresh();
+        store.complete_pending(false);
+        if let Some(token) = checkpoints.latest_token() {
+            break token;
+        }
+        assert!(Instant::now() < deadline, "No checkpoint was taken");
+        thread::sleep(Duration::from_millis(5));
+    };
+    assert_eq!(token.index, token.hybrid_log);
+    store.complete_pending(true);
+    store.stop_session();
+    checkpoints.stop();
+    drop(store);
+
+    let recovered = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_storage(storage)
+        .build()
+        .unwrap();
+    assert!(recovered.recover_from(&token).is_ok());
+}
+
+#[test]
+fn auto_checkpoint_requires_storage() {
+    let store = Arc::new(FasterKv::default());
+    let res = store.enable_auto_checkpoint(Duration::from_secs(1), CheckpointKind::HybridLog);
+    assert!(res.is_err());
+}
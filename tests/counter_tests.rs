@@ -0,0 +1,40 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{CounterStore, FasterKv};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+#[test]
+fn counters_incr_decr_reset() {
+    let counters = CounterStore::new(FasterKv::default());
+    let key: u64 = 1;
+
+    assert_eq!(counters.get(&key, 1).unwrap(), 0);
+    counters.incr(&key, 5, 1);
+    counters.incr(&key, 2, 1);
+    assert_eq!(counters.get(&key, 1).unwrap(), 7);
+
+    counters.decr(&key, 3, 1);
+    assert_eq!(counters.get(&key, 1).unwrap(), 4);
+    counters.decr(&key, 10, 1);
+    assert_eq!(counters.get(&key, 1).unwrap(), 0);
+
+    counters.incr(&key, 8, 1);
+    counters.reset(&key, 1);
+    assert_eq!(counters.get(&key, 1).unwrap(), 0);
+}
+
+#[test]
+fn counters_dump_changed_keys() {
+    let (sender, receiver) = channel();
+    let counters = CounterStore::with_dump(
+        FasterKv::default(),
+        Duration::from_millis(10),
+        move |key: &u64, count| sender.send((*key, count)).unwrap(),
+    );
+    counters.incr(&1, 3, 1);
+
+    let dumped = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(dumped, (1, 3));
+}
@@ -0,0 +1,246 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+extern crate serde_derive;
+
+// The examples as tests, on stores small enough to run anywhere
+
+use faster_rs::{status, FasterKv, FasterKvBuilder, ReadHandle, Storage, LOG_PAGE_SIZE};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+const TABLE_SIZE: u64 = 1 << 12;
+const LOG_SIZE: u64 = 3 * LOG_PAGE_SIZE;
+const NUM_OPS: u64 = 1 << 14;
+const NUM_UNIQUE_KEYS: u64 = 1 << 10;
+const REFRESH_INTERVAL: u64 = 1 << 8;
+
+fn small_store(storage: Storage) -> FasterKv {
+    FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+        .with_storage(storage)
+        .build()
+        .unwrap()
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct MyKey {
+    foo: String,
+    bar: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct MyValue {
+    foo: String,
+    bar: String,
+}
+
+#[test]
+fn example_basic() {
+    let store = small_store(Storage::temporary().unwrap());
+    let key: u64 = 1;
+    let value: u64 = 1000;
+
+    let upsert = store.upsert(&key, &value, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let rmw = store.rmw(&key, &5u64, 2);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    let (read, recv): (u8, ReadHandle<u64>) = store.read(&key, 3);
+    assert_eq!(read, status::OK);
+    assert_eq!(recv.recv().unwrap(), value + 5);
+}
+
+#[test]
+fn example_custom_keys() {
+    let store = small_store(Storage::temporary().unwrap());
+    let key = MyKey {
+        foo: String::from("Hello"),
+        bar: String::from("World"),
+    };
+
+    let upsert = store.upsert(&key, &1u64, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+    assert!(store.size() > 0);
+
+    let (read, recv): (u8, ReadHandle<u64>) = store.read(&key, 1);
+    assert_eq!(read, status::OK);
+    assert_eq!(recv.recv().unwrap(), 1);
+}
+
+#[test]
+fn example_custom_values() {
+    let store = small_store(Storage::temporary().unwrap());
+    let value = MyValue {
+        foo: String::from("Hello"),
+        bar: String::from("World"),
+    };
+
+    let upsert = store.upsert(&1u64, &value, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let (read, recv): (u8, ReadHandle<MyValue>) = store.read(&1u64, 1);
+    assert_eq!(read, status::OK);
+    assert_eq!(recv.recv().unwrap(), value);
+}
+
+// Counts of each key after the operations up to and including `serial_num`
+fn expected_sums(serial_num: u64) -> Vec<u64> {
+    let mut expected = vec![0; NUM_UNIQUE_KEYS as usize];
+    for i in 0..=serial_num {
+        expected[(i % NUM_UNIQUE_KEYS) as usize] += 1;
+    }
+    expected
+}
+
+#[test]
+fn example_sum_store_single() {
+    let storage = Storage::temporary().unwrap();
+    let store = small_store(storage.clone());
+    let session = store.start_session();
+    for i in 0..NUM_OPS {
+        store.rmw(&(i % NUM_UNIQUE_KEYS), &1u64, i);
+        if i % REFRESH_INTERVAL == 0 {
+            store.refresh();
+        }
+    }
+    store.complete_pending(true);
+    let token = store.checkpoint_token().unwrap();
+    store.complete_pending(true);
+    store.stop_session();
+    drop(store);
+
+    let recovered = small_store(storage);
+    recovered.recover_from(&token).unwrap();
+    let serial_num = recovered.continue_session(session);
+    let expected = expected_sums(serial_num);
+    for key in 0..NUM_UNIQUE_KEYS {
+        let (read, recv): (u8, ReadHandle<u64>) = recovered.read(&key, serial_num + 1);
+        if read == status::PENDING {
+            recovered.complete_pending(true);
+        }
+        assert_eq!(recv.recv().unwrap(), expected[key as usize]);
+    }
+    recovered.stop_session();
+}
+
+#[test]
+fn example_sum_store_concurrent() {
+    let num_threads = 4;
+    let storage = Storage::temporary().unwrap();
+    let store = Arc::new(small_store(storage.clone()));
+    let threads: Vec<_> = (0..num_threads)
+        .map(|_| {
+            let store = Arc::clone(&store);
+            thread::spawn(move || {
+                let session = store.session();
+                for i in 0..NUM_OPS {
+                    session.rmw(&(i % NUM_UNIQUE_KEYS), &1u64, i);
+                    if i % REFRESH_INTERVAL == 0 {
+                        session.refresh();
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let session = store.start_session();
+    let token = store.checkpoint_token().unwrap();
+    store.complete_pending(true);
+    store.stop_session();
+    drop(store);
+
+    let recovered = small_store(storage);
+    recovered.recover_from(&token).unwrap();
+    recovered.continue_session(session);
+    let expected = num_threads * NUM_OPS / NUM_UNIQUE_KEYS;
+    for key in 0..NUM_UNIQUE_KEYS {
+        let (read, recv): (u8, ReadHandle<u64>) = recovered.read(&key, 1);
+        if read == status::PENDING {
+            recovered.complete_pending(true);
+        }
+        assert_eq!(recv.recv().unwrap(), expected);
+    }
+    recovered.stop_session();
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match Pin::new(&mut future).poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn example_async_reads() {
+    let store = small_store(Storage::temporary().unwrap());
+    let session = store.session();
+    for key in 0..NUM_UNIQUE_KEYS {
+        session.upsert(&key, &(key * 2), key + 1);
+    }
+
+    let reads: Vec<_> = (0..NUM_UNIQUE_KEYS)
+        .map(|key| session.read_async::<u64, u64>(&key, NUM_UNIQUE_KEYS + 1))
+        .collect();
+    session.complete_pending(true);
+    for (key, read) in reads.into_iter().enumerate() {
+        assert_eq!(Some(key as u64 * 2), block_on(read).unwrap());
+    }
+    assert_eq!(
+        None,
+        block_on(session.read_async::<u64, u64>(&NUM_UNIQUE_KEYS, 1)).unwrap()
+    );
+}
+
+// Routes every key to one of several stores by its hash
+struct ShardedStore {
+    shards: Vec<FasterKv>,
+}
+
+impl ShardedStore {
+    fn shard<K: Hash>(&self, key: &K) -> &FasterKv {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+}
+
+#[test]
+fn example_sharding() {
+    let sharded = ShardedStore {
+        shards: (0..4)
+            .map(|_| FasterKv::new_in_memory(TABLE_SIZE, LOG_SIZE).unwrap())
+            .collect(),
+    };
+    for key in 0..NUM_UNIQUE_KEYS {
+        sharded.shard(&key).upsert(&key, &key, key + 1);
+    }
+
+    for key in 0..NUM_UNIQUE_KEYS {
+        let (read, recv): (u8, ReadHandle<u64>) = sharded.shard(&key).read(&key, 1);
+        assert_eq!(read, status::OK);
+        assert_eq!(recv.recv().unwrap(), key);
+    }
+    // Every shard holds some of the keys
+    assert!(sharded.shards.iter().all(|shard| shard.size() > 0));
+}
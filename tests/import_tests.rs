@@ -0,0 +1,53 @@
+#![cfg(feature = "import")]
+extern crate faster_rs;
+extern crate serde_derive;
+
+use faster_rs::{status, FasterKv, Importer, ReadHandle};
+use serde_derive::Deserialize;
+use std::io::Cursor;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+struct Row {
+    id: u64,
+    name: String,
+}
+
+#[test]
+fn import_ndjson() {
+    let store = Arc::new(FasterKv::default());
+    let input = "{\"id\": 1, \"name\": \"one\"}\n\n{\"id\": 2, \"name\": \"two\"}\nnot json\n";
+
+    let stats = Importer::new(2)
+        .ndjson(&store, Cursor::new(input), |row: Row| {
+            Some((row.id, row.name))
+        })
+        .unwrap();
+    assert_eq!(stats.imported, 2);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(stats.failed, 0);
+    assert_eq!(stats.first_failure, None);
+
+    let (res, recv): (u8, ReadHandle<String>) = store.read(&2u64, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), "two");
+}
+
+#[test]
+fn import_csv() {
+    let store = Arc::new(FasterKv::default());
+    let input = "id,name\n1,one\n2,two\nthree,3\n";
+
+    let stats = Importer::new(2)
+        .with_chunk_size(1)
+        .csv(&store, Cursor::new(input), b',', true, |row: Row| {
+            Some((row.id, row.name))
+        })
+        .unwrap();
+    assert_eq!(stats.imported, 2);
+    assert_eq!(stats.skipped, 1);
+
+    let (res, recv): (u8, ReadHandle<String>) = store.read(&1u64, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), "one");
+}
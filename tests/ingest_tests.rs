@@ -0,0 +1,107 @@
+#![cfg(feature = "ingest")]
+extern crate faster_rs;
+
+use faster_rs::{status, ExactlyOnce, FasterKv, FasterKvBuilder, Source, Storage, Writes};
+
+const TABLE_SIZE: u64 = 1 << 14;
+const LOG_SIZE: u64 = 1024 * 1024 * 1024;
+
+// Partition whose record at every offset is the offset itself. `redeliver` makes the first
+// poll after a seek return the record before the requested offset again.
+struct Partition {
+    position: u64,
+    available: u64,
+    redeliver: bool,
+}
+
+impl Partition {
+    fn new(available: u64) -> Partition {
+        Partition {
+            position: 0,
+            available,
+            redeliver: false,
+        }
+    }
+}
+
+impl Source for Partition {
+    type Record = u64;
+
+    fn seek(&mut self, offset: u64) {
+        self.position = if self.redeliver && offset > 0 {
+            offset - 1
+        } else {
+            offset
+        };
+    }
+
+    fn poll(&mut self) -> Option<(u64, u64)> {
+        if self.position >= self.available {
+            return None;
+        }
+        self.position += 1;
+        Some((self.position - 1, self.position - 1))
+    }
+}
+
+fn sum(writes: &mut Writes, record: u64) {
+    writes.rmw(&"sum".to_owned(), &record);
+    writes.rmw(&"records".to_owned(), &1u64);
+}
+
+fn read_u64(store: &FasterKv, key: &str) -> u64 {
+    let session = store.session();
+    let (res, recv) = session.read::<String, u64>(&key.to_owned(), 1);
+    if res == status::PENDING {
+        session.complete_pending(true);
+    }
+    recv.recv().unwrap()
+}
+
+#[test]
+fn ingestion_resumes_after_checkpoint() {
+    let storage = Storage::temporary().unwrap();
+    let open = || {
+        FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+            .with_storage(storage.clone())
+            .build()
+            .unwrap()
+    };
+    let mut partition = Partition::new(100);
+
+    let token = {
+        let store = open();
+        let mut ingestion = ExactlyOnce::start(&store, "sum", 0);
+        assert_eq!(100, ingestion.process(&mut partition, sum));
+        let token = ingestion.checkpoint().unwrap();
+        partition.available = 150;
+        assert_eq!(50, ingestion.process(&mut partition, sum));
+        token
+    };
+
+    let store = open();
+    let mut ingestion = ExactlyOnce::recover(&store, "sum", &token).unwrap();
+    assert_eq!(100, ingestion.next_offset());
+    partition.available = 200;
+    assert_eq!(100, ingestion.process(&mut partition, sum));
+    drop(ingestion);
+
+    assert_eq!(200, read_u64(&store, "records"));
+    assert_eq!((0..200).sum::<u64>(), read_u64(&store, "sum"));
+}
+
+#[test]
+fn ingestion_skips_redelivered_records() {
+    let store = FasterKv::default();
+    let mut partition = Partition::new(10);
+    partition.redeliver = true;
+
+    let mut ingestion = ExactlyOnce::start(&store, "sum", 0);
+    assert_eq!(10, ingestion.process(&mut partition, sum));
+    partition.available = 20;
+    assert_eq!(10, ingestion.process(&mut partition, sum));
+    assert_eq!(20, ingestion.next_offset());
+    drop(ingestion);
+
+    assert_eq!(20, read_u64(&store, "records"));
+}
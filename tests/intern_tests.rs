@@ -0,0 +1,79 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{status, FasterKv, KeyInterner};
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn intern_assigns_stable_ids() {
+    let store = FasterKv::default();
+    let interner = KeyInterner::new();
+
+    let a = interner.intern(&store, "https://example.com/a", 1).unwrap();
+    let b = interner.intern(&store, "https://example.com/b", 1).unwrap();
+    assert_ne!(a, b);
+    assert_eq!(
+        interner.intern(&store, "https://example.com/a", 1).unwrap(),
+        a
+    );
+    assert_eq!(
+        interner.resolve(&store, b, 1).unwrap().unwrap(),
+        "https://example.com/b"
+    );
+    assert_eq!(
+        interner.lookup(&store, "https://example.com/c", 1).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn intern_translates_operations() {
+    let store = FasterKv::default();
+    let interner = KeyInterner::new();
+    let key = "tenant-1/counter";
+
+    interner.upsert(&store, key, &10u64, 1).unwrap();
+    interner.rmw(&store, key, &5u64, 1).unwrap();
+    let (res, recv) = interner.read::<u64>(&store, key, 1).unwrap();
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 15);
+
+    interner.delete(&store, key, 1).unwrap();
+    let (res, recv) = interner.read::<u64>(&store, key, 1).unwrap();
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+
+    let (res, _) = interner.read::<u64>(&store, "never-written", 1).unwrap();
+    assert_eq!(res, status::NOT_FOUND);
+}
+
+#[test]
+fn intern_recovered_interner_reuses_dictionary() {
+    let store = FasterKv::default();
+    let interner = KeyInterner::new();
+    let id = interner.intern(&store, "first", 1).unwrap();
+
+    let recovered = KeyInterner::recover(&store, 1).unwrap();
+    assert_eq!(recovered.intern(&store, "first", 1).unwrap(), id);
+    assert_ne!(recovered.intern(&store, "second", 1).unwrap(), id);
+}
+
+#[test]
+fn intern_concurrently_agrees_on_id() {
+    let store = Arc::new(FasterKv::default());
+    let interner = Arc::new(KeyInterner::new());
+    let mut threads = vec![];
+    for _ in 0..4 {
+        let store = Arc::clone(&store);
+        let interner = Arc::clone(&interner);
+        threads.push(thread::spawn(move || {
+            let _session = store.start_session();
+            let id = interner.intern(&store, "shared", 1).unwrap();
+            store.stop_session();
+            id
+        }));
+    }
+    let ids: Vec<u64> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    assert!(ids.iter().all(|id| *id == ids[0]));
+}
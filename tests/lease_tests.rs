@@ -0,0 +1,34 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::FasterKv;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn lease_excludes_other_owners() {
+    let store = FasterKv::default();
+    let key = String::from("lock");
+    let ttl = Duration::from_secs(60);
+
+    assert!(store.acquire_lease(&key, 1, ttl, 1));
+    assert!(store.acquire_lease(&key, 1, ttl, 1));
+    assert!(!store.acquire_lease(&key, 2, ttl, 1));
+    assert!(!store.renew_lease(&key, 2, ttl, 1));
+    assert!(!store.release_lease(&key, 2, 1));
+    assert!(store.renew_lease(&key, 1, ttl, 1));
+    assert!(store.release_lease(&key, 1, 1));
+    assert!(!store.release_lease(&key, 1, 1));
+    assert!(store.acquire_lease(&key, 2, ttl, 1));
+}
+
+#[test]
+fn lease_expires_after_ttl() {
+    let store = FasterKv::default();
+    let key = String::from("lock");
+
+    assert!(store.acquire_lease(&key, 1, Duration::from_millis(20), 1));
+    thread::sleep(Duration::from_millis(50));
+    assert!(!store.renew_lease(&key, 1, Duration::from_secs(60), 1));
+    assert!(store.acquire_lease(&key, 2, Duration::from_secs(60), 1));
+}
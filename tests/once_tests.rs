@@ -0,0 +1,70 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::FasterKv;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn once_records_first_result() {
+    let store = FasterKv::default();
+    let key = String::from("request-1");
+    let ttl = Duration::from_secs(60);
+    let runs = AtomicUsize::new(0);
+    let compute = |result: u64| {
+        runs.fetch_add(1, Ordering::SeqCst);
+        result
+    };
+
+    assert_eq!(1, store.once(&key, ttl, || compute(1), 1).unwrap());
+    assert_eq!(1, store.once(&key, ttl, || compute(2), 1).unwrap());
+    assert_eq!(1, runs.load(Ordering::SeqCst));
+    assert_eq!(
+        3,
+        store
+            .once(&String::from("request-2"), ttl, || compute(3), 1)
+            .unwrap()
+    );
+}
+
+#[test]
+fn once_runs_again_after_ttl() {
+    let store = FasterKv::default();
+    let key = String::from("request");
+
+    assert_eq!(
+        1,
+        store
+            .once(&key, Duration::from_millis(20), || 1u64, 1)
+            .unwrap()
+    );
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(
+        2,
+        store
+            .once(&key, Duration::from_secs(60), || 2u64, 1)
+            .unwrap()
+    );
+}
+
+#[test]
+fn concurrent_once_agrees_on_result() {
+    let store = Arc::new(FasterKv::default());
+    let threads: Vec<_> = (0..4u64)
+        .map(|i| {
+            let store = Arc::clone(&store);
+            thread::spawn(move || {
+                store.start_session();
+                let result = store
+                    .once(&String::from("request"), Duration::from_secs(60), || i, 1)
+                    .unwrap();
+                store.stop_session();
+                result
+            })
+        })
+        .collect();
+    let results: Vec<u64> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    assert!(results.iter().all(|&result| result == results[0]));
+}
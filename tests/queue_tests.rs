@@ -0,0 +1,60 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, FasterQueue};
+
+#[test]
+fn queue_cursors_are_independent() {
+    let store = FasterKv::default();
+    let queue = FasterQueue::open(&store, "events", 1).unwrap();
+
+    for value in 0..5u64 {
+        assert_eq!(value + 1, queue.enqueue(&store, &value, 1));
+    }
+    assert_eq!(
+        queue.dequeue_from(&store, "a", 3, 1).unwrap(),
+        vec![(1, 0), (2, 1), (3, 2)]
+    );
+    assert_eq!(queue.dequeue_from(&store, "b", 1, 1).unwrap(), vec![(1, 0)]);
+    assert_eq!(
+        queue.dequeue_from(&store, "a", 10, 1).unwrap(),
+        vec![(4, 3), (5, 4)]
+    );
+    assert!(queue.dequeue_from(&store, "a", 10, 1).unwrap().is_empty());
+}
+
+#[test]
+fn queue_reopen_continues_after_last_entry() {
+    let store = FasterKv::default();
+    {
+        let queue = FasterQueue::open(&store, "events", 1).unwrap();
+        queue.enqueue(&store, &String::from("first"), 1);
+        queue.dequeue_from(&store, "consumer", 10, 1).unwrap();
+    }
+    let queue = FasterQueue::open(&store, "events", 1).unwrap();
+    assert_eq!(2, queue.enqueue(&store, &String::from("second"), 1));
+    assert_eq!(
+        queue.dequeue_from(&store, "consumer", 10, 1).unwrap(),
+        vec![(2, String::from("second"))]
+    );
+}
+
+#[test]
+fn queue_trim_skips_deleted_entries() {
+    let store = FasterKv::default();
+    let queue = FasterQueue::open(&store, "events", 1).unwrap();
+
+    for value in 0..4u64 {
+        queue.enqueue(&store, &value, 1);
+    }
+    queue.dequeue_from(&store, "slow", 1, 1).unwrap();
+    queue.trim(&store, 3, 1).unwrap();
+    assert_eq!(
+        queue.dequeue_from(&store, "slow", 10, 1).unwrap(),
+        vec![(3, 2), (4, 3)]
+    );
+    assert_eq!(
+        queue.dequeue_from(&store, "new", 1, 1).unwrap(),
+        vec![(3, 2)]
+    );
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, FasterRateLimiter};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn rate_limiter_limits_per_key() {
+    let store = FasterKv::default();
+    let limiter = FasterRateLimiter::new(3, 0.0);
+    let a = String::from("a");
+    let b = String::from("b");
+
+    assert!(limiter.try_acquire(&store, &a, 2, 1));
+    assert!(!limiter.try_acquire(&store, &a, 2, 1));
+    assert!(limiter.try_acquire(&store, &a, 1, 1));
+    assert!(!limiter.try_acquire(&store, &a, 1, 1));
+    assert!(limiter.try_acquire(&store, &b, 3, 1));
+}
+
+#[test]
+fn rate_limiter_refills_over_time() {
+    let store = FasterKv::default();
+    let limiter = FasterRateLimiter::new(1, 100.0);
+    let key = String::from("key");
+
+    assert!(limiter.try_acquire(&store, &key, 1, 1));
+    assert!(!limiter.try_acquire(&store, &key, 1, 1));
+    thread::sleep(Duration::from_millis(50));
+    assert!(limiter.try_acquire(&store, &key, 1, 1));
+}
+
+#[test]
+fn rate_limiter_concurrent_acquires_stay_within_capacity() {
+    let store = Arc::new(FasterKv::default());
+    let limiter = Arc::new(FasterRateLimiter::new(1000, 0.0));
+    let mut threads = vec![];
+    for _ in 0..4 {
+        let store = Arc::clone(&store);
+        let limiter = Arc::clone(&limiter);
+        threads.push(thread::spawn(move || {
+            let _session = store.start_session();
+            let mut acquired = 0;
+            for i in 0..500 {
+                if limiter.try_acquire(&store, &0u64, 1, i) {
+                    acquired += 1;
+                }
+            }
+            store.stop_session();
+            acquired
+        }));
+    }
+    let acquired: u64 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+    assert!(acquired <= 1000);
+}
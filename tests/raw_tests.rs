@@ -0,0 +1,44 @@
+extern crate faster_rs;
+
+use faster_rs::{status, FasterKv};
+
+#[test]
+fn faster_raw_upsert_read_delete() {
+    let store = FasterKv::default();
+    let key = b"raw";
+    let value = [1, 2, 3, 4];
+
+    let upsert = store.upsert_raw(key, &value, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+
+    let (res, recv) = store.read_raw(key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), value.to_vec());
+
+    assert_eq!(store.delete_raw(key, 1), status::OK);
+    let (res, recv) = store.read_raw(key, 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn faster_raw_rmw_appends() {
+    let store = FasterKv::default();
+    let key = b"log";
+
+    // The first modification becomes the initial value
+    store.rmw_raw(key, b"a", 1);
+    store.rmw_raw(key, b"bc", 1);
+
+    let (res, recv) = store.read_raw(key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), b"abc".to_vec());
+}
+
+#[test]
+fn faster_raw_read_missing_key() {
+    let store = FasterKv::default();
+    let (res, recv) = store.read_raw(b"missing", 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+}
@@ -0,0 +1,199 @@
+extern crate faster_rs;
+
+use faster_rs::{status, FasterKv, FasterKvBuilder, RefreshSchedule, Storage};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn active_sessions_tracks_started_sessions() {
+    let store = Arc::new(FasterKv::default());
+    assert!(store.active_sessions().is_empty());
+
+    let guid = store.start_session();
+    let leaked = {
+        let store = Arc::clone(&store);
+        thread::spawn(move || store.start_session()).join().unwrap()
+    };
+
+    let active = store.active_sessions();
+    assert_eq!(2, active.len());
+    assert_eq!(guid, active[0].guid);
+    assert_eq!(leaked, active[1].guid);
+    assert!(active[0].backtrace.is_none());
+
+    store.stop_session();
+    let active = store.active_sessions();
+    assert_eq!(1, active.len());
+    assert_eq!(leaked, active[0].guid);
+}
+
+#[test]
+fn active_sessions_records_backtraces() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .set_record_session_backtraces(true)
+        .build()
+        .unwrap();
+    store.start_session();
+    let active = store.active_sessions();
+    assert_eq!(1, active.len());
+    assert!(active[0].backtrace.is_some());
+    store.stop_session();
+    assert!(store.active_sessions().is_empty());
+}
+
+#[test]
+fn stall_watchdog_reports_sessions_without_refresh() {
+    let (sender, receiver) = channel();
+    let sender = Mutex::new(sender);
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_stall_watchdog(Duration::from_millis(50), move |stalled| {
+            let guids: Vec<String> = stalled.iter().map(|s| s.guid.clone()).collect();
+            sender.lock().unwrap().send(guids).unwrap();
+        })
+        .build()
+        .unwrap();
+
+    let guid = store.start_session();
+    for _ in 0..10 {
+        thread::sleep(Duration::from_millis(10));
+        store.refresh();
+    }
+    assert!(receiver.try_recv().is_err());
+
+    let stalled = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(vec![guid], stalled);
+    store.stop_session();
+}
+
+#[test]
+fn session_is_stopped_when_dropped() {
+    let store = FasterKv::default();
+    let session = store.session();
+    assert_eq!(session.guid(), store.active_sessions()[0].guid);
+    session.read_raw(b"key", 1);
+    session.refresh();
+    drop(session);
+    assert!(store.active_sessions().is_empty());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn stopping_session_of_another_thread_panics() {
+    let store = Arc::new(FasterKv::default());
+    store.start_session();
+    let stopped = {
+        let store = Arc::clone(&store);
+        thread::spawn(move || store.stop_session()).join()
+    };
+    assert!(stopped.is_err());
+    store.stop_session();
+}
+
+#[test]
+fn refresh_schedules_are_staggered() {
+    let first_refresh = |schedule: &mut RefreshSchedule| (1..=64).find(|_| schedule.tick());
+    let mut a = RefreshSchedule::new(64);
+    let mut b = RefreshSchedule::new(64);
+    let a_offset = first_refresh(&mut a).unwrap();
+    let b_offset = first_refresh(&mut b).unwrap();
+    assert_ne!(a_offset, b_offset);
+
+    // After the first refresh, every schedule refreshes once per interval
+    for _ in 0..10 {
+        assert_eq!(Some(64), first_refresh(&mut a));
+    }
+    assert_eq!(11, a.refreshes());
+
+    let mut every_op = RefreshSchedule::new(1);
+    assert!((0..10).all(|_| every_op.tick()));
+}
+
+#[test]
+#[cfg(feature = "typed")]
+fn session_arena_holds_values_until_reset() {
+    let store = FasterKv::default();
+    let mut session = store.session();
+    for i in 0..100u64 {
+        session.upsert(&i, &[i as u8; 32], i + 1);
+    }
+
+    {
+        let values: Vec<&[u8]> = (0..100u64)
+            .map(|i| {
+                session
+                    .read_raw_in_arena(&i.to_le_bytes(), 101)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect();
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(&[i as u8; 32][..], *value);
+        }
+        assert!(session
+            .read_raw_in_arena(b"missing", 101)
+            .unwrap()
+            .is_none());
+    }
+    assert_eq!(100 * 32, session.arena_bytes());
+
+    session.reset_arena();
+    assert_eq!(0, session.arena_bytes());
+    let value = session.read_raw_in_arena(&7u64.to_le_bytes(), 102).unwrap();
+    assert_eq!(Some(&[7u8; 32][..]), value);
+}
+
+#[test]
+fn resumed_session_continues_after_last_persisted_operation() {
+    let storage = Storage::temporary().unwrap();
+    let open = || {
+        FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+            .with_storage(storage.clone())
+            .build()
+            .unwrap()
+    };
+    let store = open();
+    let session = store.session();
+    for serial in 1..=100u64 {
+        session.upsert_raw(&serial.to_le_bytes(), b"persisted", serial);
+    }
+    let guid = session.guid().to_owned();
+    let token = session.checkpoint_token().unwrap();
+    drop(session);
+    drop(store);
+
+    let recovered = open();
+    recovered.recover_from(&token).unwrap();
+    {
+        let (session, serial_num) = recovered.resume_session(guid.clone());
+        assert_eq!(serial_num, 100);
+        assert_eq!(session.guid(), guid);
+        assert_eq!(recovered.active_sessions()[0].guid, guid);
+        let (_, recv) = session.read_raw(&serial_num.to_le_bytes(), serial_num + 1);
+        assert_eq!(recv.recv().unwrap(), b"persisted".to_vec());
+    }
+    assert!(recovered.active_sessions().is_empty());
+}
+
+#[test]
+fn handle_numbers_operations_and_refreshes_session() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_session_maintenance(8, 32)
+        .build()
+        .unwrap();
+    {
+        let mut handle = store.handle();
+        for i in 0..100u64 {
+            handle.upsert_raw(&i.to_le_bytes(), b"value");
+        }
+        assert_eq!(100, handle.serial());
+        assert_eq!(1, store.active_sessions().len());
+
+        let (res, recv) = handle.read_raw(&7u64.to_le_bytes());
+        assert_eq!(status::OK, res);
+        assert_eq!(b"value".to_vec(), recv.recv().unwrap());
+        assert_eq!(101, handle.serial());
+    }
+    assert!(store.active_sessions().is_empty());
+}
@@ -0,0 +1,126 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{DiskUsage, FasterError, FasterKv, FasterKvBuilder, Storage};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn temporary_storage_is_unique() {
+    let first = Storage::temporary().unwrap();
+    let second = Storage::temporary().unwrap();
+    assert_ne!(first.path(), second.path());
+    assert!(first.is_temporary());
+}
+
+#[test]
+fn temporary_storage_removed_after_last_handle() {
+    let storage = Storage::temporary().unwrap();
+    let path = storage.path().to_owned();
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_storage(storage.clone())
+        .build()
+        .unwrap();
+    drop(storage);
+    assert!(Path::new(&path).exists());
+    drop(store);
+    assert!(!Path::new(&path).exists());
+}
+
+#[test]
+fn tempfile_backed_store_removes_storage() {
+    let store = FasterKv::new_tempfile_backed(1 << 15, 1024 * 1024 * 1024).unwrap();
+    let path = store.storage().unwrap().path().to_owned();
+    assert!(Path::new(&path).exists());
+    drop(store);
+    assert!(!Path::new(&path).exists());
+}
+
+#[test]
+fn unique_storage_is_kept() {
+    let tmp_dir = TempDir::new().unwrap();
+    let parent = tmp_dir.path().to_str().unwrap();
+    let first = Storage::unique_in(parent).unwrap();
+    let second = Storage::unique_in(parent).unwrap();
+    assert_ne!(first.path(), second.path());
+
+    let path = first.path().to_owned();
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_storage(first)
+        .build()
+        .unwrap();
+    drop(store);
+    assert!(Path::new(&path).exists());
+}
+
+// Lays out a storage directory like FASTER does, with an index checkpoint that completed
+// and a log checkpoint that did not
+fn write_storage_layout(path: &Path) {
+    fs::write(path.join("log.log.0"), vec![0; 100]).unwrap();
+    let index_checkpoint = path.join("index-checkpoints").join("token");
+    fs::create_dir_all(&index_checkpoint).unwrap();
+    fs::write(index_checkpoint.join("info.dat"), vec![0; 10]).unwrap();
+    fs::write(index_checkpoint.join("ht.dat"), vec![0; 20]).unwrap();
+    let log_checkpoint = path.join("cpr-checkpoints").join("crashed");
+    fs::create_dir_all(&log_checkpoint).unwrap();
+    fs::write(log_checkpoint.join("snapshot.dat"), vec![0; 40]).unwrap();
+    fs::write(path.join("notes.txt"), vec![0; 5]).unwrap();
+}
+
+#[test]
+fn storage_disk_usage_by_component() {
+    let tmp_dir = TempDir::new().unwrap();
+    write_storage_layout(tmp_dir.path());
+    let storage = Storage::at(tmp_dir.path().to_str().unwrap());
+
+    let usage = storage.disk_usage().unwrap();
+    assert_eq!(
+        usage,
+        DiskUsage {
+            log_segments: 100,
+            index_checkpoints: 30,
+            log_checkpoints: 0,
+            orphaned: 40,
+            other: 5,
+        }
+    );
+    assert_eq!(usage.total(), 175);
+}
+
+#[test]
+fn storage_removes_orphaned_checkpoints() {
+    let tmp_dir = TempDir::new().unwrap();
+    write_storage_layout(tmp_dir.path());
+    let storage = Storage::at(tmp_dir.path().to_str().unwrap());
+
+    assert_eq!(
+        storage.orphaned_checkpoints().unwrap(),
+        vec![tmp_dir.path().join("cpr-checkpoints").join("crashed")]
+    );
+    assert_eq!(storage.remove_orphaned_checkpoints().unwrap(), 40);
+    assert!(storage.orphaned_checkpoints().unwrap().is_empty());
+    assert_eq!(storage.disk_usage().unwrap().index_checkpoints, 30);
+}
+
+#[test]
+fn self_test_passes_on_writable_directory() {
+    let dir = TempDir::new().unwrap();
+    faster_rs::self_test(dir.path().to_str().unwrap()).unwrap();
+    // The directory used by the test is removed again
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn self_test_reports_unusable_directories() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("file");
+    fs::write(&file, b"not a directory").unwrap();
+    let missing = dir.path().join("missing");
+    for path in &[file, missing] {
+        match faster_rs::self_test(path.to_str().unwrap()) {
+            Err(FasterError::UnusableStorage { reason, .. }) => assert!(!reason.is_empty()),
+            _ => panic!("Expected {:?} to be unusable", path),
+        }
+    }
+}
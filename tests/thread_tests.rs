@@ -1,7 +1,7 @@
+#![cfg(feature = "typed")]
 extern crate faster_rs;
 
-use faster_rs::FasterKv;
-use std::sync::mpsc::Receiver;
+use faster_rs::{FasterKv, ReadHandle};
 use std::sync::Arc;
 use std::thread;
 
@@ -44,7 +44,7 @@ fn multi_threaded_test() {
 
     for key in 0..ops {
         let expected_value = initial_value + (modification * num_threads);
-        let (_res, recv): (u8, Receiver<u64>) = store.read(&key, ops + key);
+        let (_res, recv): (u8, ReadHandle<u64>) = store.read(&key, ops + key);
         assert_eq!(recv.recv().unwrap(), expected_value);
     }
     store.complete_pending(true);
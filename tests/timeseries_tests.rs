@@ -0,0 +1,49 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, TimeSeries};
+
+#[test]
+fn timeseries_range_spans_buckets() {
+    let store = FasterKv::default();
+    let series = TimeSeries::new(10);
+
+    for timestamp in &[25, 3, 12, 17, 40] {
+        series.append(&store, "temperature", *timestamp, &(*timestamp * 2), 1);
+    }
+    assert_eq!(
+        series.range(&store, "temperature", 5, 30, 1).unwrap(),
+        vec![(12, 24), (17, 34), (25, 50)]
+    );
+    assert!(series
+        .range(&store, "pressure", 0, 50, 1)
+        .unwrap()
+        .is_empty());
+    assert!(series
+        .range(&store, "temperature", 30, 30, 1)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn timeseries_truncate_drops_old_buckets() {
+    let store = FasterKv::default();
+    let series = TimeSeries::new(10);
+
+    for timestamp in &[1, 11, 21, 31] {
+        series.append(&store, "temperature", *timestamp, &0u64, 1);
+    }
+    series.truncate(&store, "temperature", 25, 1).unwrap();
+    assert_eq!(
+        series.range(&store, "temperature", 0, 40, 1).unwrap(),
+        vec![(21, 0), (31, 0)]
+    );
+
+    // Late points before the cutoff are truncated again
+    series.append(&store, "temperature", 5, &0u64, 1);
+    series.truncate(&store, "temperature", 25, 1).unwrap();
+    assert_eq!(
+        series.range(&store, "temperature", 0, 20, 1).unwrap(),
+        vec![]
+    );
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{status, FasterError, FasterKv};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn ttl_records_readable_until_expiry() {
+    let store = FasterKv::default();
+    let key = String::from("cached");
+
+    let upsert = store.upsert_with_ttl(&key, &String::from("value"), Duration::from_millis(50), 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+    let (res, recv) = store.read_with_ttl::<String, String>(&key, 2);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), "value");
+
+    thread::sleep(Duration::from_millis(100));
+    let (res, recv) = store.read_with_ttl::<String, String>(&key, 3);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv_optional().unwrap().is_none());
+}
+
+#[test]
+fn ttl_rewrite_extends_expiry() {
+    let store = FasterKv::default();
+    store.upsert_with_ttl(&1u64, &1u64, Duration::from_secs(0), 1);
+    let (res, _) = store.read_with_ttl::<u64, u64>(&1, 2);
+    assert_eq!(res, status::NOT_FOUND);
+
+    store.upsert_with_ttl(&1u64, &2u64, Duration::from_secs(60), 3);
+    let (res, recv) = store.read_with_ttl::<u64, u64>(&1, 4);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 2);
+}
+
+#[test]
+fn ttl_read_missing_and_malformed_records() {
+    let store = FasterKv::default();
+    let (res, recv) = store.read_with_ttl::<u64, u64>(&1, 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv_optional().unwrap().is_none());
+
+    // Too short to hold an expiry
+    store.upsert(&2u64, &1u8, 2);
+    let (_, recv) = store.read_with_ttl::<u64, u8>(&2, 3);
+    match recv.recv() {
+        Err(FasterError::Deserialize { bytes, .. }) => assert_eq!(bytes, vec![1]),
+        other => panic!("Unexpected {:?}", other),
+    }
+}
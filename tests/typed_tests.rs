@@ -0,0 +1,86 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{
+    decode_error, status, DecodeError, FasterDeserialize, FasterKv, FasterRmw, FasterSerialize,
+    TypedFasterKv,
+};
+
+#[test]
+fn typed_store_operations() {
+    let store: TypedFasterKv<String, u64> = TypedFasterKv::new(FasterKv::default());
+    let key = String::from("visits");
+
+    let upsert = store.upsert(&key, &1, 1);
+    assert!(upsert == status::OK || upsert == status::PENDING);
+    let rmw = store.rmw(&key, &2, 1);
+    assert!(rmw == status::OK || rmw == status::PENDING);
+
+    let (res, recv) = store.read(&key, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), 3);
+
+    let delete = store.delete(&key, 1);
+    assert!(delete == status::OK || delete == status::PENDING);
+    let (res, recv) = store.read(&key, 1);
+    assert_eq!(res, status::NOT_FOUND);
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn typed_store_shares_underlying_store() {
+    let store: TypedFasterKv<u64, String> = TypedFasterKv::new(FasterKv::default());
+    store.upsert(&1, &String::from("one"), 1);
+
+    let store = store.into_inner();
+    let (res, recv) = store.read::<u64, String>(&1, 1);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), "one");
+}
+
+// Counter stored as its 8 little-endian bytes instead of through serde
+#[derive(Debug, PartialEq)]
+struct Counter(u64);
+
+impl FasterSerialize for Counter {
+    fn faster_serialize(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+}
+
+impl FasterDeserialize for Counter {
+    fn faster_deserialize(bytes: &[u8]) -> Result<Counter, DecodeError> {
+        let mut buf = [0u8; 8];
+        if bytes.len() != buf.len() {
+            return Err(decode_error("A counter has 8 bytes"));
+        }
+        buf.copy_from_slice(bytes);
+        Ok(Counter(u64::from_le_bytes(buf)))
+    }
+}
+
+impl FasterRmw for Counter {
+    fn rmw(&self, modification: Counter) -> Counter {
+        Counter(self.0 + modification.0)
+    }
+}
+
+#[test]
+fn custom_codec_values() {
+    let store = FasterKv::default();
+    store.upsert(&1u64, &Counter(40), 1);
+    store.rmw(&1u64, &Counter(2), 2);
+
+    let (res, recv) = store.read::<u64, Counter>(&1, 3);
+    assert_eq!(res, status::OK);
+    assert_eq!(recv.recv().unwrap(), Counter(42));
+
+    // Stored in the custom encoding
+    let (_, recv) = store.read_raw(&1u64.faster_serialize(), 4);
+    assert_eq!(recv.recv().unwrap(), 42u64.to_le_bytes().to_vec());
+
+    // Values in another encoding fail to decode
+    store.upsert(&2u64, &1u8, 5);
+    let (_, recv) = store.read::<u64, Counter>(&2, 6);
+    assert!(recv.recv().is_err());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::FasterKv;
+
+// Users are keyed by id and stored as (country, age)
+fn by_country(_id: &u64, user: &(String, u64)) -> (String, u64) {
+    (user.0.clone(), user.1)
+}
+
+fn oldest(a: u64, b: u64) -> u64 {
+    a.max(b)
+}
+
+#[test]
+fn view_follows_records_between_groups() {
+    let store = FasterKv::default();
+    assert!(store.create_view("oldest_by_country", by_country, oldest));
+
+    store.upsert(&1u64, &(String::from("se"), 30u64), 1);
+    store.upsert(&2u64, &(String::from("se"), 40u64), 1);
+    store.upsert(&3u64, &(String::from("no"), 50u64), 1);
+    // User 2 moves
+    store.upsert(&2u64, &(String::from("no"), 41u64), 1);
+
+    let view = store.view::<String, u64>("oldest_by_country").unwrap();
+    assert_eq!(view.read(&String::from("se"), 1).unwrap(), Some(30));
+    assert_eq!(view.read(&String::from("no"), 1).unwrap(), Some(50));
+
+    store.delete(&3u64, 1);
+    assert_eq!(view.read(&String::from("no"), 1).unwrap(), Some(41));
+    store.delete(&2u64, 1);
+    assert_eq!(view.read(&String::from("no"), 1).unwrap(), None);
+}
+
+#[test]
+fn view_ignores_records_of_other_types() {
+    let store = FasterKv::default();
+    store.create_view("oldest_by_country", by_country, oldest);
+
+    store.upsert(&String::from("config"), &String::from("se"), 1);
+    store.upsert(&1u64, &7u8, 1);
+    let view = store.view::<String, u64>("oldest_by_country").unwrap();
+    assert_eq!(view.read(&String::from("se"), 1).unwrap(), None);
+}
+
+#[test]
+fn view_lookup_checks_name_and_types() {
+    let store = FasterKv::default();
+    assert!(store.create_view("oldest_by_country", by_country, oldest));
+    assert!(!store.create_view("oldest_by_country", by_country, oldest));
+
+    assert!(store.view::<String, u64>("missing").is_none());
+    assert!(store.view::<u64, u64>("oldest_by_country").is_none());
+    assert!(store.view::<String, u32>("oldest_by_country").is_none());
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "typed")]
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, WindowState};
+
+fn count(count: &mut u64, _value: &String) {
+    *count += 1;
+}
+
+#[test]
+fn windows_aggregate_per_window() {
+    let store = FasterKv::default();
+    let windows = WindowState::new(100, 4, count);
+    let key = String::from("clicks");
+
+    for timestamp in &[0, 50, 99, 100, 250, 399] {
+        windows.add(&store, &key, *timestamp, &String::from("click"), 1);
+    }
+    assert_eq!(
+        windows.windows(&store, &key, 399, 1).unwrap(),
+        vec![(0, 3), (100, 1), (200, 1), (300, 1)]
+    );
+    assert_eq!(
+        windows.windows(&store, &key, 500, 1).unwrap(),
+        vec![(200, 1), (300, 1)]
+    );
+}
+
+#[test]
+fn windows_drop_evicted_values() {
+    let store = FasterKv::default();
+    let windows = WindowState::new(10, 2, count);
+    let key = String::from("clicks");
+
+    windows.add(&store, &key, 25, &String::from("click"), 1);
+    // Window 0 shares its slot with window 2, which is newer
+    windows.add(&store, &key, 5, &String::from("late"), 1);
+    assert_eq!(windows.windows(&store, &key, 25, 1).unwrap(), vec![(20, 1)]);
+}
+
+#[test]
+fn windows_expire_clears_old_windows() {
+    let store = FasterKv::default();
+    let windows = WindowState::new(10, 3, count);
+    let key = String::from("clicks");
+
+    windows.add(&store, &key, 5, &String::from("click"), 1);
+    windows.add(&store, &key, 15, &String::from("click"), 1);
+    windows.expire(&store, &key, 35, 1);
+    assert_eq!(windows.windows(&store, &key, 15, 1).unwrap(), vec![(10, 1)]);
+}
+
+#[test]
+fn windows_of_missing_key_are_empty() {
+    let store = FasterKv::default();
+    let windows = WindowState::new(10, 3, count);
+    assert!(windows
+        .windows(&store, &String::from("missing"), 0, 1)
+        .unwrap()
+        .is_empty());
+}